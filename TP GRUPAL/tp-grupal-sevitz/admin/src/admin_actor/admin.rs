@@ -1,16 +1,26 @@
 use super::admin_to_coord::MakeTrip;
-use crate::admin_actor::admin_to_storage::{MakeUpdateDriver, MakeUpdatePassenger};
+use crate::admin_actor::admin_to_storage::{
+    MakeUpdateDriver, MakeUpdatePassenger, MakeUpdateSession, MakeUpdateSurge,
+};
+use crate::admin_actor::membership::{leave_cluster, spawn_join_task, MakeUpdateMembers};
 use crate::admin_actor::ping::{spawn_ping_task, WhoIsCoordinator};
 use crate::admin_actor::reaper::spawn_reaper_task;
+use crate::admin_actor::trip_queue::spawn_queue_task;
 use crate::coordinator_actor::coordinator::Coordinator;
-use crate::coordinator_actor::coordinator_messages::{HandleTrip, UpdateDrivers, UpdatePassengers};
+use crate::coordinator_actor::coordinator_messages::{
+    HandleTrip, JoinCluster, LeaveCluster, UpdateDrivers, UpdateMembers, UpdatePassengers,
+    UpdateSession, UpdateSurge,
+};
 use crate::elections::election::CoordinatorElection;
 use crate::elections::election_messages::{CoordinatorMessage, ElectionMessage, PingMessage};
 use crate::storage_actor::storage::Storage;
 use crate::utils::admin_errors::AdminError;
 use crate::utils::logs::{log_elections, log_trips};
 use actix::prelude::*;
-use common::messages::{CanAcceptTripResponse, DriverPosition, FinishTrip, RequestTrip};
+use common::cluster::{ClusterConfig, ClusterNode};
+use common::messages::{
+    CanAcceptTripResponse, ClientId, DriverPosition, FinishTrip, RegisterClient, RequestTrip,
+};
 use common::tcp_sender::{TcpMessage, TcpSender};
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -22,13 +32,16 @@ pub type CoordElection = Arc<Addr<CoordinatorElection>>;
 
 /// This actor is responsible for handling the admin connections and business logic.
 /// Handles clients and their requests as well as the communication with the coordinator.
+/// Once a client registers, its persistent id identifies its passenger or driver.
 pub struct Admin {
     pub addr: SocketAddr,
     pub client_addr: SocketAddr,
+    pub client_id: Option<ClientId>,
     pub tcp_sender: Arc<Addr<TcpSender>>,
     pub coordinator_election: CoordElection,
     pub coordinator: Arc<Addr<Coordinator>>,
     pub storage_addr: Arc<Addr<Storage>>,
+    pub config: Arc<ClusterConfig>,
 }
 
 impl Actor for Admin {
@@ -43,6 +56,7 @@ impl Admin {
         coordinator_election: CoordElection,
         coordinator: Arc<Addr<Coordinator>>,
         storage_addr: Arc<Addr<Storage>>,
+        config: Arc<ClusterConfig>,
     ) -> Addr<Self> {
         Admin::create(|ctx| {
             let (r_half, w_half) = split(stream);
@@ -54,46 +68,79 @@ impl Admin {
             Admin {
                 addr,
                 client_addr,
+                client_id: None,
                 tcp_sender,
                 coordinator_election,
                 coordinator,
                 storage_addr,
+                config,
             }
         })
     }
 
-    pub async fn start(addr: SocketAddr, peers: Vec<SocketAddr>) -> Result<(), AdminError> {
-        println!("[{}] Starting admin server", addr);
-        if peers.is_empty() {
+    /// Starts the admin server for a node of the cluster.
+    /// If the node is not in the cluster config, it joins the cluster once it finds the coordinator.
+    pub async fn start(node: ClusterNode, config: ClusterConfig) -> Result<(), AdminError> {
+        let addr = node.addr;
+        println!("[{}] Starting admin server {}", addr, node.id);
+        if config.nodes.is_empty() {
             return Err(AdminError::InvalidPeers("No peers provided".to_string()));
         }
+        let joining = !config.nodes.contains(&node);
 
         let listener = TcpListener::bind(addr)
             .await
             .map_err(|e| AdminError::BindError(format!("Failed to bind to {}: {:?}", addr, e)))?;
 
-        let coordinator = Arc::new(Coordinator::new(addr, peers.clone()));
+        let config = Arc::new(config);
+        let coordinator = Arc::new(Coordinator::new(addr, &config));
 
         let coordinator_election = Arc::new(CoordinatorElection::new(
             addr,
             Arc::clone(&coordinator),
-            Arc::new(peers),
+            Arc::new(config.nodes.clone()),
         ));
 
         let storage_actor = Arc::new(Storage::start()?);
 
-        spawn_ping_task(coordinator_election.clone());
+        spawn_ping_task(coordinator_election.clone(), config.ping_interval);
 
-        spawn_reaper_task(storage_actor.clone(), coordinator.clone());
+        if joining {
+            spawn_join_task(
+                node.clone(),
+                coordinator.clone(),
+                coordinator_election.clone(),
+                config.ping_interval,
+            );
+        }
 
-        accept_connections(
-            listener,
-            addr,
-            coordinator_election,
-            coordinator,
-            storage_actor,
-        )
-        .await
+        spawn_reaper_task(
+            storage_actor.clone(),
+            coordinator.clone(),
+            config.dead_driver_timeout,
+        );
+
+        spawn_queue_task(
+            storage_actor.clone(),
+            coordinator.clone(),
+            coordinator_election.clone(),
+            config.clone(),
+        );
+
+        tokio::select! {
+            result = accept_connections(
+                listener,
+                addr,
+                coordinator_election.clone(),
+                coordinator.clone(),
+                storage_actor,
+                config,
+            ) => result,
+            _ = tokio::signal::ctrl_c() => {
+                leave_cluster(node, coordinator, coordinator_election).await;
+                Ok(())
+            }
+        }
     }
 }
 
@@ -145,9 +192,29 @@ impl StreamHandler<Result<String, std::io::Error>> for Admin {
                     .expect("Failed to send coordinator message");
             }
 
+            // CLUSTER MEMBERSHIP
+            if let Ok(join_cluster) = serde_json::from_str::<JoinCluster>(message) {
+                ctx.address()
+                    .try_send(join_cluster)
+                    .expect("JoinCluster failed");
+            }
+
+            if let Ok(leave_cluster) = serde_json::from_str::<LeaveCluster>(message) {
+                ctx.address()
+                    .try_send(leave_cluster)
+                    .expect("LeaveCluster failed");
+            }
+
+            // CLIENT REGISTRATION
+            if let Ok(register_client) = serde_json::from_str::<RegisterClient>(message) {
+                ctx.address()
+                    .try_send(register_client)
+                    .expect("RegisterClient failed");
+            }
+
             // TRIP REQUEST
             if let Ok(request_trip) = serde_json::from_str::<RequestTrip>(message) {
-                log_trips(format!("[Request trip: {:?}]", self.client_addr));
+                log_trips(format!("[Request trip: {:?}]", self.client_id));
                 ctx.address()
                     .try_send(request_trip)
                     .expect("RequestTrip failed");
@@ -155,7 +222,7 @@ impl StreamHandler<Result<String, std::io::Error>> for Admin {
 
             // DRIVER READY
             if let Ok(driver_ready) = serde_json::from_str::<DriverPosition>(message) {
-                log_trips(format!("[Driver ready: {:?}]", self.client_addr));
+                log_trips(format!("[Driver ready: {:?}]", self.client_id));
                 ctx.address()
                     .try_send(driver_ready)
                     .expect("DriverPosition failed");
@@ -208,6 +275,30 @@ impl StreamHandler<Result<String, std::io::Error>> for Admin {
                     })
                     .expect("MakeUpdateDriver failed to send");
             }
+
+            if let Ok(surge_update) = serde_json::from_str::<UpdateSurge>(message) {
+                ctx.address()
+                    .try_send(MakeUpdateSurge {
+                        upt_msg: surge_update,
+                    })
+                    .expect("MakeUpdateSurge failed to send");
+            }
+
+            if let Ok(session_update) = serde_json::from_str::<UpdateSession>(message) {
+                ctx.address()
+                    .try_send(MakeUpdateSession {
+                        upt_msg: session_update,
+                    })
+                    .expect("MakeUpdateSession failed to send");
+            }
+
+            if let Ok(members_update) = serde_json::from_str::<UpdateMembers>(message) {
+                ctx.address()
+                    .try_send(MakeUpdateMembers {
+                        upt_msg: members_update,
+                    })
+                    .expect("MakeUpdateMembers failed to send");
+            }
         } else {
             println!("[{:?}] Failed to read line {:?}", self.addr, read);
         }
@@ -220,6 +311,7 @@ async fn accept_connections(
     coordinator_election: Arc<Addr<CoordinatorElection>>,
     coordinator: Arc<Addr<Coordinator>>,
     storage_actor: Arc<Addr<Storage>>,
+    config: Arc<ClusterConfig>,
 ) -> Result<(), AdminError> {
     loop {
         match listener.accept().await {
@@ -235,6 +327,7 @@ async fn accept_connections(
                     coordinator_election.clone(),
                     Arc::clone(&coordinator),
                     storage_actor.clone(),
+                    config.clone(),
                 );
             }
             Err(e) => {
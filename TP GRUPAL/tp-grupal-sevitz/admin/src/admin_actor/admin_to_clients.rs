@@ -1,9 +1,11 @@
+use crate::admin_actor::dispatch::{offer_next_driver, send_offer_expired};
+use crate::admin_actor::trip_queue::{dispatch_queued_passenger, leave_queue};
 use crate::admin_actor::{admin::Admin, clients_to_admin::DriverStatus};
-use crate::coordinator_actor::coordinator_messages::{
-    Action, HandleTrip, UpdateDrivers, UpdatePassengers,
-};
+use crate::coordinator_actor::coordinator_messages::{Action, UpdateDrivers, UpdatePassengers};
 use crate::storage_actor::storage::Storage;
-use crate::storage_actor::storage_messages::{GetDriver, GetPassenger, IsFinished, UpdateDriver};
+use crate::storage_actor::storage_messages::{
+    AnswerOffer, GetDriver, GetPassenger, IsFinished, UpdateDriver,
+};
 use crate::utils::logs::log_trips;
 use crate::utils::payment_actions::{get_payment_response, make_payment_done_message};
 use actix::prelude::*;
@@ -29,6 +31,8 @@ impl Handler<FinishTrip> for Admin {
         let destination_pos = msg.destination_pos;
 
         let coord_clone = self.coordinator.clone();
+        let payment_gateway = self.config.payment_gateway;
+        let config = self.config.clone();
 
         Box::pin(
             async move {
@@ -41,8 +45,27 @@ impl Handler<FinishTrip> for Admin {
                     return;
                 }
 
+                // charge the fare that was authorized when the trip was requested
+                let Some(fare) = storage_actor
+                    .send(GetPassenger { id: passenger_id })
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|passenger| passenger.fare)
+                else {
+                    eprintln!(
+                        "Passenger {:?} not found, can't charge the trip",
+                        passenger_id
+                    );
+                    return;
+                };
+
                 let string_passenger_id = format!("{:?}", passenger_id.clone());
-                get_payment_response(make_payment_done_message(string_passenger_id)).await;
+                get_payment_response(
+                    make_payment_done_message(string_passenger_id, fare),
+                    payment_gateway,
+                )
+                .await;
 
                 acknowledge_passenger(storage_actor.clone(), msg.clone()).await;
                 acknowledge_driver(storage_actor.clone(), msg).await;
@@ -71,9 +94,15 @@ impl Handler<FinishTrip> for Admin {
                         passenger: passenger_id,
                         origin: (0.0, 0.0),
                         destination: (20.0, 20.0),
+                        fare: 0.0,
+                        queued_for: None,
                         action: Action::Delete,
                     })
                     .expect("Failed to send UpdatePassengers");
+
+                // The driver is available again, it may take a trip waiting in the queue
+                dispatch_queued_passenger(driver_id, None, storage_actor, coord_clone, config)
+                    .await;
             }
             .into_actor(self),
         )
@@ -121,10 +150,59 @@ impl Handler<CanAcceptTripResponse> for Admin {
         let coord_clone = self.coordinator.clone();
         let storage_actor = self.storage_addr.clone();
         let driver_sender = self.tcp_sender.clone();
-        let driver_id = self.client_addr;
+        let config = self.config.clone();
+        let Some(driver_id) = self.client_id else {
+            log_trips(format!("Client {:?} is not registered", self.client_addr));
+            return Box::pin(fut::ready(()));
+        };
 
         Box::pin(
             async move {
+                // The position doesn't change, but it is replicated along with the new status
+                let Some(driver_position) = storage_actor
+                    .send(GetDriver { id: driver_id })
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|driver| driver.driver_position)
+                else {
+                    eprintln!(
+                        "Driver {:?} not found, ignoring its answer for {:?}",
+                        driver_id, msg.passenger_id_car
+                    );
+                    return;
+                };
+
+                // Only the answer to the pending offer counts, the trip may have been
+                // offered to another driver after this one took too long to answer
+                let was_queued = storage_actor
+                    .send(GetPassenger {
+                        id: msg.passenger_id_car,
+                    })
+                    .await
+                    .ok()
+                    .flatten()
+                    .is_some_and(|passenger| passenger.queued_at.is_some());
+                let is_pending_offer = storage_actor
+                    .send(AnswerOffer {
+                        passenger_id: msg.passenger_id_car,
+                        driver_id,
+                        is_accepted: msg.is_accepted,
+                    })
+                    .await
+                    .unwrap_or(false);
+
+                if !is_pending_offer {
+                    // The offer already expired and the driver was made available again,
+                    // it is told so that it doesn't wait for the trip to start
+                    log_trips(format!(
+                        "Ignoring late answer of driver {:?} for {:?}",
+                        driver_id, msg.passenger_id_car
+                    ));
+                    send_offer_expired(msg.passenger_id_car, &driver_sender);
+                    return;
+                }
+
                 if msg.is_accepted {
                     // Start the trip
                     log_trips(format!(
@@ -166,6 +244,7 @@ impl Handler<CanAcceptTripResponse> for Admin {
                                 .send(UpdateDriver {
                                     driver_id,
                                     passenger_id: Some(msg.passenger_id_car),
+                                    driver_position: None,
                                     time_stamp: Instant::now(),
                                     status: DriverStatus::OnTrip,
                                 })
@@ -175,12 +254,16 @@ impl Handler<CanAcceptTripResponse> for Admin {
                             coord_clone
                                 .try_send(UpdateDrivers {
                                     driver: driver_id,
-                                    position: (0.0, 0.1),
+                                    position: driver_position,
                                     action: Action::Update,
                                     current_passenger_id: Some(msg.passenger_id_car),
                                     status: DriverStatus::OnTrip,
                                 })
                                 .expect("Failed to send UpdateDrivers");
+
+                            if was_queued {
+                                leave_queue(msg.passenger_id_car, storage_actor, coord_clone).await;
+                            }
                         } else {
                             println!(
                                 "Passenger sender not found for passenger {:?}",
@@ -192,7 +275,8 @@ impl Handler<CanAcceptTripResponse> for Admin {
                     storage_actor
                         .send(UpdateDriver {
                             driver_id,
-                            passenger_id: Some(msg.passenger_id_car),
+                            passenger_id: None,
+                            driver_position: None,
                             time_stamp: Instant::now(),
                             status: DriverStatus::Active,
                         })
@@ -202,19 +286,31 @@ impl Handler<CanAcceptTripResponse> for Admin {
                     coord_clone
                         .try_send(UpdateDrivers {
                             driver: driver_id,
-                            position: (0.0, 0.1),
+                            position: driver_position,
                             action: Action::Update,
-                            current_passenger_id: Some(msg.passenger_id_car),
+                            current_passenger_id: None,
                             status: DriverStatus::Active,
                         })
                         .expect("Failed to send UpdateDrivers");
 
-                    // Find another driver
-                    coord_clone
-                        .try_send(HandleTrip {
-                            passenger_id_ht: msg.passenger_id_car,
-                        })
-                        .expect("Failed to send HandleTrip");
+                    // Offer the trip to the next candidate
+                    offer_next_driver(
+                        msg.passenger_id_car,
+                        storage_actor.clone(),
+                        coord_clone.clone(),
+                        config.clone(),
+                    )
+                    .await;
+
+                    // The driver is available again, it may take another trip waiting in the queue
+                    dispatch_queued_passenger(
+                        driver_id,
+                        Some(msg.passenger_id_car),
+                        storage_actor,
+                        coord_clone,
+                        config,
+                    )
+                    .await;
                 }
             }
             .into_actor(self),
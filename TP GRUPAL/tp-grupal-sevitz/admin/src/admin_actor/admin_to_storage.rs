@@ -1,13 +1,16 @@
 use crate::admin_actor::admin::Admin;
 use crate::admin_actor::clients_to_admin::DriverStatus;
-use crate::coordinator_actor::coordinator_messages::{Action, UpdateDrivers, UpdatePassengers};
+use crate::coordinator_actor::coordinator_messages::{
+    Action, UpdateDrivers, UpdatePassengers, UpdateSession, UpdateSurge,
+};
 use crate::storage_actor::storage_messages::{
-    InsertDriver, InsertPassenger, RemoveDriver, RemovePassenger, UpdateDriver,
+    InsertDriver, InsertPassenger, RemoveDriver, RemovePassenger, SetPassengerQueued, SetSession,
+    SetSurge, UpdateDriver,
 };
 use crate::utils::logs::log_trips;
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[derive(Message, Serialize, Deserialize, Debug, Clone)]
 #[rtype(result = "()")]
@@ -23,6 +26,20 @@ pub struct MakeUpdatePassenger {
     pub upt_msg: UpdatePassengers,
 }
 
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to update the surge multiplier of a zone in the storage.
+pub struct MakeUpdateSurge {
+    pub upt_msg: UpdateSurge,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to register the session of a client in the storage.
+pub struct MakeUpdateSession {
+    pub upt_msg: UpdateSession,
+}
+
 impl Handler<MakeUpdateDriver> for Admin {
     type Result = ResponseActFuture<Self, ()>;
 
@@ -71,6 +88,7 @@ impl Handler<MakeUpdateDriver> for Admin {
                         .send(UpdateDriver {
                             driver_id: d_addr,
                             passenger_id: passenger,
+                            driver_position: Some(position),
                             status: driver_status,
                             time_stamp: Instant::now(),
                         })
@@ -103,6 +121,7 @@ impl Handler<MakeUpdatePassenger> for Admin {
                             id: p_addr,
                             passenger_position: passenger_update.upt_msg.origin,
                             passenger_destination: passenger_update.upt_msg.destination,
+                            fare: passenger_update.upt_msg.fare,
                             passenger_sender: None,
                         })
                         .await
@@ -116,7 +135,72 @@ impl Handler<MakeUpdatePassenger> for Admin {
                         .expect("Failed to send GetPassenger to storage");
 
                     log_trips(format!("Removed passenger {:?}", p_addr));
+                } else if action == Action::Update {
+                    storage_actor
+                        .send(SetPassengerQueued {
+                            id: p_addr,
+                            queued_for: passenger_update
+                                .upt_msg
+                                .queued_for
+                                .map(Duration::from_millis),
+                        })
+                        .await
+                        .expect("Failed to send SetPassengerQueued to storage");
+
+                    log_trips(format!("Updated passenger {:?}", p_addr));
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<MakeUpdateSurge> for Admin {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, surge_update: MakeUpdateSurge, _ctx: &mut Self::Context) -> Self::Result {
+        let zone = surge_update.upt_msg.zone;
+        let multiplier = surge_update.upt_msg.multiplier;
+        let storage_actor = self.storage_addr.clone();
+        Box::pin(
+            async move {
+                if let Err(e) = storage_actor.send(SetSurge { zone, multiplier }).await {
+                    log_trips(format!("Failed to send SetSurge to storage: {:?}", e));
+                    return;
                 }
+
+                log_trips(format!(
+                    "Updated surge multiplier of zone {:?} to {}",
+                    zone, multiplier
+                ));
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<MakeUpdateSession> for Admin {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(
+        &mut self,
+        session_update: MakeUpdateSession,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let client_id = session_update.upt_msg.client;
+        let session_token = session_update.upt_msg.session_token;
+        let storage_actor = self.storage_addr.clone();
+        Box::pin(
+            async move {
+                storage_actor
+                    .send(SetSession {
+                        client_id,
+                        session_token,
+                    })
+                    .await
+                    .expect("Failed to send SetSession to storage");
+
+                log_trips(format!("Registered session of client {}", client_id));
             }
             .into_actor(self),
         )
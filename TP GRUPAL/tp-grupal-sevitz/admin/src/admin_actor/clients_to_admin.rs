@@ -1,13 +1,23 @@
 use crate::admin_actor::admin::Admin;
-use crate::coordinator_actor::coordinator_messages::{Action, UpdateDrivers, UpdatePassengers};
+use crate::admin_actor::trip_queue::dispatch_queued_passenger;
+use crate::coordinator_actor::coordinator_messages::{
+    Action, GetMembers, UpdateDrivers, UpdatePassengers, UpdateSession, UpdateSurge,
+};
 use crate::elections::election_messages::AmICoordinator;
-use crate::storage_actor::storage_messages::{InsertDriver, InsertPassenger};
+use crate::storage_actor::storage_messages::{
+    ComputeSurge, InsertDriver, InsertPassenger, RegisterSession, SessionStatus,
+};
+use crate::utils::fare::trip_fare;
 use crate::utils::logs::log_trips;
 use crate::utils::payment_actions::{get_payment_response, make_payment_check_message};
+use crate::utils::surge::zone_of;
 use actix::prelude::*;
-use common::messages::{AuthConfirmation, DriverPosition, RequestTrip};
+use common::messages::{
+    AuthConfirmation, ClientId, DriverPosition, RegisterClient, RegisterClientResponse,
+    RequestTrip, TripFare,
+};
+use common::tcp_sender::TcpMessage;
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Message, PartialEq)]
 #[rtype(result = "()")]
@@ -25,8 +35,90 @@ pub enum DriverStatus {
 #[rtype(result = "()")]
 /// Message to inform the Admin that a trip has finished.
 pub struct TripFinished {
-    pub passenger_id_tf: SocketAddr,
-    pub driver_id_tf: SocketAddr,
+    pub passenger_id_tf: ClientId,
+    pub driver_id_tf: ClientId,
+}
+
+impl Handler<RegisterClient> for Admin {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: RegisterClient, _ctx: &mut Self::Context) -> Self::Result {
+        log_trips(format!("| ----- Register Client {} ----- |", msg.client_id));
+
+        let tcp_sender = self.tcp_sender.clone();
+        let storage_actor = self.storage_addr.clone();
+        let cord_election_clone = self.coordinator_election.clone();
+        let cord_clone = self.coordinator.clone();
+
+        Box::pin(
+            async move {
+                // only the coordinator registers clients, the rest reject them
+                // so they ask again for the coordinator instead of waiting for a response
+                if !cord_election_clone
+                    .send(AmICoordinator)
+                    .await
+                    .unwrap_or(false)
+                {
+                    log_trips(format!(
+                        "Rejecting client {}, not the coordinator",
+                        msg.client_id
+                    ));
+                    return (SessionStatus::Rejected, Vec::new());
+                }
+
+                // binds the client's passenger or driver (if it already has one) to this connection
+                let status = storage_actor
+                    .send(RegisterSession {
+                        client_id: msg.client_id,
+                        session_token: msg.session_token,
+                        sender: tcp_sender,
+                    })
+                    .await
+                    .unwrap_or(SessionStatus::Rejected);
+
+                if status == SessionStatus::New {
+                    if let Err(e) = cord_clone
+                        .send(UpdateSession {
+                            client: msg.client_id,
+                            session_token: msg.session_token,
+                        })
+                        .await
+                    {
+                        log_trips(format!("Failed to update sessions: {:?}", e));
+                    }
+                }
+
+                let admins = cord_clone
+                    .send(GetMembers)
+                    .await
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|node| node.addr)
+                    .collect();
+                (status, admins)
+            }
+            .into_actor(self)
+            .map(move |(status, admins), admin, _| {
+                if status != SessionStatus::Rejected {
+                    admin.client_id = Some(msg.client_id);
+                }
+
+                let response = RegisterClientResponse {
+                    is_accepted: status != SessionStatus::Rejected,
+                    resumed: status == SessionStatus::Resumed,
+                    admins,
+                };
+                match serde_json::to_string(&response) {
+                    Ok(json_string) => {
+                        if let Err(e) = admin.tcp_sender.try_send(TcpMessage(json_string)) {
+                            log_trips(format!("Failed to send RegisterClientResponse: {:?}", e));
+                        }
+                    }
+                    Err(err) => eprintln!("Error serializing RegisterClientResponse: {}", err),
+                }
+            }),
+        )
+    }
 }
 
 impl Handler<RequestTrip> for Admin {
@@ -36,12 +128,16 @@ impl Handler<RequestTrip> for Admin {
         log_trips("| ----- Request Trip ----- | ".to_string());
 
         let tcp_sender = self.tcp_sender.clone();
-        let client_addr = self.client_addr;
+        let Some(client_id) = self.client_id else {
+            log_trips(format!("Client {:?} is not registered", self.client_addr));
+            return Box::pin(fut::ready(()));
+        };
         let storage_actor = self.storage_addr.clone();
 
         let cord_election_clone = self.coordinator_election.clone();
         let cord_clone = self.coordinator.clone();
         let adress = ctx.address();
+        let payment_gateway = self.config.payment_gateway;
 
         Box::pin(
             async move {
@@ -50,21 +146,47 @@ impl Handler<RequestTrip> for Admin {
                     .await
                     .unwrap_or(false)
                 {
+                    // the surge multiplier is computed by the coordinator when quoting and replicated
+                    // when it changes, so every admin keeps the same multiplier for the zone
+                    let zone = zone_of(msg.origin);
+                    let (surge_multiplier, changed) = storage_actor
+                        .send(ComputeSurge {
+                            zone,
+                            passenger_id: client_id,
+                        })
+                        .await
+                        .unwrap_or((1.0, false));
+                    if changed {
+                        if let Err(e) = cord_clone
+                            .send(UpdateSurge {
+                                zone,
+                                multiplier: surge_multiplier,
+                            })
+                            .await
+                        {
+                            log_trips(format!("Failed to update surge: {:?}", e));
+                        }
+                    }
+
+                    let fare = trip_fare(msg.origin, msg.destination, surge_multiplier);
                     storage_actor
                         .send(InsertPassenger {
-                            id: client_addr,
+                            id: client_id,
                             passenger_position: msg.origin,
                             passenger_destination: msg.destination,
-                            passenger_sender: Some(tcp_sender),
+                            fare,
+                            passenger_sender: Some(tcp_sender.clone()),
                         })
                         .await
                         .unwrap();
 
                     if let Err(e) = cord_clone
                         .send(UpdatePassengers {
-                            passenger: client_addr,
+                            passenger: client_id,
                             origin: msg.origin,
                             destination: msg.destination,
+                            fare,
+                            queued_for: None,
                             action: Action::Insert,
                         })
                         .await
@@ -72,13 +194,34 @@ impl Handler<RequestTrip> for Admin {
                         log_trips(format!("Failed to update passengers: {:?}", e));
                     }
 
+                    // quote the fare before authorizing it, the same amount is charged when the trip finishes
+                    log_trips(format!(
+                        "Trip fare for {:?} is {:.2} (surge x{})",
+                        client_id, fare, surge_multiplier
+                    ));
+                    match serde_json::to_string(&TripFare {
+                        fare,
+                        surge_multiplier,
+                    }) {
+                        Ok(json_string) => {
+                            if let Err(e) = tcp_sender.send(TcpMessage(json_string)).await {
+                                log_trips(format!("Failed to send TripFare: {:?}", e));
+                            }
+                        }
+                        Err(err) => eprintln!("Error serializing TripFare: {}", err),
+                    }
+
                     // check payment request
-                    let passenger_id = format!("{:?}", client_addr.clone());
-                    let auth = get_payment_response(make_payment_check_message(passenger_id)).await;
+                    let passenger_id = client_id.to_string();
+                    let auth = get_payment_response(
+                        make_payment_check_message(passenger_id, fare),
+                        payment_gateway,
+                    )
+                    .await;
                     log_trips(format!("Payment response is authorized: {:?}", auth));
                     adress
                         .try_send(AuthConfirmation {
-                            passenger_id_ac: client_addr,
+                            passenger_id_ac: client_id,
                             is_authorized: auth,
                         })
                         .expect("failed to send auth confirmation");
@@ -97,9 +240,13 @@ impl Handler<DriverPosition> for Admin {
 
         let cord_election_clone = self.coordinator_election.clone();
         let cord_clone = self.coordinator.clone();
+        let config = self.config.clone();
 
         let tcp_sender = self.tcp_sender.clone();
-        let client_addr = self.client_addr;
+        let Some(client_id) = self.client_id else {
+            log_trips(format!("Client {:?} is not registered", self.client_addr));
+            return Box::pin(fut::ready(()));
+        };
         let storage_actor = self.storage_addr.clone();
 
         Box::pin(
@@ -111,7 +258,7 @@ impl Handler<DriverPosition> for Admin {
                 {
                     storage_actor
                         .send(InsertDriver {
-                            id: client_addr,
+                            id: client_id,
                             current_passenger_id: None,
                             driver_position: msg.position,
                             driver_sender: Some(tcp_sender),
@@ -123,7 +270,7 @@ impl Handler<DriverPosition> for Admin {
 
                     if let Err(e) = cord_clone
                         .send(UpdateDrivers {
-                            driver: client_addr,
+                            driver: client_id,
                             position: msg.position,
                             action: Action::Insert,
                             current_passenger_id: None,
@@ -133,6 +280,10 @@ impl Handler<DriverPosition> for Admin {
                     {
                         log_trips(format!("Failed to update drivers: {:?}", e));
                     }
+
+                    // A new driver is available, it may take a trip waiting in the queue
+                    dispatch_queued_passenger(client_id, None, storage_actor, cord_clone, config)
+                        .await;
                 }
             }
             .into_actor(self),
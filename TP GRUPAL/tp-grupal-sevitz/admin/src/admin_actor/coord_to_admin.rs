@@ -2,11 +2,12 @@ use crate::admin_actor::admin::Admin;
 use crate::admin_actor::admin_to_coord::MakeTrip;
 use crate::coordinator_actor::coordinator_messages::HandleTrip;
 use crate::elections::election_messages::GetCoordAddr;
-use crate::storage_actor::storage_messages::GetNearestDriver;
-use crate::utils::consts::MAX_RETRIES;
+use crate::storage_actor::storage_messages::{GetNearestDriver, GetPassenger};
+use crate::utils::consts::MAX_DRIVER_CANDIDATES;
 use crate::utils::logs::log_trips;
 use actix::prelude::*;
 use actix::Message;
+use common::messages::ClientId;
 use common::tcp_sender::TcpMessage;
 use std::net::SocketAddr;
 use tokio::io::{split, AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -17,7 +18,7 @@ use tokio::time::{timeout, Duration};
 #[rtype(result = "()")]
 /// Message to request a trip from a Passenger to the Admin
 pub struct FindNearestDriver {
-    pub passenger_addr: SocketAddr,
+    pub passenger_id: ClientId,
 }
 
 impl Handler<HandleTrip> for Admin {
@@ -31,7 +32,7 @@ impl Handler<HandleTrip> for Admin {
         Box::pin(
             async move {
                 let find_msg = FindNearestDriver {
-                    passenger_addr: msg.passenger_id_ht,
+                    passenger_id: msg.passenger_id_ht,
                 };
 
                 actor_addr
@@ -48,30 +49,44 @@ impl Handler<FindNearestDriver> for Admin {
 
     fn handle(&mut self, _msg: FindNearestDriver, ctx: &mut Self::Context) -> Self::Result {
         let coord_elect_clone = self.coordinator_election.clone();
-        let current_passenger = _msg.passenger_addr;
+        let current_passenger = _msg.passenger_id;
         let storage_actor = self.storage_addr.clone();
         let self_addr = ctx.address();
+        let max_retries = self.config.max_retries;
 
         Box::pin(
             async move {
                 log_trips("| ----- Find Nearest Driver ----- |".to_string());
 
-                let mut make_trip = MakeTrip {
-                    passenger_id_mt: current_passenger,
-                    driver_id_mt: SocketAddr::new([0, 0, 0, 0].into(), 0),
+                let pickup = storage_actor
+                    .send(GetPassenger {
+                        id: current_passenger,
+                    })
+                    .await
+                    .expect("Failed to get passenger")
+                    .map(|passenger| passenger.passenger_position);
+
+                let candidates = match pickup {
+                    Some(pickup) => storage_actor
+                        .send(GetNearestDriver {
+                            pickup,
+                            max_candidates: MAX_DRIVER_CANDIDATES,
+                        })
+                        .await
+                        .expect("Failed to get nearest driver"),
+                    None => {
+                        log_trips(format!("Passenger {:?} not found", current_passenger));
+                        Vec::new()
+                    }
                 };
 
-                if let Some(nearest_driver) = storage_actor
-                    .send(GetNearestDriver {})
-                    .await
-                    .expect("Failed to get nearest driver")
-                {
+                if let Some(nearest_driver) = candidates.first() {
                     log_trips(format!("Found a Driver!: {:?}", nearest_driver));
-                    make_trip = MakeTrip {
-                        passenger_id_mt: current_passenger,
-                        driver_id_mt: nearest_driver,
-                    };
                 }
+                let make_trip = MakeTrip {
+                    passenger_id_mt: current_passenger,
+                    candidates_mt: candidates,
+                };
 
                 // Send to coordinator
                 let coord_addr = coord_elect_clone
@@ -80,7 +95,7 @@ impl Handler<FindNearestDriver> for Admin {
                     .expect("Failed to get coordinator address");
 
                 if let Some(coord_addr) = coord_addr {
-                    send_trip_to_coordinator(make_trip, coord_addr, self_addr).await;
+                    send_trip_to_coordinator(make_trip, coord_addr, max_retries, self_addr).await;
                 } else {
                     log_trips("Coordinator address not found".to_string());
                 }
@@ -93,6 +108,7 @@ impl Handler<FindNearestDriver> for Admin {
 async fn send_trip_to_coordinator(
     make_trip: MakeTrip,
     coordinator_addr: SocketAddr,
+    max_retries: u8,
     admin_addr: Addr<Admin>,
 ) {
     log_trips(format!(
@@ -102,7 +118,7 @@ async fn send_trip_to_coordinator(
     let mut got_res = false;
     let mut got_ack = false;
 
-    for _ in 0..MAX_RETRIES {
+    for _ in 0..max_retries {
         let con = TcpStream::connect(coordinator_addr).await;
         match con {
             Ok(s) => {
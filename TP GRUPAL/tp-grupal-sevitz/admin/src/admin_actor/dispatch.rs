@@ -0,0 +1,102 @@
+use crate::admin_actor::trip_queue::enqueue_passenger;
+use crate::coordinator_actor::coordinator::Coordinator;
+use crate::storage_actor::storage::Storage;
+use crate::storage_actor::storage_messages::{DispatchOffer, ExpireOffer, NextDispatchOffer};
+use crate::utils::logs::log_trips;
+use actix::Addr;
+use common::cluster::ClusterConfig;
+use common::messages::{CanAcceptTrip, ClientId, OfferExpired};
+use common::tcp_sender::{TcpMessage, TcpSender};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Offers the trip of the passenger to the next candidate driver of its dispatch.
+/// If the driver doesn't answer within `offer_timeout` seconds the trip is offered to the following one,
+/// and once every candidate was tried (or the dispatch deadline passed) the passenger waits in the trip queue.
+pub fn offer_next_driver(
+    passenger_id: ClientId,
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    config: Arc<ClusterConfig>,
+) -> BoxFuture<'static, ()> {
+    async move {
+        let offer = storage_actor
+            .send(NextDispatchOffer {
+                passenger_id,
+                dispatch_timeout: Duration::from_secs(config.dispatch_timeout),
+            })
+            .await
+            .unwrap_or(DispatchOffer::Cancelled);
+
+        match offer {
+            DispatchOffer::Offer {
+                driver_id,
+                driver_sender,
+            } => {
+                log_trips(format!(
+                    "Offering trip of {:?} to driver {:?}",
+                    passenger_id, driver_id
+                ));
+                let can_accept_trip = CanAcceptTrip {
+                    passenger_id_ca: passenger_id,
+                };
+                match serde_json::to_string(&can_accept_trip) {
+                    Ok(json_string) => {
+                        if let Err(err) = driver_sender.try_send(TcpMessage(json_string)) {
+                            eprintln!("Failed to send CanAcceptTrip to {:?}: {}", driver_id, err);
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Error serializing CanAcceptTrip message: {}", err);
+                    }
+                }
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(config.offer_timeout)).await;
+                    let expired = storage_actor
+                        .send(ExpireOffer {
+                            passenger_id,
+                            driver_id,
+                        })
+                        .await
+                        .unwrap_or(false);
+                    if expired {
+                        log_trips(format!(
+                            "Driver {:?} didn't answer the trip of {:?} in time",
+                            driver_id, passenger_id
+                        ));
+                        send_offer_expired(passenger_id, &driver_sender);
+                        offer_next_driver(passenger_id, storage_actor, coord_addr, config).await;
+                    }
+                });
+            }
+            DispatchOffer::Exhausted => {
+                enqueue_passenger(passenger_id, storage_actor, coord_addr).await;
+            }
+            DispatchOffer::Cancelled => {
+                log_trips(format!(
+                    "Trip of {:?} is no longer dispatched",
+                    passenger_id
+                ));
+            }
+        }
+    }
+    .boxed()
+}
+
+/// Tells the driver that the offer of the trip of the passenger is no longer valid,
+/// so it doesn't wait for the trip to start if it answers late.
+pub fn send_offer_expired(passenger_id: ClientId, driver_sender: &Addr<TcpSender>) {
+    match serde_json::to_string(&OfferExpired {
+        passenger_id_oe: passenger_id,
+    }) {
+        Ok(json_string) => {
+            if let Err(err) = driver_sender.try_send(TcpMessage(json_string)) {
+                eprintln!("Failed to send OfferExpired: {}", err);
+            }
+        }
+        Err(err) => eprintln!("Error serializing OfferExpired message: {}", err),
+    }
+}
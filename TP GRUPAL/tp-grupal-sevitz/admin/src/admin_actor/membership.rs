@@ -0,0 +1,285 @@
+use crate::admin_actor::admin::{Admin, CoordElection};
+use crate::coordinator_actor::coordinator::Coordinator;
+use crate::coordinator_actor::coordinator_messages::{
+    AddMember, ConnectNewPeer, JoinCluster, LeaveCluster, RemoveMember, SetMembers, UpdateMembers,
+};
+use crate::elections::election_messages::{AmICoordinator, GetCoordAddr};
+use crate::utils::logs::log_elections;
+use actix::prelude::*;
+use common::cluster::{ClusterNode, NodeId};
+use common::tcp_sender::TcpMessage;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{split, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Internal message to apply the cluster members broadcasted by the Coordinator
+pub struct MakeUpdateMembers {
+    pub upt_msg: UpdateMembers,
+}
+
+impl Handler<JoinCluster> for Admin {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: JoinCluster, _ctx: &mut Self::Context) -> Self::Result {
+        let coordinator = self.coordinator.clone();
+        let election = self.coordinator_election.clone();
+        let tcp_sender = self.tcp_sender.clone();
+
+        Box::pin(
+            async move {
+                if !election.send(AmICoordinator).await.unwrap_or(false) {
+                    log_elections(format!(
+                        "Ignoring join of {:?}, not the coordinator",
+                        msg.joining_node
+                    ));
+                    return;
+                }
+
+                let joining_addr = msg.joining_node.addr;
+                let Some(members) = add_member(msg.joining_node, &coordinator, &election).await
+                else {
+                    return;
+                };
+
+                // the new admin may not be a peer of the coordinator yet, so it gets the members directly
+                match serde_json::to_string(&UpdateMembers { members }) {
+                    Ok(serialized) => {
+                        if let Err(e) = tcp_sender.try_send(TcpMessage(serialized)) {
+                            log_elections(format!("Failed to send UpdateMembers: {:?}", e));
+                        }
+                    }
+                    Err(e) => eprintln!("Error serializing UpdateMembers: {}", e),
+                }
+
+                if let Err(e) = coordinator.try_send(ConnectNewPeer {
+                    new_peer: joining_addr,
+                }) {
+                    log_elections(format!("Failed to send ConnectNewPeer: {:?}", e));
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<LeaveCluster> for Admin {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: LeaveCluster, _ctx: &mut Self::Context) -> Self::Result {
+        let coordinator = self.coordinator.clone();
+        let election = self.coordinator_election.clone();
+        let tcp_sender = self.tcp_sender.clone();
+
+        Box::pin(
+            async move {
+                if !election.send(AmICoordinator).await.unwrap_or(false) {
+                    log_elections(format!(
+                        "Ignoring leave of {:?}, not the coordinator",
+                        msg.leaving_node
+                    ));
+                    return;
+                }
+
+                remove_member(msg.leaving_node, &coordinator, &election).await;
+
+                if let Err(e) = tcp_sender.try_send(TcpMessage("Ack".to_string())) {
+                    log_elections(format!("Failed to send Ack: {:?}", e));
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<MakeUpdateMembers> for Admin {
+    type Result = ();
+
+    fn handle(&mut self, msg: MakeUpdateMembers, _ctx: &mut Self::Context) -> Self::Result {
+        log_elections(format!(
+            "[{:?}] Cluster members: {:?}",
+            self.addr, msg.upt_msg.members
+        ));
+        set_members(
+            msg.upt_msg.members,
+            &self.coordinator,
+            &self.coordinator_election,
+        );
+    }
+}
+
+/// Adds a member to the cluster and broadcasts the new members, only called on the coordinator.
+/// Returns the new members, or None if the Coordinator actor could not add it.
+async fn add_member(
+    node: ClusterNode,
+    coordinator: &Arc<Addr<Coordinator>>,
+    election: &CoordElection,
+) -> Option<Vec<ClusterNode>> {
+    let members = match coordinator.send(AddMember { node }).await {
+        Ok(members) => members,
+        Err(e) => {
+            log_elections(format!("Failed to add member: {:?}", e));
+            return None;
+        }
+    };
+    broadcast_members(members.clone(), coordinator, election).await;
+    Some(members)
+}
+
+/// Removes a member from the cluster and broadcasts the new members, only called on the coordinator
+async fn remove_member(
+    node_id: NodeId,
+    coordinator: &Arc<Addr<Coordinator>>,
+    election: &CoordElection,
+) {
+    let members = match coordinator.send(RemoveMember { node_id }).await {
+        Ok(members) => members,
+        Err(e) => {
+            log_elections(format!("Failed to remove member: {:?}", e));
+            return;
+        }
+    };
+    broadcast_members(members, coordinator, election).await;
+}
+
+async fn broadcast_members(
+    members: Vec<ClusterNode>,
+    coordinator: &Arc<Addr<Coordinator>>,
+    election: &CoordElection,
+) {
+    log_elections(format!("Cluster members: {:?}", members));
+    if let Err(e) = election.try_send(SetMembers {
+        members: members.clone(),
+    }) {
+        log_elections(format!("Failed to set members: {:?}", e));
+    }
+    if let Err(e) = coordinator.send(UpdateMembers { members }).await {
+        log_elections(format!("Failed to broadcast members: {:?}", e));
+    }
+}
+
+fn set_members(
+    members: Vec<ClusterNode>,
+    coordinator: &Arc<Addr<Coordinator>>,
+    election: &CoordElection,
+) {
+    if let Err(e) = coordinator.try_send(SetMembers {
+        members: members.clone(),
+    }) {
+        log_elections(format!("Failed to set members: {:?}", e));
+    }
+    if let Err(e) = election.try_send(SetMembers { members }) {
+        log_elections(format!("Failed to set members: {:?}", e));
+    }
+}
+
+/// Joins the cluster as a new admin, once the coordinator is known.
+/// If no admin answered, this admin is the coordinator and the cluster only has itself as new member.
+pub fn spawn_join_task(
+    node: ClusterNode,
+    coordinator: Arc<Addr<Coordinator>>,
+    election: CoordElection,
+    retry_interval: u64,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(retry_interval)).await;
+
+            let coord_addr = match election.send(GetCoordAddr).await {
+                Ok(Some(coord_addr)) => coord_addr,
+                _ => continue,
+            };
+
+            if coord_addr == node.addr {
+                if add_member(node.clone(), &coordinator, &election)
+                    .await
+                    .is_some()
+                {
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(members) = request_join(coord_addr, node.clone()).await {
+                log_elections(format!("[{:?}] Joined the cluster", node.addr));
+                set_members(members, &coordinator, &election);
+                break;
+            }
+        }
+    });
+}
+
+/// Sends JoinCluster to the coordinator and waits for the new cluster members
+async fn request_join(coord_addr: SocketAddr, node: ClusterNode) -> Option<Vec<ClusterNode>> {
+    let stream = TcpStream::connect(coord_addr).await.ok()?;
+    let (reader, mut writer) = split(stream);
+
+    let join = serde_json::to_string(&JoinCluster { joining_node: node }).ok()?;
+    if let Err(e) = writer.write_all(format!("{}\n", join).as_bytes()).await {
+        eprintln!("Error writing JoinCluster message: {}", e);
+        return None;
+    }
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    match timeout(Duration::from_secs(3), reader.read_line(&mut line)).await {
+        Ok(Ok(_)) => serde_json::from_str::<UpdateMembers>(&line)
+            .ok()
+            .map(|update| update.members),
+        _ => {
+            log_elections("Timeout joining the cluster".to_string());
+            None
+        }
+    }
+}
+
+/// Leaves the cluster before shutting down the admin.
+/// The coordinator removes the admin from the members, if the admin is the coordinator
+/// it broadcasts its own removal and the rest elect a new coordinator once it stops answering pings.
+pub async fn leave_cluster(
+    node: ClusterNode,
+    coordinator: Arc<Addr<Coordinator>>,
+    election: CoordElection,
+) {
+    println!("[{}] Leaving the cluster", node.addr);
+
+    let coord_addr = match election.send(GetCoordAddr).await {
+        Ok(Some(coord_addr)) => coord_addr,
+        _ => return,
+    };
+
+    if coord_addr == node.addr {
+        remove_member(node.id, &coordinator, &election).await;
+        // give the tcp senders time to write the update before exiting
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        return;
+    }
+
+    let Ok(stream) = TcpStream::connect(coord_addr).await else {
+        println!("[{}] Could not reach the coordinator to leave", node.addr);
+        return;
+    };
+    let (reader, mut writer) = split(stream);
+
+    let Ok(leave) = serde_json::to_string(&LeaveCluster {
+        leaving_node: node.id,
+    }) else {
+        return;
+    };
+    if let Err(e) = writer.write_all(format!("{}\n", leave).as_bytes()).await {
+        eprintln!("Error writing LeaveCluster message: {}", e);
+        return;
+    }
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    if timeout(Duration::from_secs(3), reader.read_line(&mut line))
+        .await
+        .is_err()
+    {
+        println!("[{}] Timeout leaving the cluster", node.addr);
+    }
+}
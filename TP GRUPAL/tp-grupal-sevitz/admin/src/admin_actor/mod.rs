@@ -4,5 +4,8 @@ pub mod admin_to_coord;
 pub mod admin_to_storage;
 pub mod clients_to_admin;
 pub mod coord_to_admin;
+pub mod dispatch;
+pub mod membership;
 pub mod ping;
 pub mod reaper;
+pub mod trip_queue;
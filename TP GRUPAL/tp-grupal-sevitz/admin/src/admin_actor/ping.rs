@@ -3,7 +3,6 @@ use crate::coordinator_actor::coordinator_messages::ConnectNewPeer;
 use crate::elections::election::CoordinatorElection;
 use crate::elections::election_messages::GetCoordAddr;
 use crate::elections::election_messages::{PingCoordinator, PingMessage};
-use crate::utils::consts::PING_INTERVAL;
 use crate::utils::logs::log_elections;
 use actix::prelude::*;
 use common::messages::WhoIsCoordinatorResponse;
@@ -76,10 +75,10 @@ impl Handler<WhoIsCoordinator> for Admin {
     }
 }
 
-pub fn spawn_ping_task(coordinator_election: Arc<Addr<CoordinatorElection>>) {
+pub fn spawn_ping_task(coordinator_election: Arc<Addr<CoordinatorElection>>, ping_interval: u64) {
     tokio::spawn(async move {
         loop {
-            tokio::time::sleep(Duration::from_secs(PING_INTERVAL)).await;
+            tokio::time::sleep(Duration::from_secs(ping_interval)).await;
             if let Err(e) = coordinator_election.try_send(PingCoordinator) {
                 log_elections(format!("Failed to send ping: {:?}", e));
             }
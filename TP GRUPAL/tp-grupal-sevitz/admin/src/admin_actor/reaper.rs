@@ -11,8 +11,15 @@ use std::time::Duration;
 
 /// Function to remove dead drivers from the system.
 /// (Driver who never responded if they can accept a trip)
-async fn reap_dead_drivers(storage_actor: Arc<Addr<Storage>>, coord_addr: Arc<Addr<Coordinator>>) {
-    let reaped_drivers = storage_actor.send(ReapDeadDrivers).await.unwrap();
+async fn reap_dead_drivers(
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    timeout: Duration,
+) {
+    let reaped_drivers = storage_actor
+        .send(ReapDeadDrivers { timeout })
+        .await
+        .unwrap();
 
     if !reaped_drivers.is_empty() {
         println!("Reaped {} dead drivers", reaped_drivers.len());
@@ -28,16 +35,19 @@ async fn reap_dead_drivers(storage_actor: Arc<Addr<Storage>>, coord_addr: Arc<Ad
                 })
                 .expect("Failed to send UpdateDrivers");
 
-            coord_addr
-                .try_send(UpdatePassengers {
-                    action: Action::Delete,
-                    passenger: dead_driver
-                        .passenger_id
-                        .unwrap_or_else(|| "0.0.0.0:0".parse().unwrap()),
-                    origin: (0.0, 0.0),
-                    destination: (0.0, 0.0),
-                })
-                .expect("Failed to send UpdateDrivers");
+            // Drivers whose trip offer expired are no longer linked to a passenger
+            if let Some(passenger_id) = dead_driver.passenger_id {
+                coord_addr
+                    .try_send(UpdatePassengers {
+                        action: Action::Delete,
+                        passenger: passenger_id,
+                        origin: (0.0, 0.0),
+                        destination: (0.0, 0.0),
+                        fare: 0.0,
+                        queued_for: None,
+                    })
+                    .expect("Failed to send UpdateDrivers");
+            }
 
             let tcp_message = TcpMessage(
                 serde_json::to_string(&RejectTrip {
@@ -57,11 +67,20 @@ async fn reap_dead_drivers(storage_actor: Arc<Addr<Storage>>, coord_addr: Arc<Ad
     }
 }
 
-pub fn spawn_reaper_task(storage_actor: Arc<Addr<Storage>>, coord_addr: Arc<Addr<Coordinator>>) {
+pub fn spawn_reaper_task(
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    dead_driver_timeout: u64,
+) {
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(Duration::from_secs(5)).await;
-            reap_dead_drivers(storage_actor.clone(), coord_addr.clone()).await;
+            reap_dead_drivers(
+                storage_actor.clone(),
+                coord_addr.clone(),
+                Duration::from_secs(dead_driver_timeout),
+            )
+            .await;
         }
     });
 }
@@ -0,0 +1,227 @@
+use crate::admin_actor::dispatch::offer_next_driver;
+use crate::coordinator_actor::coordinator::Coordinator;
+use crate::coordinator_actor::coordinator_messages::{Action, UpdatePassengers};
+use crate::elections::election::CoordinatorElection;
+use crate::elections::election_messages::AmICoordinator;
+use crate::storage_actor::storage::Storage;
+use crate::storage_actor::storage_messages::{
+    EnqueuePassenger, ExpireQueuedPassengers, GetNearestDriver, GetPassenger, GetQueuedPassengers,
+    StartDispatch, TakeQueuedPassenger,
+};
+use crate::utils::consts::MAX_DRIVER_CANDIDATES;
+use crate::utils::logs::log_trips;
+use actix::Addr;
+use common::cluster::ClusterConfig;
+use common::messages::{ClientId, RejectTrip, TripQueued};
+use common::tcp_sender::TcpMessage;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Puts a passenger without an available driver in the trip queue,
+/// telling it its position and replicating it to the other admins.
+pub async fn enqueue_passenger(
+    passenger_id: ClientId,
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+) {
+    let queued = storage_actor
+        .send(EnqueuePassenger { id: passenger_id })
+        .await
+        .ok()
+        .flatten();
+    let Some((position, queued_for)) = queued else {
+        log_trips(format!("Passenger {:?} not found", passenger_id));
+        return;
+    };
+
+    log_trips(format!(
+        "No drivers available for {:?}, waiting in position {}",
+        passenger_id, position
+    ));
+    replicate_queued(passenger_id, Some(queued_for), &coord_addr);
+    notify_queue_positions(storage_actor).await;
+}
+
+/// Offers the trip of a queued passenger to a driver that became available, following the arrival order,
+/// skipping the passenger whose trip the driver has just rejected.
+/// If the driver rejects it, the trip is offered to the rest of the active drivers nearest to the passenger
+/// before it goes back to the queue.
+pub async fn dispatch_queued_passenger(
+    driver_id: ClientId,
+    rejected: Option<ClientId>,
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    config: Arc<ClusterConfig>,
+) {
+    let passenger_id = storage_actor
+        .send(TakeQueuedPassenger {
+            driver_id,
+            rejected,
+        })
+        .await
+        .ok()
+        .flatten();
+
+    if let Some(passenger_id) = passenger_id {
+        log_trips(format!(
+            "Dispatching queued passenger {:?} to driver {:?}",
+            passenger_id, driver_id
+        ));
+        let pickup = storage_actor
+            .send(GetPassenger { id: passenger_id })
+            .await
+            .ok()
+            .flatten()
+            .map(|passenger| passenger.passenger_position);
+        let nearest_drivers = match pickup {
+            Some(pickup) => storage_actor
+                .send(GetNearestDriver {
+                    pickup,
+                    max_candidates: MAX_DRIVER_CANDIDATES,
+                })
+                .await
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let mut candidates = vec![driver_id];
+        candidates.extend(nearest_drivers.into_iter().filter(|id| *id != driver_id));
+
+        if let Err(e) = storage_actor
+            .send(StartDispatch {
+                passenger_id,
+                candidates,
+            })
+            .await
+        {
+            log_trips(format!("Failed to start dispatch: {:?}", e));
+            return;
+        }
+
+        offer_next_driver(passenger_id, storage_actor, coord_addr, config).await;
+    }
+}
+
+/// Takes out of the trip queue a passenger whose trip was accepted by a driver.
+pub async fn leave_queue(
+    passenger_id: ClientId,
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+) {
+    replicate_queued(passenger_id, None, &coord_addr);
+    notify_queue_positions(storage_actor).await;
+}
+
+/// Tells every queued passenger its current position in the trip queue, in arrival order.
+async fn notify_queue_positions(storage_actor: Arc<Addr<Storage>>) {
+    let queued_passengers = storage_actor
+        .send(GetQueuedPassengers)
+        .await
+        .unwrap_or_default();
+
+    for queued in queued_passengers {
+        let Some(sender) = queued.passenger_sender else {
+            continue;
+        };
+        match serde_json::to_string(&TripQueued {
+            queue_position: queued.position,
+        }) {
+            Ok(json_string) => {
+                if let Err(e) = sender.try_send(TcpMessage(json_string)) {
+                    log_trips(format!("Failed to send TripQueued: {:?}", e));
+                }
+            }
+            Err(err) => eprintln!("Error serializing TripQueued: {}", err),
+        }
+    }
+}
+
+/// Replicates how long the passenger has been waiting in the queue, None if it left the queue.
+fn replicate_queued(
+    passenger_id: ClientId,
+    queued_for: Option<Duration>,
+    coord_addr: &Arc<Addr<Coordinator>>,
+) {
+    if let Err(e) = coord_addr.try_send(UpdatePassengers {
+        action: Action::Update,
+        passenger: passenger_id,
+        origin: (0.0, 0.0),
+        destination: (0.0, 0.0),
+        fare: 0.0,
+        queued_for: queued_for.map(|queued_for| queued_for.as_millis() as u64),
+    }) {
+        log_trips(format!("Failed to send UpdatePassengers: {:?}", e));
+    }
+}
+
+/// Function to reject the passengers that waited in the trip queue longer than `max_wait`.
+async fn expire_queued_passengers(
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    max_wait: Duration,
+) {
+    let expired_passengers = storage_actor
+        .send(ExpireQueuedPassengers { max_wait })
+        .await
+        .unwrap_or_default();
+    if expired_passengers.is_empty() {
+        return;
+    }
+
+    for expired in expired_passengers {
+        if let Err(e) = coord_addr.try_send(UpdatePassengers {
+            action: Action::Delete,
+            passenger: expired.passenger_id,
+            origin: (0.0, 0.0),
+            destination: (0.0, 0.0),
+            fare: 0.0,
+            queued_for: None,
+        }) {
+            log_trips(format!("Failed to send UpdatePassengers: {:?}", e));
+        }
+
+        if let Some(sender) = expired.passenger_sender.as_ref() {
+            match serde_json::to_string(&RejectTrip {
+                response: "No drivers became available in time, please try again".to_string(),
+            }) {
+                Ok(json_string) => {
+                    if let Err(e) = sender.send(TcpMessage(json_string)).await {
+                        log_trips(format!("Failed to send RejectTrip: {:?}", e));
+                    }
+                }
+                Err(err) => eprintln!("Error serializing RejectTrip: {}", err),
+            }
+        }
+        log_trips(format!(
+            "Passenger {:?} waited too long for a driver",
+            expired.passenger_id
+        ));
+    }
+    notify_queue_positions(storage_actor).await;
+}
+
+/// Spawns the task that rejects the passengers waiting in the trip queue for more than `max_queue_wait` seconds.
+pub fn spawn_queue_task(
+    storage_actor: Arc<Addr<Storage>>,
+    coord_addr: Arc<Addr<Coordinator>>,
+    coordinator_election: Arc<Addr<CoordinatorElection>>,
+    config: Arc<ClusterConfig>,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(config.queue_check_interval)).await;
+            // Only the coordinator expires queued passengers, the others receive the updates
+            if coordinator_election
+                .send(AmICoordinator)
+                .await
+                .unwrap_or(false)
+            {
+                expire_queued_passengers(
+                    storage_actor.clone(),
+                    coord_addr.clone(),
+                    Duration::from_secs(config.max_queue_wait),
+                )
+                .await;
+            }
+        }
+    });
+}
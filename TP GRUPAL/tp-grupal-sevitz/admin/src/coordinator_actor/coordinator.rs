@@ -1,8 +1,7 @@
 use crate::coordinator_actor::coordinator_messages::*;
 use crate::utils::admin_errors::AdminError;
-use crate::utils::consts::MAX_RETRIES;
-use crate::utils::consts::MAX_TIME_WITHOUT_PINGING;
 use actix::prelude::*;
+use common::cluster::{ClusterConfig, ClusterNode};
 use common::tcp_sender::{TcpMessage, TcpSender};
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -16,9 +15,11 @@ use tokio::net::TcpStream;
 /// so it can balance trips between them
 pub struct Coordinator {
     pub addr: SocketAddr,
-    pub peers: Vec<SocketAddr>,
+    pub peers: Vec<ClusterNode>,
     pub peer_handles: Peers,
     pub peer_counter: u8,
+    pub max_retries: u8,
+    pub max_time_without_pinging: u64,
 }
 
 impl Actor for Coordinator {
@@ -26,12 +27,14 @@ impl Actor for Coordinator {
 }
 
 impl Coordinator {
-    pub fn new(addr: SocketAddr, peers: Vec<SocketAddr>) -> Addr<Self> {
+    pub fn new(addr: SocketAddr, config: &ClusterConfig) -> Addr<Self> {
         Coordinator::create(|_ctx| Coordinator {
             addr,
-            peers,
+            peers: config.nodes.clone(),
             peer_handles: Arc::new(HashMap::new()),
             peer_counter: 0,
+            max_retries: config.max_retries,
+            max_time_without_pinging: config.max_time_without_pinging,
         })
     }
 }
@@ -41,12 +44,13 @@ impl Handler<BecomeCoordinator> for Coordinator {
 
     fn handle(&mut self, _msg: BecomeCoordinator, _ctx: &mut Self::Context) -> Self::Result {
         let addr = self.addr;
-        let peers = self.peers.clone();
+        let peers = self.peers.iter().map(|node| node.addr).collect();
+        let max_retries = self.max_retries;
         let actor_addr = _ctx.address();
 
         Box::pin(
             async move {
-                if let Err(e) = connect_to_peers(addr, peers, actor_addr).await {
+                if let Err(e) = connect_to_peers(addr, peers, max_retries, actor_addr).await {
                     eprintln!("Failed to connect to peers: {:?}", e);
                 }
             }
@@ -60,7 +64,8 @@ impl Handler<HandleTrip> for Coordinator {
     type Result = ResponseActFuture<Self, ()>;
 
     fn handle(&mut self, msg: HandleTrip, _ctx: &mut Self::Context) -> Self::Result {
-        let peers = self.peers.clone();
+        let peers: Vec<SocketAddr> = self.peers.iter().map(|node| node.addr).collect();
+        let max_time_without_pinging = self.max_time_without_pinging;
         let actor_addr = _ctx.address();
 
         Box::pin(
@@ -73,7 +78,7 @@ impl Handler<HandleTrip> for Coordinator {
                     let peer = peers[counter as usize % peers.len()];
                     let handles = actor_addr.send(GetPeerDict).await.unwrap_or_default();
                     if let Some(tuple) = handles.get(&peer) {
-                        if tuple.1.elapsed().as_secs() >= MAX_TIME_WITHOUT_PINGING {
+                        if tuple.1.elapsed().as_secs() >= max_time_without_pinging {
                             times_retry -= 1;
                             continue;
                         }
@@ -117,6 +122,104 @@ impl Handler<UpdatePassengers> for Coordinator {
     }
 }
 
+impl Handler<UpdateSurge> for Coordinator {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: UpdateSurge, _ctx: &mut Self::Context) -> Self::Result {
+        let msg = serde_json::to_string(&msg).expect("Error converting to JSON");
+        let actor_addr = _ctx.address();
+
+        Box::pin(
+            async move {
+                broadcast_update(actor_addr, msg).await;
+            }
+            .into_actor(self)
+            .map(|_, _, _| ()),
+        )
+    }
+}
+
+impl Handler<UpdateSession> for Coordinator {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: UpdateSession, _ctx: &mut Self::Context) -> Self::Result {
+        let msg = serde_json::to_string(&msg).expect("Error converting to JSON");
+        let actor_addr = _ctx.address();
+
+        Box::pin(
+            async move {
+                broadcast_update(actor_addr, msg).await;
+            }
+            .into_actor(self)
+            .map(|_, _, _| ()),
+        )
+    }
+}
+
+impl Handler<UpdateMembers> for Coordinator {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: UpdateMembers, _ctx: &mut Self::Context) -> Self::Result {
+        let msg = serde_json::to_string(&msg).expect("Error converting to JSON");
+        let actor_addr = _ctx.address();
+
+        Box::pin(
+            async move {
+                broadcast_update(actor_addr, msg).await;
+            }
+            .into_actor(self)
+            .map(|_, _, _| ()),
+        )
+    }
+}
+
+impl Handler<SetMembers> for Coordinator {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetMembers, _ctx: &mut Self::Context) -> Self::Result {
+        self.peers = msg.members;
+        self.drop_removed_peers();
+    }
+}
+
+impl Handler<AddMember> for Coordinator {
+    type Result = MessageResult<AddMember>;
+
+    fn handle(&mut self, msg: AddMember, _ctx: &mut Self::Context) -> Self::Result {
+        self.peers.retain(|node| node.id != msg.node.id);
+        self.peers.push(msg.node);
+        self.peers.sort_by_key(|node| node.id);
+        self.drop_removed_peers();
+        MessageResult(self.peers.clone())
+    }
+}
+
+impl Handler<RemoveMember> for Coordinator {
+    type Result = MessageResult<RemoveMember>;
+
+    fn handle(&mut self, msg: RemoveMember, _ctx: &mut Self::Context) -> Self::Result {
+        self.peers.retain(|node| node.id != msg.node_id);
+        self.drop_removed_peers();
+        MessageResult(self.peers.clone())
+    }
+}
+
+impl Handler<GetMembers> for Coordinator {
+    type Result = MessageResult<GetMembers>;
+
+    fn handle(&mut self, _msg: GetMembers, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.peers.clone())
+    }
+}
+
+impl Coordinator {
+    /// Stops sending updates and trips to the admins that are no longer members of the cluster
+    fn drop_removed_peers(&mut self) {
+        let members: Vec<SocketAddr> = self.peers.iter().map(|node| node.addr).collect();
+        Arc::make_mut(&mut self.peer_handles).retain(|peer, _| members.contains(peer));
+    }
+}
+
 impl Handler<UpdateDrivers> for Coordinator {
     type Result = ResponseActFuture<Self, ()>;
 
@@ -150,6 +253,7 @@ impl Handler<ConnectNewPeer> for Coordinator {
     fn handle(&mut self, msg: ConnectNewPeer, _ctx: &mut Self::Context) -> Self::Result {
         let addr = self.addr;
         let peer = msg.new_peer;
+        let max_retries = self.max_retries;
         let actor_addr = _ctx.address();
 
         Box::pin(
@@ -166,7 +270,7 @@ impl Handler<ConnectNewPeer> for Coordinator {
                     return;
                 }
 
-                match connect_to_peer(addr, peer, actor_addr).await {
+                match connect_to_peer(addr, peer, max_retries, actor_addr).await {
                     Ok(_) => (),
                     Err(e) => println!("[{}] Failed to connect to {:?}: {:?}", addr, peer, e),
                 }
@@ -208,12 +312,13 @@ pub async fn broadcast_update(coord_actor: Addr<Coordinator>, msg: String) {
 async fn connect_to_peers(
     addr: SocketAddr,
     peers: Vec<SocketAddr>,
+    max_retries: u8,
     coord_actor: Addr<Coordinator>,
 ) -> Result<(), AdminError> {
     println!("[{}] Connecting to peers {:?}", addr, peers);
 
     for &peer in peers.iter() {
-        connect_to_peer(addr, peer, coord_actor.clone()).await?;
+        connect_to_peer(addr, peer, max_retries, coord_actor.clone()).await?;
     }
 
     Ok(())
@@ -222,6 +327,7 @@ async fn connect_to_peers(
 async fn connect_to_peer(
     addr: SocketAddr,
     peer: SocketAddr,
+    max_retries: u8,
     coord_actor: Addr<Coordinator>,
 ) -> Result<(), AdminError> {
     if addr == peer {
@@ -231,7 +337,7 @@ async fn connect_to_peer(
     let mut attempts = 0;
     let mut stream = None;
 
-    while attempts < MAX_RETRIES {
+    while attempts < max_retries {
         match TcpStream::connect(peer).await {
             Ok(s) => {
                 stream = Some(s);
@@ -1,5 +1,8 @@
 use crate::admin_actor::clients_to_admin::DriverStatus;
+use crate::utils::surge::Zone;
 use actix::prelude::*;
+use common::cluster::{ClusterNode, NodeId};
+use common::messages::ClientId;
 use common::tcp_sender::TcpSender;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -36,9 +39,12 @@ pub enum Action {
 /// This message is used to update the passengers in the non coordinator Admins
 pub struct UpdatePassengers {
     pub action: Action,
-    pub passenger: SocketAddr,
+    pub passenger: ClientId,
     pub origin: (f32, f32),
     pub destination: (f32, f32),
+    pub fare: f32,
+    /// Milliseconds the passenger has been waiting in the trip queue of the coordinator, None if it is not queued
+    pub queued_for: Option<u64>,
 }
 
 #[derive(Message, Serialize, Deserialize, Debug, Clone)]
@@ -46,17 +52,83 @@ pub struct UpdatePassengers {
 /// This message is used to update the drivers in the non coordinator Admins
 pub struct UpdateDrivers {
     pub action: Action,
-    pub driver: SocketAddr,
+    pub driver: ClientId,
     pub position: (f32, f32),
-    pub current_passenger_id: Option<SocketAddr>,
+    pub current_passenger_id: Option<ClientId>,
     pub status: DriverStatus,
 }
 
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// This message is used to update the surge multiplier of a zone in the non coordinator Admins
+pub struct UpdateSurge {
+    pub zone: Zone,
+    pub multiplier: f32,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// This message is used to register the session of a client in the non coordinator Admins,
+/// so any of them can resume it if it becomes the coordinator
+pub struct UpdateSession {
+    pub client: ClientId,
+    pub session_token: u64,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// This message is sent by a new admin to the Coordinator to join the cluster
+pub struct JoinCluster {
+    pub joining_node: ClusterNode,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// This message is sent by an admin to the Coordinator before shutting down, to leave the cluster
+pub struct LeaveCluster {
+    pub leaving_node: NodeId,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// This message is used to update the cluster members in the non coordinator Admins
+/// after an admin joins or leaves the cluster
+pub struct UpdateMembers {
+    pub members: Vec<ClusterNode>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Internal message to replace the cluster members known by the Coordinator and Election actors
+pub struct SetMembers {
+    pub members: Vec<ClusterNode>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<ClusterNode>")]
+/// Internal message to add (or update the address of) a cluster member,
+/// returns the new cluster members
+pub struct AddMember {
+    pub node: ClusterNode,
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<ClusterNode>")]
+/// Internal message to remove a cluster member, returns the new cluster members
+pub struct RemoveMember {
+    pub node_id: NodeId,
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<ClusterNode>")]
+/// Internal message to get the current cluster members
+pub struct GetMembers;
+
 #[derive(Message, Serialize, Deserialize)]
 #[rtype(result = "()")]
 /// This message is used to tell one of the admins to handle a trip for a passenger
 pub struct HandleTrip {
-    pub passenger_id_ht: SocketAddr,
+    pub passenger_id_ht: ClientId,
 }
 
 #[derive(Message)]
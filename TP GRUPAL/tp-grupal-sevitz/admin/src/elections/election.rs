@@ -1,11 +1,12 @@
 use crate::coordinator_actor::coordinator::Coordinator;
-use crate::coordinator_actor::coordinator_messages::BecomeCoordinator;
+use crate::coordinator_actor::coordinator_messages::{BecomeCoordinator, SetMembers};
 use crate::elections::election_messages::{
     AmICoordinator, CoordinatorMessage, ElectionMessage, GetCoordAddr, GetCoordId, PingCoordinator,
     PingMessage, SetCoordId, StartElection,
 };
 use crate::utils::logs::log_elections;
 use actix::prelude::*;
+use common::cluster::ClusterNode;
 use common::messages::WhoIsCoordinatorResponse;
 use common::tcp_sender::TcpMessage;
 use std::net::SocketAddr;
@@ -15,17 +16,15 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::time::{timeout, Duration};
 
-const MIN_PEER_PORT: u16 = 8080;
-const MAX_PEER_PORT: u16 = 8084;
-
 #[derive(Debug, Clone)]
 /// This actor is responsible for the election of the coordinator.
 /// It implements the Ring election algorithm.
+/// The ring follows the order of the node ids of the cluster members.
 pub struct CoordinatorElection {
     pub id: SocketAddr,
     pub coordinator_id: Option<SocketAddr>,
     pub coordinator: Arc<Addr<Coordinator>>,
-    pub peers: Arc<Vec<SocketAddr>>,
+    pub peers: Arc<Vec<ClusterNode>>,
     pub in_election: bool,
 }
 
@@ -41,7 +40,7 @@ impl CoordinatorElection {
     pub fn new(
         id: SocketAddr,
         coordinator: Arc<Addr<Coordinator>>,
-        peers: Arc<Vec<SocketAddr>>,
+        peers: Arc<Vec<ClusterNode>>,
     ) -> Addr<Self> {
         CoordinatorElection::create(|_ctx| CoordinatorElection {
             id,
@@ -61,6 +60,14 @@ impl Handler<SetCoordId> for CoordinatorElection {
     }
 }
 
+impl Handler<SetMembers> for CoordinatorElection {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetMembers, _ctx: &mut Self::Context) {
+        self.peers = Arc::new(msg.members);
+    }
+}
+
 impl Handler<GetCoordId> for CoordinatorElection {
     type Result = MessageResult<GetCoordId>;
 
@@ -84,8 +91,9 @@ impl Handler<AskForCoordinator> for CoordinatorElection {
                 if election.in_election || election.is_coordinator(addr.clone()).await {
                     return;
                 }
+                let peers = election.peers.iter().map(|node| node.addr).collect();
                 election
-                    .ask_who_is_coordinator(election.peers.to_vec(), election.id, addr)
+                    .ask_who_is_coordinator(peers, election.id, addr)
                     .await;
             }
             .into_actor(self)
@@ -229,17 +237,13 @@ impl CoordinatorElection {
             serde_json::to_string(&msg).expect("Error converting to JSON")
         ));
 
-        let mut next_peer_port = self.id.port();
         let mut got_ack = false;
 
-        while !got_ack && next_peer_port != self.id.port() - 1 {
-            next_peer_port = if next_peer_port >= MAX_PEER_PORT {
-                MIN_PEER_PORT
-            } else {
-                next_peer_port + 1
-            };
+        for next_peer in self.ring_after_self() {
+            if got_ack {
+                break;
+            }
 
-            let next_peer = SocketAddr::new(self.id.ip(), next_peer_port);
             log_elections(format!("Trying to connect to next peer: {:?}", next_peer));
 
             for _ in 0..3 {
@@ -286,7 +290,7 @@ impl CoordinatorElection {
         candidates: Vec<SocketAddr>,
         addr: Addr<CoordinatorElection>,
     ) {
-        let new_coordinator = match candidates.iter().min_by_key(|&x| x.port()) {
+        let new_coordinator = match candidates.iter().min_by_key(|&&x| self.ring_position(x)) {
             Some(coordinator) => coordinator,
             None => {
                 panic!("No candidates found");
@@ -332,7 +336,7 @@ impl CoordinatorElection {
         );
         let msg = TcpMessage(msg);
 
-        for &peer in self.peers.iter() {
+        for peer in self.peers.iter().map(|node| node.addr) {
             if peer == self.id {
                 continue;
             }
@@ -365,6 +369,29 @@ impl CoordinatorElection {
         }
     }
 
+    /// Position of an admin in the ring, members are sorted by node id.
+    /// Admins that are not members yet go last.
+    fn ring_position(&self, addr: SocketAddr) -> usize {
+        self.peers
+            .iter()
+            .position(|node| node.addr == addr)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Addresses of the other members in ring order, starting from the one after this admin
+    fn ring_after_self(&self) -> Vec<SocketAddr> {
+        let start = self
+            .peers
+            .iter()
+            .position(|node| node.addr == self.id)
+            .map_or(0, |position| position + 1);
+
+        (0..self.peers.len())
+            .map(|i| self.peers[(start + i) % self.peers.len()].addr)
+            .filter(|&addr| addr != self.id)
+            .collect()
+    }
+
     pub async fn ping_coordinator(&self, actor_addr: Addr<Self>) {
         log_elections(format!("[{:?}] Pinging coordinator", self.id));
         let coord_id = actor_addr
@@ -511,3 +538,69 @@ impl CoordinatorElection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::cluster::ClusterConfig;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    /// Election of the admin at `id`, with the members 1 to 3 in the ports 8080 to 8082
+    fn election(id: SocketAddr) -> CoordinatorElection {
+        let config = ClusterConfig {
+            nodes: (1..=3)
+                .map(|node_id| ClusterNode {
+                    id: node_id,
+                    addr: addr(8079 + node_id as u16),
+                })
+                .collect(),
+            payment_gateway: addr(8085),
+            ping_interval: 2,
+            max_time_without_pinging: 5,
+            max_retries: 3,
+            offer_timeout: 2,
+            dispatch_timeout: 15,
+            max_queue_wait: 60,
+            queue_check_interval: 5,
+            dead_driver_timeout: 3,
+        };
+        CoordinatorElection {
+            id,
+            coordinator_id: None,
+            coordinator: Arc::new(Coordinator::new(id, &config)),
+            peers: Arc::new(config.nodes),
+            in_election: false,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn ring_starts_after_self_and_wraps_around() {
+        assert_eq!(
+            election(addr(8081)).ring_after_self(),
+            vec![addr(8082), addr(8080)]
+        );
+        assert_eq!(
+            election(addr(8082)).ring_after_self(),
+            vec![addr(8080), addr(8081)]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn admin_not_member_yet_rings_every_member() {
+        assert_eq!(
+            election(addr(8086)).ring_after_self(),
+            vec![addr(8080), addr(8081), addr(8082)]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn ring_position_follows_node_ids() {
+        let election = election(addr(8080));
+        assert_eq!(election.ring_position(addr(8080)), 0);
+        assert_eq!(election.ring_position(addr(8082)), 2);
+        assert_eq!(election.ring_position(addr(8086)), usize::MAX);
+    }
+}
@@ -4,23 +4,45 @@ mod elections;
 mod storage_actor;
 mod utils;
 use admin_actor::admin::Admin;
+use common::cluster::{ClusterConfig, ClusterNode, NodeId};
 use common::utils::socket_addr_from_string;
-use std::net::SocketAddr;
 use utils::admin_errors::AdminError;
 
-const PORT_ARG: usize = 1;
+const NODE_ID_ARG: usize = 1;
+const NODE_ADDR_ARG: usize = 2;
 
+/// Usage: admin <node_id> [host:port]
+/// The address is only needed for nodes that are not in the cluster config, which join the cluster.
 #[actix_rt::main]
 async fn main() -> Result<(), AdminError> {
-    let port = std::env::args().nth(PORT_ARG).unwrap_or("8080".to_string());
+    let config = ClusterConfig::load()?;
 
-    let addr = socket_addr_from_string(format!("127.0.0.1:{}", port));
-    let peers: Vec<SocketAddr> = (8080..8085)
-        .map(|port| format!("127.0.0.1:{}", port))
-        .map(socket_addr_from_string)
-        .collect();
+    let node_id: NodeId = match std::env::args().nth(NODE_ID_ARG) {
+        Some(id) => id
+            .parse()
+            .map_err(|_| AdminError::InvalidPeers(format!("Invalid node id {}", id)))?,
+        None => config
+            .nodes
+            .first()
+            .map(|node| node.id)
+            .ok_or(AdminError::InvalidPeers("No peers provided".to_string()))?,
+    };
 
-    Admin::start(addr, peers).await?;
+    let node = match (config.node(node_id), std::env::args().nth(NODE_ADDR_ARG)) {
+        (Some(node), _) => node.clone(),
+        (None, Some(addr)) => ClusterNode {
+            id: node_id,
+            addr: socket_addr_from_string(addr),
+        },
+        (None, None) => {
+            return Err(AdminError::InvalidPeers(format!(
+                "Node {} is not in the cluster config, its address is needed to join",
+                node_id
+            )))
+        }
+    };
+
+    Admin::start(node, config).await?;
 
     Ok(())
 }
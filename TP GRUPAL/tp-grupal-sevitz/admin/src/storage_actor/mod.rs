@@ -1,3 +1,4 @@
+pub mod spatial_index;
 pub mod storage;
 pub mod storage_handlers;
 pub mod storage_messages;
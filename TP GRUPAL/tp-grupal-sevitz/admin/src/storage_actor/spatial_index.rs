@@ -0,0 +1,191 @@
+use common::messages::ClientId;
+use std::collections::{HashMap, HashSet};
+
+type Cell = (i32, i32);
+
+/// Uniform grid over the map that indexes drivers by their position,
+/// so the nearest drivers to a point are found visiting only the cells around it
+/// instead of scanning every driver.
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<Cell, HashSet<ClientId>>,
+    positions: HashMap<ClientId, (f32, f32)>,
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialIndex {
+            cell_size,
+            cells: HashMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Indexes the driver at the given position, moving it if it was already indexed.
+    pub fn insert(&mut self, id: ClientId, position: (f32, f32)) {
+        self.remove(&id);
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().insert(id);
+        self.positions.insert(id, position);
+    }
+
+    /// Removes the driver from the index, if it was indexed.
+    pub fn remove(&mut self, id: &ClientId) {
+        if let Some(position) = self.positions.remove(id) {
+            let cell = self.cell_of(position);
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Returns up to `limit` drivers accepted by `is_candidate`, ranked from nearest to farthest to `point`.
+    /// Cells are visited in rings of growing size around the point, stopping as soon as
+    /// no unvisited cell can hold a driver nearer than the ones already found, or every occupied cell was visited.
+    /// Once the rings visited more cells than there are occupied ones, the remaining occupied cells
+    /// are checked directly, so sparse drivers far from the point don't cost a ring per cell of distance.
+    pub fn nearest<F>(&self, point: (f32, f32), limit: usize, is_candidate: F) -> Vec<ClientId>
+    where
+        F: Fn(&ClientId) -> bool,
+    {
+        let mut found: Vec<(f32, ClientId)> = Vec::new();
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let center = self.cell_of(point);
+        let add_cell = |ids: &HashSet<ClientId>, found: &mut Vec<(f32, ClientId)>| {
+            for id in ids.iter().filter(|id| is_candidate(id)) {
+                found.push((distance(self.positions[id], point), *id));
+            }
+        };
+
+        let mut ring = 0;
+        let mut visited_cells = 0;
+        let mut visited_occupied = 0;
+        while visited_occupied < self.cells.len() {
+            // Every cell of the ring is at least (ring - 1) cells away from the point
+            let ring_distance = (ring - 1) as f32 * self.cell_size;
+            if found.len() >= limit && found[limit - 1].0 <= ring_distance {
+                break;
+            }
+
+            if visited_cells > self.cells.len() {
+                for (cell, ids) in &self.cells {
+                    if ring_of(center, *cell) >= ring {
+                        add_cell(ids, &mut found);
+                    }
+                }
+                break;
+            }
+
+            for cell in ring_cells(center, ring) {
+                visited_cells += 1;
+                if let Some(ids) = self.cells.get(&cell) {
+                    visited_occupied += 1;
+                    add_cell(ids, &mut found);
+                }
+            }
+            found.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+            ring += 1;
+        }
+
+        found.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        found.truncate(limit);
+        found.into_iter().map(|(_, id)| id).collect()
+    }
+
+    fn cell_of(&self, position: (f32, f32)) -> Cell {
+        (
+            (position.0 / self.cell_size).floor() as i32,
+            (position.1 / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+/// Distance (in cells) from the center to the cell, the ring the cell belongs to.
+fn ring_of(center: Cell, cell: Cell) -> i32 {
+    (cell.0 - center.0).abs().max((cell.1 - center.1).abs())
+}
+
+/// Cells whose distance (in cells) to the center is exactly `ring`.
+fn ring_cells(center: Cell, ring: i32) -> Vec<Cell> {
+    if ring == 0 {
+        return vec![center];
+    }
+
+    let mut cells = Vec::new();
+    for dx in -ring..=ring {
+        cells.push((center.0 + dx, center.1 - ring));
+        cells.push((center.0 + dx, center.1 + ring));
+    }
+    for dy in (1 - ring)..ring {
+        cells.push((center.0 - ring, center.1 + dy));
+        cells.push((center.0 + ring, center.1 + dy));
+    }
+    cells
+}
+
+pub fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(drivers: &[(ClientId, (f32, f32))]) -> SpatialIndex {
+        let mut index = SpatialIndex::new(10.0);
+        for (id, position) in drivers {
+            index.insert(*id, *position);
+        }
+        index
+    }
+
+    #[test]
+    fn nearest_ranks_by_distance() {
+        let index = index(&[(1, (50.0, 50.0)), (2, (12.0, 3.0)), (3, (1.0, 1.0))]);
+
+        assert_eq!(index.nearest((0.0, 0.0), 3, |_| true), vec![3, 2, 1]);
+        assert_eq!(index.nearest((0.0, 0.0), 2, |_| true), vec![3, 2]);
+        assert!(index.nearest((0.0, 0.0), 0, |_| true).is_empty());
+    }
+
+    #[test]
+    fn nearest_skips_rejected_candidates() {
+        let index = index(&[(1, (1.0, 1.0)), (2, (25.0, 0.0))]);
+
+        assert_eq!(index.nearest((0.0, 0.0), 1, |id| *id != 1), vec![2]);
+    }
+
+    #[test]
+    fn nearest_checks_cells_beyond_the_first_ring_found() {
+        // The point is near the edge of its cell: the driver in the adjacent cell (ring 1) is at 10.4,
+        // farther than the one two cells away (ring 2) at 10.1
+        let index = index(&[(1, (-0.5, 0.5)), (2, (20.0, 0.5))]);
+
+        assert_eq!(index.nearest((9.9, 0.5), 1, |_| true), vec![2]);
+        assert_eq!(index.nearest((9.9, 0.5), 2, |_| true), vec![2, 1]);
+    }
+
+    #[test]
+    fn nearest_finds_far_away_drivers() {
+        let index = index(&[(1, (1_000_000.0, -1_000_000.0)), (2, (-500_000.0, 0.0))]);
+
+        assert_eq!(index.nearest((0.0, 0.0), 2, |_| true), vec![2, 1]);
+        assert!(index.nearest((0.0, 0.0), 2, |_| false).is_empty());
+    }
+
+    #[test]
+    fn moved_and_removed_drivers_are_reindexed() {
+        let mut index = index(&[(1, (1.0, 1.0)), (2, (30.0, 30.0))]);
+        index.insert(1, (100.0, 100.0));
+        index.remove(&2);
+
+        assert_eq!(index.nearest((0.0, 0.0), 2, |_| true), vec![1]);
+        assert_eq!(index.cells.len(), 1);
+    }
+}
@@ -1,15 +1,28 @@
+use super::spatial_index::SpatialIndex;
+use crate::admin_actor::clients_to_admin::DriverStatus;
 use crate::utils::{
     admin_errors::AdminError,
-    entities::{DriverEntity, PassengerEntity},
+    consts::GRID_CELL_SIZE,
+    entities::{DispatchEntity, DriverEntity, PassengerEntity},
+    surge::{zone_of, Zone},
 };
 use actix::Addr;
 use actix::{Actor, Context};
-use std::{collections::HashMap, net::SocketAddr};
+use common::messages::ClientId;
+use std::collections::{HashMap, HashSet};
 
 /// This actor is responsible for storing the passengers and drivers in the system.
+/// The positions of the drivers are also kept in a spatial index to find the nearest ones to a passenger.
+/// It also keeps the last surge multiplier quoted in each zone of the map,
+/// the candidate drivers to whom each pending trip is being offered,
+/// and the session token of each registered client, kept while the client has a passenger or driver.
 pub struct Storage {
-    pub passengers: HashMap<SocketAddr, PassengerEntity>,
-    pub drivers: HashMap<SocketAddr, DriverEntity>,
+    pub passengers: HashMap<ClientId, PassengerEntity>,
+    pub drivers: HashMap<ClientId, DriverEntity>,
+    pub driver_index: SpatialIndex,
+    pub surge_multipliers: HashMap<Zone, f32>,
+    pub dispatches: HashMap<ClientId, DispatchEntity>,
+    pub sessions: HashMap<ClientId, u64>,
 }
 
 impl Actor for Storage {
@@ -17,15 +30,138 @@ impl Actor for Storage {
 }
 
 impl Storage {
-    pub fn start() -> Result<Addr<Storage>, AdminError> {
-        println!("[STORAGE] Starting storage actor");
-        let storage = Storage {
+    pub fn new() -> Self {
+        Storage {
             passengers: HashMap::new(),
             drivers: HashMap::new(),
-        };
+            driver_index: SpatialIndex::new(GRID_CELL_SIZE),
+            surge_multipliers: HashMap::new(),
+            dispatches: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
 
-        let storage_addr = storage.start();
+    pub fn start() -> Result<Addr<Storage>, AdminError> {
+        println!("[STORAGE] Starting storage actor");
+        let storage_addr = Storage::new().start();
 
         Ok(storage_addr)
     }
+
+    /// Demand and supply of a zone for a new trip request of `requester` from it:
+    /// the passengers of the zone still waiting for a driver plus the requester (counted once,
+    /// even if it is already stored), and the active drivers of the zone.
+    pub fn zone_demand_and_supply(&self, zone: Zone, requester: ClientId) -> (usize, usize) {
+        let assigned_passengers: HashSet<ClientId> = self
+            .drivers
+            .values()
+            .filter_map(|driver| driver.current_passenger_id)
+            .collect();
+        let waiting_passengers = self
+            .passengers
+            .iter()
+            .filter(|(id, passenger)| {
+                **id != requester
+                    && !assigned_passengers.contains(id)
+                    && zone_of(passenger.passenger_position) == zone
+            })
+            .count();
+        let active_drivers = self
+            .drivers
+            .values()
+            .filter(|driver| {
+                driver.status == DriverStatus::Active && zone_of(driver.driver_position) == zone
+            })
+            .count();
+
+        (waiting_passengers + 1, active_drivers)
+    }
+
+    /// Forgets the session of a client once it has neither a passenger nor a driver,
+    /// if it registers again it starts a new session.
+    pub fn prune_session(&mut self, client_id: ClientId) {
+        if !self.passengers.contains_key(&client_id) && !self.drivers.contains_key(&client_id) {
+            self.sessions.remove(&client_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn passenger(position: (f32, f32)) -> PassengerEntity {
+        PassengerEntity {
+            passenger_position: position,
+            passenger_destination: (0.0, 0.0),
+            fare: 0.0,
+            passenger_sender: None,
+            queued_at: None,
+        }
+    }
+
+    fn driver(
+        position: (f32, f32),
+        status: DriverStatus,
+        passenger: Option<ClientId>,
+    ) -> DriverEntity {
+        DriverEntity {
+            driver_position: position,
+            current_passenger_id: passenger,
+            driver_sender: None,
+            status,
+            time_stamp: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn requester_is_counted_once() {
+        let mut storage = Storage::new();
+        storage.passengers.insert(1, passenger((1.0, 1.0)));
+        storage.passengers.insert(2, passenger((2.0, 2.0)));
+
+        assert_eq!(storage.zone_demand_and_supply((0, 0), 1), (2, 0));
+        assert_eq!(storage.zone_demand_and_supply((0, 0), 3), (3, 0));
+    }
+
+    #[test]
+    fn demand_and_supply_only_count_the_zone() {
+        let mut storage = Storage::new();
+        storage.passengers.insert(1, passenger((1.0, 1.0)));
+        storage.passengers.insert(2, passenger((15.0, 1.0)));
+        storage.passengers.insert(3, passenger((2.0, 2.0)));
+        storage
+            .drivers
+            .insert(10, driver((3.0, 3.0), DriverStatus::Active, None));
+        storage
+            .drivers
+            .insert(11, driver((4.0, 4.0), DriverStatus::OnTrip, Some(3)));
+        storage
+            .drivers
+            .insert(12, driver((25.0, 3.0), DriverStatus::Active, None));
+
+        // passenger 2 is in another zone and passenger 3 already has a driver
+        assert_eq!(storage.zone_demand_and_supply((0, 0), 4), (2, 1));
+    }
+
+    #[test]
+    fn session_is_pruned_once_the_client_has_no_passenger_or_driver() {
+        let mut storage = Storage::new();
+        storage.sessions.insert(1, 100);
+        storage.sessions.insert(2, 200);
+        storage.passengers.insert(1, passenger((1.0, 1.0)));
+        storage
+            .drivers
+            .insert(1, driver((1.0, 1.0), DriverStatus::Active, None));
+
+        storage.passengers.remove(&1);
+        storage.prune_session(1);
+        assert!(storage.sessions.contains_key(&1));
+
+        storage.drivers.remove(&1);
+        storage.prune_session(1);
+        storage.prune_session(2);
+        assert!(storage.sessions.is_empty());
+    }
 }
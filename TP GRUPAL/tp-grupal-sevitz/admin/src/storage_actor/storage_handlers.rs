@@ -1,14 +1,63 @@
 use super::storage::Storage;
 use super::storage_messages::{
-    GetDriver, GetNearestDriver, GetPassenger, InsertDriver, InsertPassenger, IsFinished,
-    ReapDeadDrivers, RemoveDriver, RemovePassenger, UpdateDriver,
+    AnswerOffer, ComputeSurge, DispatchOffer, EnqueuePassenger, ExpireOffer,
+    ExpireQueuedPassengers, GetDriver, GetNearestDriver, GetPassenger, GetQueuedPassengers,
+    InsertDriver, InsertPassenger, IsFinished, NextDispatchOffer, QueuedPassenger, ReapDeadDrivers,
+    RegisterSession, RemoveDriver, RemovePassenger, SessionStatus, SetPassengerQueued, SetSession,
+    SetSurge, StartDispatch, TakeQueuedPassenger, UpdateDriver,
 };
 use crate::admin_actor::clients_to_admin::DriverStatus;
+use crate::storage_actor::spatial_index::distance;
 use crate::storage_actor::storage_messages::DeadDriver;
-use crate::utils::entities::{DriverEntity, PassengerEntity};
-use actix::{Context, Handler};
-use common::messages::FinishTrip;
-use std::net::SocketAddr;
+use crate::utils::consts::QUEUE_OVERTAKE_WINDOW;
+use crate::utils::entities::{DispatchEntity, DriverEntity, PassengerEntity};
+use crate::utils::surge::surge_multiplier;
+use actix::{Context, Handler, MessageResult};
+use common::messages::{ClientId, FinishTrip};
+use std::time::{Duration, Instant};
+
+impl Handler<RegisterSession> for Storage {
+    type Result = SessionStatus;
+
+    fn handle(&mut self, msg: RegisterSession, _: &mut Self::Context) -> Self::Result {
+        match self.sessions.get(&msg.client_id) {
+            None => {
+                println!("[STORAGE] Registering client {}", msg.client_id);
+                self.sessions.insert(msg.client_id, msg.session_token);
+                SessionStatus::New
+            }
+            Some(session_token) if *session_token == msg.session_token => {
+                println!("[STORAGE] Resuming session of client {}", msg.client_id);
+                if let Some(passenger) = self.passengers.get_mut(&msg.client_id) {
+                    passenger.passenger_sender = Some(msg.sender.clone());
+                }
+                if let Some(driver) = self.drivers.get_mut(&msg.client_id) {
+                    driver.driver_sender = Some(msg.sender);
+                }
+                SessionStatus::Resumed
+            }
+            Some(_) => {
+                eprintln!(
+                    "[STORAGE] Invalid session token for client {}",
+                    msg.client_id
+                );
+                SessionStatus::Rejected
+            }
+        }
+    }
+}
+
+impl Handler<SetSession> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetSession, _: &mut Self::Context) {
+        println!(
+            "[STORAGE - UPDATE] Setting session of client {}",
+            msg.client_id
+        );
+        self.sessions.insert(msg.client_id, msg.session_token);
+    }
+}
 
 impl Handler<InsertDriver> for Storage {
     type Result = ();
@@ -34,6 +83,7 @@ impl Handler<InsertDriver> for Storage {
                 );
             }
         }
+        self.driver_index.insert(msg.id, msg.driver_position);
     }
 }
 
@@ -47,7 +97,9 @@ impl Handler<InsertPassenger> for Storage {
             PassengerEntity {
                 passenger_position: msg.passenger_position,
                 passenger_destination: msg.passenger_destination,
+                fare: msg.fare,
                 passenger_sender: msg.passenger_sender,
+                queued_at: None,
             },
         );
     }
@@ -88,6 +140,10 @@ impl Handler<UpdateDriver> for Storage {
             driver.status = msg.status;
             driver.current_passenger_id = msg.passenger_id;
             driver.time_stamp = msg.time_stamp;
+            if let Some(position) = msg.driver_position {
+                driver.driver_position = position;
+                self.driver_index.insert(msg.driver_id, position);
+            }
         } else {
             eprintln!("Driver with id {:?} not found", msg.driver_id);
         }
@@ -100,6 +156,8 @@ impl Handler<RemoveDriver> for Storage {
     fn handle(&mut self, msg: RemoveDriver, _: &mut Self::Context) {
         println!("[STORAGE - DELETE] Removing driver with id {:?}", msg.id);
         self.drivers.remove(&msg.id);
+        self.driver_index.remove(&msg.id);
+        self.prune_session(msg.id);
     }
 }
 
@@ -109,34 +167,308 @@ impl Handler<RemovePassenger> for Storage {
     fn handle(&mut self, msg: RemovePassenger, _: &mut Self::Context) {
         println!("[STORAGE - DELETE] Removing passenger with id {:?}", msg.id);
         self.passengers.remove(&msg.id);
+        self.prune_session(msg.id);
     }
 }
 
 impl Handler<GetNearestDriver> for Storage {
-    type Result = Option<SocketAddr>;
-
-    fn handle(&mut self, _: GetNearestDriver, _: &mut Self::Context) -> Self::Result {
-        println!("[STORAGE - GET] Getting nearest driver");
-        let mut nearest_driver_addr: Option<SocketAddr> = None;
-        let mut nearest_distance = f32::INFINITY;
-
-        for (addr, driver) in self.drivers.iter() {
-            if driver.status == DriverStatus::Active {
-                let distance = ((driver.driver_position.0).powi(2)
-                    + (driver.driver_position.1).powi(2))
-                .sqrt();
-                if distance < nearest_distance {
-                    nearest_distance = distance;
-                    nearest_driver_addr = Some(*addr);
+    type Result = Vec<ClientId>;
+
+    fn handle(&mut self, msg: GetNearestDriver, _: &mut Self::Context) -> Self::Result {
+        println!(
+            "[STORAGE - GET] Getting nearest drivers to {:?}",
+            msg.pickup
+        );
+        let drivers = &self.drivers;
+        let nearest_drivers = self
+            .driver_index
+            .nearest(msg.pickup, msg.max_candidates, |addr| {
+                drivers
+                    .get(addr)
+                    .is_some_and(|driver| driver.status == DriverStatus::Active)
+            });
+        println!("[STORAGE - GET] Nearest drivers are {:?}", nearest_drivers);
+
+        nearest_drivers
+    }
+}
+
+impl Handler<ComputeSurge> for Storage {
+    type Result = MessageResult<ComputeSurge>;
+
+    fn handle(&mut self, msg: ComputeSurge, _: &mut Self::Context) -> Self::Result {
+        let (demand, supply) = self.zone_demand_and_supply(msg.zone, msg.passenger_id);
+        let multiplier = surge_multiplier(demand, supply);
+        println!(
+            "[STORAGE] Surge multiplier of zone {:?} is {} ({} passengers, {} drivers)",
+            msg.zone, multiplier, demand, supply
+        );
+        let previous = self.surge_multipliers.insert(msg.zone, multiplier);
+        MessageResult((multiplier, previous != Some(multiplier)))
+    }
+}
+
+impl Handler<SetSurge> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetSurge, _: &mut Self::Context) {
+        println!(
+            "[STORAGE - UPDATE] Setting surge multiplier of zone {:?} to {}",
+            msg.zone, msg.multiplier
+        );
+        self.surge_multipliers.insert(msg.zone, msg.multiplier);
+    }
+}
+
+impl Handler<StartDispatch> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, msg: StartDispatch, _: &mut Self::Context) {
+        println!(
+            "[STORAGE] Dispatching trip of passenger {:?} to {:?}",
+            msg.passenger_id, msg.candidates
+        );
+        let dispatch = self
+            .dispatches
+            .entry(msg.passenger_id)
+            .or_insert_with(|| DispatchEntity {
+                candidates: Default::default(),
+                tried_drivers: Default::default(),
+                current_offer: None,
+                started: Instant::now(),
+            });
+        dispatch.candidates = msg.candidates.into();
+    }
+}
+
+impl Handler<NextDispatchOffer> for Storage {
+    type Result = DispatchOffer;
+
+    fn handle(&mut self, msg: NextDispatchOffer, _: &mut Self::Context) -> Self::Result {
+        let Some(dispatch) = self.dispatches.get_mut(&msg.passenger_id) else {
+            return DispatchOffer::Cancelled;
+        };
+
+        if dispatch.started.elapsed() <= msg.dispatch_timeout {
+            while let Some(driver_id) = dispatch.candidates.pop_front() {
+                if !dispatch.tried_drivers.insert(driver_id) {
+                    continue;
+                }
+                let Some(driver) = self.drivers.get_mut(&driver_id) else {
+                    continue;
+                };
+                if driver.status != DriverStatus::Active {
+                    continue;
+                }
+                if let Some(driver_sender) = driver.driver_sender.clone() {
+                    driver.status = DriverStatus::Waiting;
+                    driver.current_passenger_id = Some(msg.passenger_id);
+                    driver.time_stamp = Instant::now();
+                    dispatch.current_offer = Some(driver_id);
+                    println!(
+                        "[STORAGE] Offering trip of passenger {:?} to driver {:?}",
+                        msg.passenger_id, driver_id
+                    );
+                    return DispatchOffer::Offer {
+                        driver_id,
+                        driver_sender,
+                    };
                 }
             }
         }
+
+        println!(
+            "[STORAGE] No more drivers to offer the trip of passenger {:?}",
+            msg.passenger_id
+        );
+        self.dispatches.remove(&msg.passenger_id);
+        DispatchOffer::Exhausted
+    }
+}
+
+impl Handler<ExpireOffer> for Storage {
+    type Result = bool;
+
+    fn handle(&mut self, msg: ExpireOffer, _: &mut Self::Context) -> Self::Result {
+        let Some(dispatch) = self.dispatches.get_mut(&msg.passenger_id) else {
+            return false;
+        };
+        if dispatch.current_offer != Some(msg.driver_id) {
+            return false;
+        }
+
         println!(
-            "[STORAGE - GET] Nearest driver is {:?}",
-            nearest_driver_addr
+            "[STORAGE] Offer of passenger {:?} to driver {:?} expired",
+            msg.passenger_id, msg.driver_id
         );
+        dispatch.current_offer = None;
+        // The driver is available again, if it is down the reaper removes it once it stops sending its position
+        if let Some(driver) = self.drivers.get_mut(&msg.driver_id) {
+            if driver.current_passenger_id == Some(msg.passenger_id) {
+                driver.status = DriverStatus::Active;
+                driver.current_passenger_id = None;
+            }
+        }
+        true
+    }
+}
+
+impl Handler<AnswerOffer> for Storage {
+    type Result = bool;
+
+    fn handle(&mut self, msg: AnswerOffer, _: &mut Self::Context) -> Self::Result {
+        let Some(dispatch) = self.dispatches.get_mut(&msg.passenger_id) else {
+            return false;
+        };
+        if dispatch.current_offer != Some(msg.driver_id) {
+            return false;
+        }
+
+        if msg.is_accepted {
+            self.dispatches.remove(&msg.passenger_id);
+            if let Some(passenger) = self.passengers.get_mut(&msg.passenger_id) {
+                passenger.queued_at = None;
+            }
+        } else {
+            dispatch.current_offer = None;
+        }
+        true
+    }
+}
+
+impl Handler<EnqueuePassenger> for Storage {
+    type Result = Option<(usize, Duration)>;
+
+    fn handle(&mut self, msg: EnqueuePassenger, _: &mut Self::Context) -> Self::Result {
+        let passenger = self.passengers.get_mut(&msg.id)?;
+        // A passenger that goes back to the queue keeps its original place
+        let waited = passenger
+            .queued_at
+            .get_or_insert_with(Instant::now)
+            .elapsed();
+        println!("[STORAGE] Passenger {:?} waits in the trip queue", msg.id);
+
+        self.queued_passengers()
+            .iter()
+            .find(|queued| queued.passenger_id == msg.id)
+            .map(|queued| (queued.position, waited))
+    }
+}
+
+impl Handler<SetPassengerQueued> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetPassengerQueued, _: &mut Self::Context) {
+        println!(
+            "[STORAGE - UPDATE] Setting passenger {:?} queued for {:?}",
+            msg.id, msg.queued_for
+        );
+        if let Some(passenger) = self.passengers.get_mut(&msg.id) {
+            // The coordinator's waiting time is kept, so the queue order survives a change of coordinator
+            passenger.queued_at = msg.queued_for.map(|queued_for| {
+                let now = Instant::now();
+                now.checked_sub(queued_for).unwrap_or(now)
+            });
+        }
+    }
+}
+
+impl Handler<TakeQueuedPassenger> for Storage {
+    type Result = Option<ClientId>;
+
+    fn handle(&mut self, msg: TakeQueuedPassenger, _: &mut Self::Context) -> Self::Result {
+        let driver = self.drivers.get(&msg.driver_id)?;
+        if driver.status != DriverStatus::Active {
+            return None;
+        }
+        let driver_position = driver.driver_position;
 
-        nearest_driver_addr
+        // Passengers that may be taken, in arrival order, up to the first one that can't be overtaken anymore
+        let overtake_window = Duration::from_secs(QUEUE_OVERTAKE_WINDOW);
+        let mut eligible = Vec::new();
+        for (id, passenger, queued_at) in self.queue_order() {
+            if self.dispatches.contains_key(&id) || msg.rejected == Some(id) {
+                continue;
+            }
+            eligible.push((distance(driver_position, passenger.passenger_position), id));
+            if queued_at.elapsed() >= overtake_window {
+                break;
+            }
+        }
+        // min_by keeps the first of equally near passengers, the one that arrived first
+        let taken = eligible
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, id)| id);
+
+        if let Some(passenger_id) = taken {
+            println!(
+                "[STORAGE] Taking passenger {:?} from the trip queue for driver {:?}",
+                passenger_id, msg.driver_id
+            );
+        }
+        taken
+    }
+}
+
+impl Handler<GetQueuedPassengers> for Storage {
+    type Result = Vec<QueuedPassenger>;
+
+    fn handle(&mut self, _: GetQueuedPassengers, _: &mut Self::Context) -> Self::Result {
+        self.queued_passengers()
+    }
+}
+
+impl Handler<ExpireQueuedPassengers> for Storage {
+    type Result = Vec<QueuedPassenger>;
+
+    fn handle(&mut self, msg: ExpireQueuedPassengers, _: &mut Self::Context) -> Self::Result {
+        let expired: Vec<QueuedPassenger> = self
+            .queued_passengers()
+            .into_iter()
+            .filter(|queued| !self.dispatches.contains_key(&queued.passenger_id))
+            .filter(|queued| {
+                self.passengers[&queued.passenger_id]
+                    .queued_at
+                    .is_some_and(|queued_at| queued_at.elapsed() >= msg.max_wait)
+            })
+            .collect();
+
+        for queued in &expired {
+            println!(
+                "[STORAGE - DELETE] Passenger {:?} waited too long in the trip queue",
+                queued.passenger_id
+            );
+            self.passengers.remove(&queued.passenger_id);
+            self.prune_session(queued.passenger_id);
+        }
+        expired
+    }
+}
+
+impl Storage {
+    /// Passengers waiting in the trip queue with the time they were queued, in arrival order.
+    fn queue_order(&self) -> Vec<(ClientId, &PassengerEntity, Instant)> {
+        let mut queue: Vec<(ClientId, &PassengerEntity, Instant)> = self
+            .passengers
+            .iter()
+            .filter_map(|(id, passenger)| Some((*id, passenger, passenger.queued_at?)))
+            .collect();
+        queue.sort_by_key(|(id, _, queued_at)| (*queued_at, *id));
+        queue
+    }
+
+    /// Passengers waiting in the trip queue with their position, in arrival order.
+    fn queued_passengers(&self) -> Vec<QueuedPassenger> {
+        self.queue_order()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, passenger, _))| QueuedPassenger {
+                passenger_id: id,
+                position: index + 1,
+                passenger_sender: passenger.passenger_sender.clone(),
+            })
+            .collect()
     }
 }
 
@@ -152,6 +484,8 @@ impl Handler<FinishTrip> for Storage {
             driver.status = DriverStatus::Active;
             driver.time_stamp = std::time::Instant::now();
             driver.driver_position = msg.destination_pos;
+            self.driver_index
+                .insert(msg.driver_id_ft, msg.destination_pos);
         }
         if self.passengers.remove(&msg.passenger_id_ft).is_none() {
             eprintln!(
@@ -177,20 +511,17 @@ impl Handler<IsFinished> for Storage {
 impl Handler<ReapDeadDrivers> for Storage {
     type Result = Vec<DeadDriver>;
 
-    fn handle(&mut self, _: ReapDeadDrivers, _: &mut Context<Self>) -> Vec<DeadDriver> {
+    fn handle(&mut self, msg: ReapDeadDrivers, _: &mut Context<Self>) -> Vec<DeadDriver> {
         println!("[STORAGE - REAPER] Reaping dead drivers . . .");
         let mut dead_drivers = Vec::new();
         for (driver_id, driver) in self.drivers.iter_mut() {
             if matches!(driver.status, DriverStatus::Waiting)
-                && driver.time_stamp.elapsed().as_secs() > 3
+                && driver.time_stamp.elapsed() > msg.timeout
             {
                 let passenger_id = driver.current_passenger_id;
-                let passenger_sender = self
-                    .passengers
-                    .get(&passenger_id.unwrap())
-                    .unwrap()
-                    .passenger_sender
-                    .clone();
+                let passenger_sender = passenger_id
+                    .and_then(|id| self.passengers.get(&id))
+                    .and_then(|passenger| passenger.passenger_sender.clone());
 
                 let dead_driver = DeadDriver {
                     driver_id: *driver_id,
@@ -210,9 +541,356 @@ impl Handler<ReapDeadDrivers> for Storage {
 
         for dead_driver in &dead_drivers {
             self.drivers.remove(&dead_driver.driver_id);
-            self.passengers.remove(&dead_driver.passenger_id.unwrap());
+            self.driver_index.remove(&dead_driver.driver_id);
+            self.prune_session(dead_driver.driver_id);
+            if let Some(passenger_id) = dead_driver.passenger_id {
+                self.passengers.remove(&passenger_id);
+                self.dispatches.remove(&passenger_id);
+                self.prune_session(passenger_id);
+            }
         }
 
         dead_drivers
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix::{Actor, Addr};
+    use common::tcp_sender::TcpSender;
+    use std::sync::Arc;
+
+    const PASSENGER: ClientId = 1;
+
+    fn passenger(position: (f32, f32), queued_for: Option<u64>) -> PassengerEntity {
+        PassengerEntity {
+            passenger_position: position,
+            passenger_destination: (5.0, 5.0),
+            fare: 10.0,
+            passenger_sender: None,
+            queued_at: queued_for.map(|secs| Instant::now() - Duration::from_secs(secs)),
+        }
+    }
+
+    fn driver_storage(drivers: &[(ClientId, DriverStatus)]) -> Storage {
+        let driver_sender = Arc::new(TcpSender { write: None }.start());
+        let mut storage = Storage::new();
+        for (id, status) in drivers {
+            storage.drivers.insert(
+                *id,
+                DriverEntity {
+                    driver_position: (1.0, 1.0),
+                    current_passenger_id: None,
+                    driver_sender: Some(driver_sender.clone()),
+                    status: status.clone(),
+                    time_stamp: Instant::now(),
+                },
+            );
+        }
+        storage
+    }
+
+    fn storage(drivers: &[(ClientId, DriverStatus)]) -> Addr<Storage> {
+        let mut storage = driver_storage(drivers);
+        storage
+            .passengers
+            .insert(PASSENGER, passenger((0.0, 0.0), None));
+        storage.start()
+    }
+
+    async fn take_queued(
+        storage: &Addr<Storage>,
+        driver_id: ClientId,
+        rejected: Option<ClientId>,
+    ) -> Option<ClientId> {
+        storage
+            .send(TakeQueuedPassenger {
+                driver_id,
+                rejected,
+            })
+            .await
+            .unwrap()
+    }
+
+    async fn start_dispatch(storage: &Addr<Storage>, candidates: Vec<ClientId>) {
+        storage
+            .send(StartDispatch {
+                passenger_id: PASSENGER,
+                candidates,
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn next_offer(storage: &Addr<Storage>) -> Option<ClientId> {
+        match storage
+            .send(NextDispatchOffer {
+                passenger_id: PASSENGER,
+                dispatch_timeout: Duration::from_secs(15),
+            })
+            .await
+            .unwrap()
+        {
+            DispatchOffer::Offer { driver_id, .. } => Some(driver_id),
+            _ => None,
+        }
+    }
+
+    async fn answer(storage: &Addr<Storage>, driver_id: ClientId, is_accepted: bool) -> bool {
+        storage
+            .send(AnswerOffer {
+                passenger_id: PASSENGER,
+                driver_id,
+                is_accepted,
+            })
+            .await
+            .unwrap()
+    }
+
+    async fn expire(storage: &Addr<Storage>, driver_id: ClientId) -> bool {
+        storage
+            .send(ExpireOffer {
+                passenger_id: PASSENGER,
+                driver_id,
+            })
+            .await
+            .unwrap()
+    }
+
+    async fn driver(storage: &Addr<Storage>, id: ClientId) -> DriverEntity {
+        storage.send(GetDriver { id }).await.unwrap().unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn offered_driver_waits_until_it_accepts() {
+        let storage = storage(&[(10, DriverStatus::Active)]);
+        start_dispatch(&storage, vec![10]).await;
+
+        assert_eq!(next_offer(&storage).await, Some(10));
+        assert_eq!(driver(&storage, 10).await.status, DriverStatus::Waiting);
+        assert!(answer(&storage, 10, true).await);
+        // the dispatch is over, so a repeated answer is not pending anymore
+        assert!(!answer(&storage, 10, true).await);
+    }
+
+    #[actix_rt::test]
+    async fn rejected_trip_is_offered_to_the_next_active_candidate() {
+        let storage = storage(&[
+            (10, DriverStatus::Active),
+            (11, DriverStatus::OnTrip),
+            (12, DriverStatus::Active),
+        ]);
+        start_dispatch(&storage, vec![10, 11, 12]).await;
+
+        assert_eq!(next_offer(&storage).await, Some(10));
+        assert!(answer(&storage, 10, false).await);
+        assert_eq!(next_offer(&storage).await, Some(12));
+        assert!(answer(&storage, 12, false).await);
+        assert_eq!(next_offer(&storage).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn tried_drivers_are_not_offered_again() {
+        let storage = storage(&[(10, DriverStatus::Active)]);
+        start_dispatch(&storage, vec![10]).await;
+        next_offer(&storage).await;
+        answer(&storage, 10, false).await;
+
+        start_dispatch(&storage, vec![10]).await;
+        assert_eq!(next_offer(&storage).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn expired_offer_makes_the_driver_active_again() {
+        let storage = storage(&[(10, DriverStatus::Active), (11, DriverStatus::Active)]);
+        start_dispatch(&storage, vec![10, 11]).await;
+        next_offer(&storage).await;
+
+        assert!(expire(&storage, 10).await);
+        let driver_10 = driver(&storage, 10).await;
+        assert_eq!(driver_10.status, DriverStatus::Active);
+        assert_eq!(driver_10.current_passenger_id, None);
+        assert!(!expire(&storage, 10).await);
+
+        assert_eq!(next_offer(&storage).await, Some(11));
+        // the late answer of the first driver doesn't count, only the pending offer does
+        assert!(!answer(&storage, 10, true).await);
+        assert!(answer(&storage, 11, true).await);
+    }
+
+    async fn queue_positions(storage: &Addr<Storage>) -> Vec<(ClientId, usize)> {
+        storage
+            .send(GetQueuedPassengers)
+            .await
+            .unwrap()
+            .iter()
+            .map(|queued| (queued.passenger_id, queued.position))
+            .collect()
+    }
+
+    #[actix_rt::test]
+    async fn queue_positions_follow_the_arrival_order() {
+        let mut storage = driver_storage(&[(10, DriverStatus::Active)]);
+        storage
+            .passengers
+            .insert(2, passenger((50.0, 50.0), Some(QUEUE_OVERTAKE_WINDOW)));
+        storage.passengers.insert(3, passenger((2.0, 2.0), Some(1)));
+        let storage = storage.start();
+
+        assert_eq!(queue_positions(&storage).await, vec![(2, 1), (3, 2)]);
+        // the far passenger waited the whole overtake window, so the near one can't go first
+        assert_eq!(take_queued(&storage, 10, None).await, Some(2));
+    }
+
+    #[actix_rt::test]
+    async fn near_passenger_overtakes_only_within_the_overtake_window() {
+        let mut storage = driver_storage(&[(10, DriverStatus::Active)]);
+        storage
+            .passengers
+            .insert(2, passenger((50.0, 50.0), Some(QUEUE_OVERTAKE_WINDOW - 1)));
+        storage.passengers.insert(3, passenger((2.0, 2.0), Some(1)));
+        let storage = storage.start();
+
+        assert_eq!(queue_positions(&storage).await, vec![(2, 1), (3, 2)]);
+        assert_eq!(take_queued(&storage, 10, None).await, Some(3));
+    }
+
+    #[actix_rt::test]
+    async fn expired_passengers_leave_the_queue() {
+        let mut storage = Storage::new();
+        storage
+            .passengers
+            .insert(2, passenger((2.0, 2.0), Some(30)));
+        storage.passengers.insert(3, passenger((2.0, 2.0), Some(1)));
+        let storage = storage.start();
+
+        let expired: Vec<ClientId> = storage
+            .send(ExpireQueuedPassengers {
+                max_wait: Duration::from_secs(20),
+            })
+            .await
+            .unwrap()
+            .iter()
+            .map(|queued| queued.passenger_id)
+            .collect();
+
+        assert_eq!(expired, vec![2]);
+        assert_eq!(queue_positions(&storage).await, vec![(3, 1)]);
+    }
+
+    #[actix_rt::test]
+    async fn queue_positions_without_drivers_follow_the_arrival_order() {
+        let mut storage = Storage::new();
+        storage
+            .passengers
+            .insert(2, passenger((50.0, 50.0), Some(10)));
+        storage.passengers.insert(3, passenger((2.0, 2.0), Some(1)));
+        let storage = storage.start();
+
+        assert_eq!(queue_positions(&storage).await, vec![(2, 1), (3, 2)]);
+    }
+
+    #[actix_rt::test]
+    async fn only_active_drivers_take_queued_passengers() {
+        let mut storage = driver_storage(&[
+            (10, DriverStatus::Waiting),
+            (11, DriverStatus::OnTrip),
+            (12, DriverStatus::Active),
+        ]);
+        storage.passengers.insert(2, passenger((2.0, 2.0), Some(1)));
+        let storage = storage.start();
+
+        assert_eq!(take_queued(&storage, 10, None).await, None);
+        assert_eq!(take_queued(&storage, 11, None).await, None);
+        assert_eq!(take_queued(&storage, 12, None).await, Some(2));
+    }
+
+    #[actix_rt::test]
+    async fn rejected_passenger_is_not_taken_again_by_the_same_driver() {
+        let mut storage = driver_storage(&[(10, DriverStatus::Active)]);
+        storage
+            .passengers
+            .insert(2, passenger((2.0, 2.0), Some(10)));
+        storage
+            .passengers
+            .insert(3, passenger((30.0, 30.0), Some(1)));
+        let storage = storage.start();
+
+        assert_eq!(take_queued(&storage, 10, Some(2)).await, Some(3));
+        assert_eq!(take_queued(&storage, 10, Some(3)).await, Some(2));
+    }
+
+    #[actix_rt::test]
+    async fn only_drivers_waiting_longer_than_the_timeout_are_reaped() {
+        let storage = driver_storage(&[(10, DriverStatus::Waiting), (11, DriverStatus::Active)]);
+        let storage = storage.start();
+        let reap = |timeout| ReapDeadDrivers { timeout };
+
+        assert!(storage
+            .send(reap(Duration::from_secs(3)))
+            .await
+            .unwrap()
+            .is_empty());
+        let reaped: Vec<ClientId> = storage
+            .send(reap(Duration::ZERO))
+            .await
+            .unwrap()
+            .iter()
+            .map(|dead| dead.driver_id)
+            .collect();
+        assert_eq!(reaped, vec![10]);
+    }
+
+    #[actix_rt::test]
+    async fn replicas_keep_the_waiting_time_of_the_coordinator() {
+        let storage = storage(&[]);
+        storage
+            .send(SetPassengerQueued {
+                id: PASSENGER,
+                queued_for: Some(Duration::from_secs(30)),
+            })
+            .await
+            .unwrap();
+        let queued_at = storage
+            .send(GetPassenger { id: PASSENGER })
+            .await
+            .unwrap()
+            .unwrap()
+            .queued_at
+            .unwrap();
+        // enqueueing again keeps the replicated place in the queue
+        let (position, waited) = storage
+            .send(EnqueuePassenger { id: PASSENGER })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(queued_at.elapsed() >= Duration::from_secs(30));
+        assert_eq!(position, 1);
+        assert!(waited >= Duration::from_secs(30));
+    }
+
+    #[actix_rt::test]
+    async fn surge_is_replicated_only_when_it_changes() {
+        let storage = storage(&[(10, DriverStatus::Active)]);
+        let compute = |passenger_id| ComputeSurge {
+            zone: (0, 0),
+            passenger_id,
+        };
+
+        // passenger 1 and the new request of passenger 2 for one active driver
+        assert_eq!(storage.send(compute(2)).await.unwrap(), (2.0, true));
+        assert_eq!(storage.send(compute(2)).await.unwrap(), (2.0, false));
+
+        // a multiplier replicated by the coordinator is not replicated again
+        storage
+            .send(SetSurge {
+                zone: (0, 0),
+                multiplier: 1.0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(storage.send(compute(1)).await.unwrap(), (1.0, false));
+    }
+}
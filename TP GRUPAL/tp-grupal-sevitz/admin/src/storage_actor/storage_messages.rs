@@ -1,37 +1,90 @@
 use crate::admin_actor::clients_to_admin::DriverStatus;
 use crate::utils::entities::{DriverEntity, PassengerEntity};
-use actix::{Addr, Message};
+use crate::utils::surge::Zone;
+use actix::{Addr, Message, MessageResponse};
+use common::messages::ClientId;
 use common::tcp_sender::TcpSender;
-use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct DeadDriver {
-    pub driver_id: SocketAddr,
-    pub passenger_id: Option<SocketAddr>,
+    pub driver_id: ClientId,
+    pub passenger_id: Option<ClientId>,
     pub passenger_sender: Option<Arc<Addr<TcpSender>>>,
 }
 
+/// Passenger waiting in the trip queue, with its position (starting from 1) in dispatch order.
+pub struct QueuedPassenger {
+    pub passenger_id: ClientId,
+    pub position: usize,
+    pub passenger_sender: Option<Arc<Addr<TcpSender>>>,
+}
+
+/// Result of the registration of a client.
+/// New: the client registered for the first time.
+/// Resumed: the client was already registered, its passenger or driver is now bound to the new connection.
+/// Rejected: the session token doesn't match the one the client registered with.
+#[derive(MessageResponse, Debug, PartialEq)]
+pub enum SessionStatus {
+    New,
+    Resumed,
+    Rejected,
+}
+
+/// Next step of the dispatch of a trip.
+/// Offer: the trip has to be offered to the given driver, already marked as Waiting.
+/// Exhausted: every candidate was tried or the dispatch deadline passed, the trip has to be rejected.
+/// Cancelled: the trip is no longer being dispatched.
+#[derive(MessageResponse)]
+pub enum DispatchOffer {
+    Offer {
+        driver_id: ClientId,
+        driver_sender: Arc<Addr<TcpSender>>,
+    },
+    Exhausted,
+    Cancelled,
+}
+
+#[derive(Message)]
+#[rtype(result = "SessionStatus")]
+/// Message to register a client with its session token, binding its passenger or driver
+/// (if it has one) to the connection of the given sender.
+pub struct RegisterSession {
+    pub client_id: ClientId,
+    pub session_token: u64,
+    pub sender: Arc<Addr<TcpSender>>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Message to set the session token of a client, as registered by the coordinator.
+pub struct SetSession {
+    pub client_id: ClientId,
+    pub session_token: u64,
+}
+
 #[derive(Message)]
 #[rtype(result = "Option<PassengerEntity>")]
 /// Message to get a passenger from the storage.
 pub struct GetPassenger {
-    pub id: SocketAddr,
+    pub id: ClientId,
 }
 
 #[derive(Message)]
 #[rtype(result = "Option<DriverEntity>")]
 /// Message to get a driver from the storage.
 pub struct GetDriver {
-    pub id: SocketAddr,
+    pub id: ClientId,
 }
 
 #[derive(Message)]
 #[rtype(result = "()")]
 /// Message to update a driver in the storage.
+/// The position is only updated if `driver_position` is provided.
 pub struct UpdateDriver {
-    pub driver_id: SocketAddr,
-    pub passenger_id: Option<SocketAddr>,
+    pub driver_id: ClientId,
+    pub passenger_id: Option<ClientId>,
+    pub driver_position: Option<(f32, f32)>,
     pub status: DriverStatus,
     pub time_stamp: Instant,
 }
@@ -40,9 +93,9 @@ pub struct UpdateDriver {
 #[rtype(result = "()")]
 /// Message to insert a driver in the storage.
 pub struct InsertDriver {
-    pub id: SocketAddr,
+    pub id: ClientId,
     pub driver_position: (f32, f32),
-    pub current_passenger_id: Option<SocketAddr>,
+    pub current_passenger_id: Option<ClientId>,
     pub driver_sender: Option<Arc<Addr<TcpSender>>>,
     pub status: DriverStatus,
     pub time_stamp: Instant,
@@ -52,9 +105,10 @@ pub struct InsertDriver {
 #[rtype(result = "()")]
 /// Message to insert a passenger in the storage.
 pub struct InsertPassenger {
-    pub id: SocketAddr,
+    pub id: ClientId,
     pub passenger_position: (f32, f32),
     pub passenger_destination: (f32, f32),
+    pub fare: f32,
     pub passenger_sender: Option<Arc<Addr<TcpSender>>>,
 }
 
@@ -62,29 +116,133 @@ pub struct InsertPassenger {
 #[rtype(result = "()")]
 /// Message to remove a passenger from the storage.
 pub struct RemovePassenger {
-    pub id: SocketAddr,
+    pub id: ClientId,
 }
 
 #[derive(Message)]
 #[rtype(result = "()")]
 /// Message to remove a driver from the storage.
 pub struct RemoveDriver {
-    pub id: SocketAddr,
+    pub id: ClientId,
 }
 
 #[derive(Message)]
-#[rtype(result = "Option<SocketAddr>")]
-/// Message to get the nearest driver to a passenger.
-pub struct GetNearestDriver;
+#[rtype(result = "Vec<ClientId>")]
+/// Message to get the active drivers nearest to a passenger's pickup point,
+/// ranked from nearest to farthest and limited to `max_candidates`.
+pub struct GetNearestDriver {
+    pub pickup: (f32, f32),
+    pub max_candidates: usize,
+}
+
+#[derive(Message)]
+#[rtype(result = "(f32, bool)")]
+/// Message to compute the surge multiplier of a zone for a new trip request of `passenger_id` from it,
+/// counting the request as demand along with the other passengers of the zone still waiting for a driver.
+/// The multiplier is kept as the last one quoted in the zone, returns it and whether it changed
+/// from the previous one, so only changes are replicated.
+pub struct ComputeSurge {
+    pub zone: Zone,
+    pub passenger_id: ClientId,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Message to set the surge multiplier of a zone, as quoted by the coordinator.
+pub struct SetSurge {
+    pub zone: Zone,
+    pub multiplier: f32,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Message to start dispatching the trip of a passenger to the given candidates, ranked from best to worst.
+/// If the trip was already being dispatched, the drivers that were already tried are still skipped.
+pub struct StartDispatch {
+    pub passenger_id: ClientId,
+    pub candidates: Vec<ClientId>,
+}
+
+#[derive(Message)]
+#[rtype(result = "DispatchOffer")]
+/// Message to pick the next active candidate to offer the trip of a passenger to,
+/// unless `dispatch_timeout` passed since the first offer.
+pub struct NextDispatchOffer {
+    pub passenger_id: ClientId,
+    pub dispatch_timeout: Duration,
+}
+
+#[derive(Message)]
+#[rtype(result = "bool")]
+/// Message to expire the offer of a trip to a driver that didn't answer in time, the driver becomes Active again.
+/// Returns whether the offer was still pending.
+pub struct ExpireOffer {
+    pub passenger_id: ClientId,
+    pub driver_id: ClientId,
+}
+
+#[derive(Message)]
+#[rtype(result = "bool")]
+/// Message to register the answer of a driver to the offer of a trip.
+/// Returns whether the offer was still pending, late answers must be ignored.
+pub struct AnswerOffer {
+    pub passenger_id: ClientId,
+    pub driver_id: ClientId,
+    pub is_accepted: bool,
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<(usize, Duration)>")]
+/// Message to put a passenger without an available driver in the trip queue.
+/// Returns its position in the queue and how long it has been waiting in it,
+/// or None if the passenger is not in the storage.
+pub struct EnqueuePassenger {
+    pub id: ClientId,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+/// Message to set how long a passenger has been waiting in the trip queue, as replicated by the coordinator.
+/// None if the passenger is not queued.
+pub struct SetPassengerQueued {
+    pub id: ClientId,
+    pub queued_for: Option<Duration>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<ClientId>")]
+/// Message to pick the queued passenger whose trip is offered to a driver that became available.
+/// The driver takes the nearest passenger, but only among the ones that arrived before the first passenger
+/// that waited `QUEUE_OVERTAKE_WINDOW` seconds, so nobody is overtaken after that.
+/// The passenger stays in the queue until a driver accepts the trip.
+/// `rejected` is the passenger whose trip the driver has just rejected, it isn't offered to it again.
+pub struct TakeQueuedPassenger {
+    pub driver_id: ClientId,
+    pub rejected: Option<ClientId>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<QueuedPassenger>")]
+/// Message to get the passengers waiting in the trip queue.
+pub struct GetQueuedPassengers;
+
+#[derive(Message)]
+#[rtype(result = "Vec<QueuedPassenger>")]
+/// Message to remove the passengers that waited in the trip queue longer than `max_wait`.
+pub struct ExpireQueuedPassengers {
+    pub max_wait: Duration,
+}
 
 #[derive(Message)]
 #[rtype(result = "bool")]
 /// Message to check if a passenger is finished.
 pub struct IsFinished {
-    pub passenger_id: SocketAddr,
+    pub passenger_id: ClientId,
 }
 
 #[derive(Message)]
 #[rtype(result = "Vec<DeadDriver>")]
-/// Message to reap dead drivers.
-pub struct ReapDeadDrivers;
+/// Message to reap dead drivers, the ones Waiting for longer than `timeout`.
+pub struct ReapDeadDrivers {
+    pub timeout: Duration,
+}
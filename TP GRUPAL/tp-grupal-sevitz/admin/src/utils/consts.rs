@@ -1,5 +1,9 @@
-pub const PING_INTERVAL: u64 = 2;
-pub const MAX_RETRIES: u8 = 3;
-pub const PAYMENT_GATEWAY_PORT: u16 = 8085;
-pub const PAYMENT_GATEWAY_IP: &str = "127.0.0.1";
-pub const MAX_TIME_WITHOUT_PINGING: u64 = 5;
+pub const GRID_CELL_SIZE: f32 = 2.0;
+pub const MAX_DRIVER_CANDIDATES: usize = 5;
+pub const BASE_FARE: f32 = 5.0;
+pub const FARE_PER_DISTANCE: f32 = 2.0;
+pub const FARE_PER_SECOND: f32 = 0.5;
+pub const TRIP_SPEED: f32 = 1.0;
+pub const SURGE_ZONE_SIZE: f32 = 10.0;
+pub const MAX_SURGE_MULTIPLIER: f32 = 3.0;
+pub const QUEUE_OVERTAKE_WINDOW: u64 = 10;
@@ -1,7 +1,8 @@
 use crate::admin_actor::clients_to_admin::DriverStatus;
 use actix::Addr;
+use common::messages::ClientId;
 use common::tcp_sender::TcpSender;
-use std::net::SocketAddr;
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -9,14 +10,24 @@ use std::time::Instant;
 pub struct PassengerEntity {
     pub passenger_position: (f32, f32),
     pub passenger_destination: (f32, f32),
+    pub fare: f32,
     pub passenger_sender: Option<Arc<Addr<TcpSender>>>,
+    pub queued_at: Option<Instant>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DriverEntity {
     pub driver_position: (f32, f32),
-    pub current_passenger_id: Option<SocketAddr>,
+    pub current_passenger_id: Option<ClientId>,
     pub driver_sender: Option<Arc<Addr<TcpSender>>>,
     pub status: DriverStatus,
     pub time_stamp: Instant,
 }
+
+#[derive(Debug, Clone)]
+pub struct DispatchEntity {
+    pub candidates: VecDeque<ClientId>,
+    pub tried_drivers: HashSet<ClientId>,
+    pub current_offer: Option<ClientId>,
+    pub started: Instant,
+}
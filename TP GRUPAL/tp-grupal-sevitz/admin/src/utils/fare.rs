@@ -0,0 +1,56 @@
+use super::consts::{BASE_FARE, FARE_PER_DISTANCE, FARE_PER_SECOND, TRIP_SPEED};
+use crate::storage_actor::spatial_index::distance;
+
+/// Price of a trip from `origin` to `destination`: a base fare plus a rate for the distance
+/// and another for the estimated duration of the trip, multiplied by the surge multiplier of the zone
+/// and rounded to cents.
+/// The clients travel at `TRIP_SPEED` units of distance per second.
+pub fn trip_fare(origin: (f32, f32), destination: (f32, f32), surge_multiplier: f32) -> f32 {
+    let trip_distance = distance(origin, destination);
+    let trip_seconds = trip_distance / TRIP_SPEED;
+    let fare = (BASE_FARE + trip_distance * FARE_PER_DISTANCE + trip_seconds * FARE_PER_SECOND)
+        * surge_multiplier;
+    (fare * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fare_of_an_empty_trip_is_the_base_fare() {
+        assert_eq!(trip_fare((3.0, 3.0), (3.0, 3.0), 1.0), BASE_FARE);
+    }
+
+    #[test]
+    fn fare_grows_with_the_distance_and_the_duration() {
+        assert_eq!(
+            trip_fare((0.0, 0.0), (3.0, 4.0), 1.0),
+            BASE_FARE + 5.0 * FARE_PER_DISTANCE + 5.0 / TRIP_SPEED * FARE_PER_SECOND
+        );
+    }
+
+    #[test]
+    fn fare_charges_the_estimated_duration() {
+        let distance_only = BASE_FARE + 5.0 * FARE_PER_DISTANCE;
+        let duration_fare = trip_fare((0.0, 0.0), (3.0, 4.0), 1.0) - distance_only;
+
+        assert_eq!(duration_fare, 5.0 / TRIP_SPEED * FARE_PER_SECOND);
+        assert!(duration_fare > 0.0);
+    }
+
+    #[test]
+    fn surge_multiplies_the_whole_fare() {
+        assert_eq!(
+            trip_fare((0.0, 0.0), (3.0, 4.0), 2.0),
+            2.0 * trip_fare((0.0, 0.0), (3.0, 4.0), 1.0)
+        );
+    }
+
+    #[test]
+    fn fare_is_rounded_to_cents() {
+        let fare = trip_fare((0.0, 0.0), (1.0, 1.0), 1.0);
+
+        assert_eq!(fare, 8.54);
+    }
+}
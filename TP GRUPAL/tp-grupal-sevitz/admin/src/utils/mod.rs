@@ -1,5 +1,7 @@
 pub mod admin_errors;
 pub mod consts;
 pub mod entities;
+pub mod fare;
 pub mod logs;
 pub mod payment_actions;
+pub mod surge;
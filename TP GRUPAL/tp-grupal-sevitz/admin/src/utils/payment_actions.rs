@@ -9,7 +9,7 @@ use common::payment_messages::{
     PaymentRequest, PaymentResponse,
 };
 use common::tcp_sender::TcpMessage;
-use rand::random;
+use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
@@ -78,13 +78,8 @@ impl Handler<AuthConfirmation> for Admin {
     }
 }
 
-pub async fn get_payment_response(msg: SendPaymentMessage) -> bool {
-    let gateway_addr = format!(
-        "{}:{}",
-        crate::utils::consts::PAYMENT_GATEWAY_IP,
-        crate::utils::consts::PAYMENT_GATEWAY_PORT
-    );
-    let tcp_stream = TcpStream::connect(&gateway_addr).await;
+pub async fn get_payment_response(msg: SendPaymentMessage, gateway_addr: SocketAddr) -> bool {
+    let tcp_stream = TcpStream::connect(gateway_addr).await;
     match tcp_stream {
         Ok(s) => {
             let (reader, mut writer) = s.into_split();
@@ -135,18 +130,18 @@ pub async fn get_payment_response(msg: SendPaymentMessage) -> bool {
     }
 }
 
-pub fn make_payment_check_message(passenger_id: String) -> SendPaymentMessage {
+pub fn make_payment_check_message(passenger_id: String, fare: f32) -> SendPaymentMessage {
     SendPaymentMessage {
         passenger_id,
-        amount: random::<f32>() * 100.0,
+        amount: fare,
         message_type: PaymentMessageType::Check,
     }
 }
 
-pub fn make_payment_done_message(passenger_id: String) -> SendPaymentMessage {
+pub fn make_payment_done_message(passenger_id: String, fare: f32) -> SendPaymentMessage {
     SendPaymentMessage {
         passenger_id,
-        amount: random::<f32>() * 100.0,
+        amount: fare,
         message_type: PaymentMessageType::Pay,
     }
 }
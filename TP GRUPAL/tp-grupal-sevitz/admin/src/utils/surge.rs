@@ -0,0 +1,53 @@
+use super::consts::{MAX_SURGE_MULTIPLIER, SURGE_ZONE_SIZE};
+
+/// Zone of the map, the surge multiplier is the same for every trip requested from it.
+pub type Zone = (i32, i32);
+
+/// Zone of the map that contains the given position.
+pub fn zone_of(position: (f32, f32)) -> Zone {
+    (
+        (position.0 / SURGE_ZONE_SIZE).floor() as i32,
+        (position.1 / SURGE_ZONE_SIZE).floor() as i32,
+    )
+}
+
+/// Multiplier applied to the fares of a zone, given the passengers waiting for a driver (demand)
+/// and the active drivers (supply) in it.
+/// It follows the demand/supply ratio, rounded to one decimal, without going below 1
+/// or above `MAX_SURGE_MULTIPLIER` (also used when there are no active drivers).
+pub fn surge_multiplier(demand: usize, supply: usize) -> f32 {
+    match (demand, supply) {
+        (0, 0) => 1.0,
+        (_, 0) => MAX_SURGE_MULTIPLIER,
+        _ => {
+            let ratio = demand as f32 / supply as f32;
+            ((ratio * 10.0).round() / 10.0).clamp(1.0, MAX_SURGE_MULTIPLIER)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_grouped_in_zones() {
+        assert_eq!(zone_of((0.0, 9.9)), (0, 0));
+        assert_eq!(zone_of((10.0, 25.0)), (1, 2));
+        assert_eq!(zone_of((-0.1, -10.0)), (-1, -1));
+    }
+
+    #[test]
+    fn multiplier_follows_the_demand_supply_ratio() {
+        assert_eq!(surge_multiplier(3, 2), 1.5);
+        assert_eq!(surge_multiplier(2, 3), 1.0);
+        assert_eq!(surge_multiplier(4, 3), 1.3);
+    }
+
+    #[test]
+    fn multiplier_is_capped() {
+        assert_eq!(surge_multiplier(10, 1), MAX_SURGE_MULTIPLIER);
+        assert_eq!(surge_multiplier(1, 0), MAX_SURGE_MULTIPLIER);
+        assert_eq!(surge_multiplier(0, 0), 1.0);
+    }
+}
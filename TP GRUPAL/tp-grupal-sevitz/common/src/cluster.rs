@@ -0,0 +1,192 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// Environment variable to use a cluster config file other than the default one
+pub const CLUSTER_CONFIG_VAR: &str = "CONCURIDE_CLUSTER_CONFIG";
+/// Cluster config file, relative to the working directory (the root of the workspace with `cargo run`)
+pub const DEFAULT_CLUSTER_CONFIG: &str = "cluster.json";
+
+/// Identifier of an admin node, the node with the lowest id is preferred as coordinator
+pub type NodeId = u64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// An admin node of the cluster
+pub struct ClusterNode {
+    pub id: NodeId,
+    #[serde(deserialize_with = "resolve_addr")]
+    pub addr: SocketAddr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Cluster config shared by the admins, the payment gateway and the clients
+pub struct ClusterConfig {
+    /// Initial admin nodes, admins can join or leave the cluster at runtime
+    pub nodes: Vec<ClusterNode>,
+    #[serde(deserialize_with = "resolve_addr")]
+    pub payment_gateway: SocketAddr,
+    /// Seconds between pings to the coordinator
+    pub ping_interval: u64,
+    /// Seconds without pings after which the coordinator considers an admin down
+    pub max_time_without_pinging: u64,
+    /// Connection attempts before considering a peer down
+    pub max_retries: u8,
+    /// Seconds a driver has to answer a trip offer before it is offered to the next candidate
+    #[serde(default = "default_offer_timeout")]
+    pub offer_timeout: u64,
+    /// Seconds since the first offer of a trip after which the passenger waits in the trip queue
+    #[serde(default = "default_dispatch_timeout")]
+    pub dispatch_timeout: u64,
+    /// Seconds a passenger can wait in the trip queue before its trip is rejected
+    #[serde(default = "default_max_queue_wait")]
+    pub max_queue_wait: u64,
+    /// Seconds between checks of the passengers that waited too long in the trip queue
+    #[serde(default = "default_queue_check_interval")]
+    pub queue_check_interval: u64,
+    /// Seconds a driver can stay Waiting without answering before the reaper removes it
+    #[serde(default = "default_dead_driver_timeout")]
+    pub dead_driver_timeout: u64,
+}
+
+fn default_offer_timeout() -> u64 {
+    2
+}
+
+fn default_dispatch_timeout() -> u64 {
+    15
+}
+
+fn default_max_queue_wait() -> u64 {
+    60
+}
+
+fn default_queue_check_interval() -> u64 {
+    5
+}
+
+fn default_dead_driver_timeout() -> u64 {
+    3
+}
+
+impl ClusterConfig {
+    /// Loads the config file given by `CONCURIDE_CLUSTER_CONFIG`, or `cluster.json` in the working directory.
+    pub fn load() -> io::Result<Self> {
+        let path = std::env::var(CLUSTER_CONFIG_VAR)
+            .unwrap_or_else(|_| DEFAULT_CLUSTER_CONFIG.to_string());
+        Self::from_file(&path)
+    }
+
+    /// Loads and validates a config file, nodes are sorted by id.
+    pub fn from_file(path: &str) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to read cluster config {}: {}", path, e),
+            )
+        })?;
+        let mut config: ClusterConfig = serde_json::from_str(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid cluster config {}: {}", path, e),
+            )
+        })?;
+
+        let ids: HashSet<NodeId> = config.nodes.iter().map(|node| node.id).collect();
+        let addrs: HashSet<SocketAddr> = config.nodes.iter().map(|node| node.addr).collect();
+        if ids.len() != config.nodes.len() || addrs.len() != config.nodes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Repeated node id or address in cluster config {}", path),
+            ));
+        }
+        config.nodes.sort_by_key(|node| node.id);
+
+        Ok(config)
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&ClusterNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn admin_addrs(&self) -> Vec<SocketAddr> {
+        self.nodes.iter().map(|node| node.addr).collect()
+    }
+}
+
+/// Resolves a "host:port" string, so nodes can be configured by hostname
+fn resolve_addr<'de, D>(deserializer: D) -> Result<SocketAddr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let addr = String::deserialize(deserializer)?;
+    addr.to_socket_addrs()
+        .map_err(serde::de::Error::custom)?
+        .next()
+        .ok_or_else(|| serde::de::Error::custom(format!("Unable to resolve {}", addr)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODES: &str =
+        r#"[{ "id": 2, "addr": "127.0.0.1:8081" }, { "id": 1, "addr": "127.0.0.1:8080" }]"#;
+
+    /// Writes a config with the given nodes to a temporary file and loads it
+    fn load_with_nodes(name: &str, nodes: &str) -> io::Result<ClusterConfig> {
+        let path =
+            std::env::temp_dir().join(format!("concuride_{}_{}.json", name, std::process::id()));
+        let content = format!(
+            r#"{{ "nodes": {}, "payment_gateway": "127.0.0.1:8085", "ping_interval": 2, "max_time_without_pinging": 5, "max_retries": 3 }}"#,
+            nodes
+        );
+        std::fs::write(&path, content)?;
+        let config = ClusterConfig::from_file(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        config
+    }
+
+    #[test]
+    fn nodes_are_sorted_by_id() {
+        let config = load_with_nodes("sorted", NODES).unwrap();
+        let ids: Vec<NodeId> = config.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(
+            config.node(2).map(|node| node.addr),
+            Some("127.0.0.1:8081".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn repeated_ids_are_rejected() {
+        let nodes =
+            r#"[{ "id": 1, "addr": "127.0.0.1:8080" }, { "id": 1, "addr": "127.0.0.1:8081" }]"#;
+        let error = load_with_nodes("repeated_ids", nodes).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn repeated_addresses_are_rejected() {
+        let nodes =
+            r#"[{ "id": 1, "addr": "127.0.0.1:8080" }, { "id": 2, "addr": "127.0.0.1:8080" }]"#;
+        let error = load_with_nodes("repeated_addrs", nodes).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn missing_timeouts_take_the_default() {
+        let config = load_with_nodes("defaults", NODES).unwrap();
+        assert_eq!(config.offer_timeout, 2);
+        assert_eq!(config.dispatch_timeout, 15);
+        assert_eq!(config.max_queue_wait, 60);
+        assert_eq!(config.queue_check_interval, 5);
+        assert_eq!(config.dead_driver_timeout, 3);
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let error = ClusterConfig::from_file("missing_cluster_config.json").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod cluster;
 pub mod messages;
 pub mod payment_messages;
+pub mod session;
 pub mod tcp_sender;
 pub mod utils;
@@ -3,6 +3,9 @@ use actix::Message;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
+/// Persistent identifier of a passenger or driver, that doesn't change when it reconnects to another admin
+pub type ClientId = u64;
+
 // ---------------------------------- ADMIN MESSAGES ----------------------------------
 
 #[derive(Message, Serialize, Deserialize, Debug)]
@@ -18,10 +21,22 @@ pub struct SendPaymentMessage {
 #[rtype(result = "()")]
 /// Confirmation from payment gateway
 pub struct AuthConfirmation {
-    pub passenger_id_ac: SocketAddr,
+    pub passenger_id_ac: ClientId,
     pub is_authorized: bool,
 }
 
+#[derive(Message, Serialize, Deserialize, Debug)]
+#[rtype(result = "()")]
+/// Response to the registration of a client.
+/// Resumed is true when the client already had a passenger or driver, bound now to the new connection.
+/// Admins are the current cluster members, so the client can reach admins that joined at runtime.
+pub struct RegisterClientResponse {
+    pub is_accepted: bool,
+    pub resumed: bool,
+    #[serde(default)]
+    pub admins: Vec<SocketAddr>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Message)]
 #[rtype(result = "()")]
 /// Message response with the current coordinator address
@@ -31,6 +46,15 @@ pub struct WhoIsCoordinatorResponse {
 
 //------------------------------------ CLIENT MESSAGES ----------------------------------
 
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to register a client to the coordinator, right after connecting to it.
+/// The session token proves that a reconnecting client is the owner of the id.
+pub struct RegisterClient {
+    pub client_id: ClientId,
+    pub session_token: u64,
+}
+
 #[derive(Message, serde::Serialize, serde::Deserialize, Debug)]
 #[rtype(result = "()")]
 /// Message to request a trip to the admins
@@ -39,27 +63,51 @@ pub struct RequestTrip {
     pub destination: (f32, f32),
 }
 
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to quote the price of the requested trip to the passenger, before the payment is authorized.
+/// The fare already includes the surge multiplier of the zone where the trip starts.
+pub struct TripFare {
+    pub fare: f32,
+    pub surge_multiplier: f32,
+}
+
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to tell the passenger its position in the queue of trips waiting for a driver
+pub struct TripQueued {
+    pub queue_position: usize,
+}
+
 #[derive(Message, Serialize, Deserialize, Debug, Clone)]
 #[rtype(result = "()")]
 /// Message to ask if driver can accept a trip
 pub struct CanAcceptTrip {
-    pub passenger_id_ca: SocketAddr,
+    pub passenger_id_ca: ClientId,
 }
 
 #[derive(Message, Serialize, Deserialize, Debug, Clone)]
 #[rtype(result = "()")]
 /// Response from driver to can accept trip request
 pub struct CanAcceptTripResponse {
-    pub passenger_id_car: SocketAddr,
+    pub passenger_id_car: ClientId,
     pub is_accepted: bool,
 }
 
+#[derive(Message, Serialize, Deserialize, Debug, Clone)]
+#[rtype(result = "()")]
+/// Message to tell a driver that the offer of a trip is no longer valid,
+/// because it didn't answer in time and the trip was offered to another driver
+pub struct OfferExpired {
+    pub passenger_id_oe: ClientId,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Message)]
 #[rtype(result = "()")]
 /// Message to start a trip from admin to clients
 pub struct StartTrip {
-    pub passenger_id_st: SocketAddr,
-    pub driver_id_st: SocketAddr,
+    pub passenger_id_st: ClientId,
+    pub driver_id_st: ClientId,
     pub origin: (f32, f32),
     pub destination: (f32, f32),
 }
@@ -75,8 +123,8 @@ pub struct RejectTrip {
 #[rtype(result = "()")]
 /// Message to inform a trip finished from client to admin
 pub struct FinishTrip {
-    pub passenger_id_ft: SocketAddr,
-    pub driver_id_ft: SocketAddr,
+    pub passenger_id_ft: ClientId,
+    pub driver_id_ft: ClientId,
     pub destination_pos: (f32, f32),
 }
 
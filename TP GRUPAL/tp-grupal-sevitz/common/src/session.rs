@@ -0,0 +1,165 @@
+use crate::messages::{ClientId, RegisterClient, RegisterClientResponse, WhoIsCoordinatorResponse};
+use crate::tcp_sender::TcpMessage;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{split, AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout};
+
+/// Time to wait for the coordinator to answer a registration
+pub const REGISTER_TIMEOUT: Duration = Duration::from_secs(3);
+/// Attempts to connect and register to the coordinator before giving up
+pub const RECONNECT_ATTEMPTS: usize = 3;
+/// Time between connection attempts
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Lines received from the coordinator
+pub type SessionReader = Lines<BufReader<OwnedReadHalf>>;
+
+/// Connects to the current coordinator and registers the client with its id and session token,
+/// retrying up to `RECONNECT_ATTEMPTS` times.
+/// It is used for the first registration and to resume the session after losing the connection,
+/// so the admins bind the client (and its trip, if any) to the new connection.
+/// `servers` is replaced by the cluster members the coordinator answers with,
+/// so the next reconnection also reaches the admins that joined at runtime.
+/// `role` identifies the client in the logs.
+pub async fn open_session(
+    servers: &mut Vec<SocketAddr>,
+    client_id: ClientId,
+    session_token: u64,
+    role: &str,
+) -> Option<(SessionReader, OwnedWriteHalf)> {
+    for _ in 0..RECONNECT_ATTEMPTS {
+        if let Some(stream) = connect_to_coordinator(servers, role).await {
+            let (reader, writer) = stream.into_split();
+            let mut reader = BufReader::new(reader).lines();
+            let mut writer = writer;
+            if let Some(admins) =
+                register(&mut reader, &mut writer, client_id, session_token, role).await
+            {
+                if !admins.is_empty() {
+                    *servers = admins;
+                }
+                return Some((reader, writer));
+            }
+        }
+        sleep(RECONNECT_DELAY).await;
+    }
+    None
+}
+
+/// Registers the client to the coordinator it is connected to, with its id and session token.
+/// Returns the current cluster members if the coordinator accepted the registration.
+async fn register(
+    reader: &mut SessionReader,
+    writer: &mut OwnedWriteHalf,
+    client_id: ClientId,
+    session_token: u64,
+    role: &str,
+) -> Option<Vec<SocketAddr>> {
+    let register_client = RegisterClient {
+        client_id,
+        session_token,
+    };
+    let Ok(serialized) = serde_json::to_string(&register_client) else {
+        return None;
+    };
+    if let Err(e) = writer
+        .write_all(format!("{}\n", serialized).as_bytes())
+        .await
+    {
+        eprintln!("[{}] Failed to send RegisterClient: {}", role, e);
+        return None;
+    }
+
+    match timeout(REGISTER_TIMEOUT, reader.next_line()).await {
+        Ok(Ok(Some(line))) => match serde_json::from_str::<RegisterClientResponse>(&line) {
+            Ok(response) if response.is_accepted => {
+                if response.resumed {
+                    println!("[{}] Session resumed as client {}", role, client_id);
+                } else {
+                    println!("[{}] Registered as client {}", role, client_id);
+                }
+                Some(response.admins)
+            }
+            Ok(_) => {
+                eprintln!("[{}] Registration rejected by the admin", role);
+                None
+            }
+            Err(_) => {
+                eprintln!("[{}] Unexpected message while registering: {}", role, line);
+                None
+            }
+        },
+        Ok(Ok(None)) | Ok(Err(_)) | Err(_) => {
+            eprintln!(
+                "[{}] Error or timeout while waiting for RegisterClientResponse",
+                role
+            );
+            None
+        }
+    }
+}
+
+/// Asks the admins for the coordinator and connects to it.
+pub async fn connect_to_coordinator(servers: &[SocketAddr], role: &str) -> Option<TcpStream> {
+    let mut coord_addr: Option<SocketAddr> = None;
+    println!("[{}] Asking For Coordinator...", role);
+
+    for server in servers {
+        match TcpStream::connect(server).await {
+            Ok(stream) => {
+                let (reader, mut writer) = split(stream);
+                let msg = TcpMessage("WhoIsCoordinator\n".to_string());
+                if let Err(e) = writer.write_all(msg.0.as_bytes()).await {
+                    eprintln!("Error writing CoordinatorElection message: {}", e);
+                }
+                let mut reader = BufReader::new(reader);
+                let mut line = String::new();
+
+                match timeout(Duration::from_secs(3), reader.read_line(&mut line)).await {
+                    Ok(Ok(_)) => {
+                        if let Ok(who_is_coord_msg) =
+                            serde_json::from_str::<WhoIsCoordinatorResponse>(&line)
+                        {
+                            coord_addr = Some(who_is_coord_msg.coord_id);
+                            break;
+                        } else if line.trim() == "Ack" {
+                            break;
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        println!("Failed to read line: {:?}", e);
+                    }
+                    Err(_) => {
+                        println!("Timeout reading line");
+                    }
+                }
+                break;
+            }
+            Err(_) => {
+                println!("[{}] Could not connect to coordinator at {}", role, server);
+            }
+        }
+
+        if coord_addr.is_some() {
+            break;
+        }
+    }
+
+    if let Some(coord_ip) = coord_addr {
+        println!("[{}] Found Coordinator at {}", role, coord_ip);
+        let stream = match TcpStream::connect(coord_ip).await {
+            Ok(stream) => {
+                println!("[{}] Connected to Coordinator", role);
+                Some(stream)
+            }
+            Err(_) => None,
+        };
+
+        return stream;
+    }
+
+    None
+}
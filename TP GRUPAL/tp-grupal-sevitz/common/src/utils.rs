@@ -16,3 +16,8 @@ pub fn get_rand_f32_tuple() -> (f32, f32) {
         (rand::random::<f32>() * 20.0).round(),
     )
 }
+
+/// Random id used by clients to identify themselves (and their session) across reconnections
+pub fn get_rand_id() -> u64 {
+    rand::random::<u64>()
+}
@@ -1,39 +1,48 @@
 use common::messages::{
-    CanAcceptTrip, CanAcceptTripResponse, DriverPosition, FinishTrip, StartTrip,
-    WhoIsCoordinatorResponse,
+    CanAcceptTrip, CanAcceptTripResponse, ClientId, DriverPosition, FinishTrip, OfferExpired,
+    StartTrip,
 };
-use common::tcp_sender::TcpMessage;
-use common::utils::get_rand_f32_tuple;
+use common::session::{open_session, SessionReader};
+use common::utils::{get_rand_f32_tuple, get_rand_id};
 use rand::Rng;
+use std::io;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio::io::{split, AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
-use tokio::net::TcpStream;
-use tokio::time::{sleep, timeout};
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::time::sleep;
 
 /// Driver struct.
 /// Models a driver in the system.
 pub struct Driver {
     servers: Vec<SocketAddr>,
-    reader: Lines<BufReader<OwnedReadHalf>>,
+    reader: SessionReader,
     writer: OwnedWriteHalf,
+    client_id: ClientId,
+    session_token: u64,
 }
 
 impl Driver {
-    pub async fn new(servers: Vec<SocketAddr>) -> Self {
-        let tcp_stream: Option<TcpStream> = connect_to_coordinator(servers.clone()).await;
-
-        if let Some(stream) = tcp_stream {
-            let (rx, wx) = stream.into_split();
-            Self {
-                servers,
-                reader: BufReader::new(rx).lines(),
-                writer: wx,
-            }
-        } else {
-            panic!("Unable to connect to any server.");
-        }
+    /// Connects to the coordinator and registers a new driver, retrying if the coordinator can't be reached.
+    pub async fn new(mut servers: Vec<SocketAddr>) -> io::Result<Self> {
+        let client_id = get_rand_id();
+        let session_token = get_rand_id();
+        let (reader, writer) = open_session(&mut servers, client_id, session_token, "DRIVER")
+            .await
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "Unable to register to the coordinator",
+                )
+            })?;
+
+        Ok(Self {
+            servers,
+            reader,
+            writer,
+            client_id,
+            session_token,
+        })
     }
 
     pub async fn run(&mut self) {
@@ -46,12 +55,18 @@ impl Driver {
                             self.handle_server_message(message).await;
                         }
                         Ok(None) => {
-                            println!("[DRIVER] Server closed the connection.");
-                            break;
+                            println!("[DRIVER] Server closed the connection. Resuming session...");
+                            if !self.resume_session().await {
+                                eprintln!("[DRIVER] Unable to reconnect to any server. Exiting...");
+                                break;
+                            }
                         }
                         Err(e) => {
-                            eprintln!("[DRIVER] Error reading from server: {}", e);
-                            break;
+                            eprintln!("[DRIVER] Error reading from server: {}. Resuming session...", e);
+                            if !self.resume_session().await {
+                                eprintln!("[DRIVER] Unable to reconnect to any server. Exiting...");
+                                break;
+                            }
                         }
                     }
                 }
@@ -64,6 +79,11 @@ impl Driver {
             self.handle_can_accept_trip(request).await;
         } else if let Ok(start_trip) = serde_json::from_str::<StartTrip>(&message) {
             self.handle_start_trip(start_trip).await;
+        } else if let Ok(expired) = serde_json::from_str::<OfferExpired>(&message) {
+            println!(
+                "[DRIVER] Offer of the trip of {} expired, waiting for new trips",
+                expired.passenger_id_oe
+            );
         } else {
             eprintln!("[DRIVER] Unknown message: {}", message);
         }
@@ -166,13 +186,28 @@ impl Driver {
         }
     }
 
-    async fn attempt_reconnect(&mut self, message: String) -> bool {
-        let coord = connect_to_coordinator(self.servers.clone()).await;
-        if let Some(stream) = coord {
-            let (reader, writer) = stream.into_split();
-            self.reader = BufReader::new(reader).lines();
-            self.writer = writer;
+    /// Connects to the current coordinator and registers again with the same id and session token,
+    /// so the admins bind the driver (and its trip, if any) to the new connection.
+    async fn resume_session(&mut self) -> bool {
+        match open_session(
+            &mut self.servers,
+            self.client_id,
+            self.session_token,
+            "DRIVER",
+        )
+        .await
+        {
+            Some((reader, writer)) => {
+                self.reader = reader;
+                self.writer = writer;
+                true
+            }
+            None => false,
+        }
+    }
 
+    async fn attempt_reconnect(&mut self, message: String) -> bool {
+        if self.resume_session().await {
             if let Err(e) = self
                 .writer
                 .write_all(format!("{}\n", message).as_bytes())
@@ -187,64 +222,3 @@ impl Driver {
         false
     }
 }
-
-async fn connect_to_coordinator(servers: Vec<SocketAddr>) -> Option<TcpStream> {
-    let mut coord_addr: Option<SocketAddr> = None;
-    println!("[DRIVER] Asking For Coordinator...");
-
-    for server in servers {
-        match TcpStream::connect(server).await {
-            Ok(stream) => {
-                let (reader, mut writer) = split(stream);
-                let msg = TcpMessage("WhoIsCoordinator\n".to_string());
-                if let Err(e) = writer.write_all(msg.0.as_bytes()).await {
-                    eprintln!("Error writing CoordinatorElection message: {}", e);
-                }
-                let mut reader = BufReader::new(reader);
-                let mut line = String::new();
-
-                match timeout(Duration::from_secs(3), reader.read_line(&mut line)).await {
-                    Ok(Ok(_)) => {
-                        if let Ok(who_is_coord_msg) =
-                            serde_json::from_str::<WhoIsCoordinatorResponse>(&line)
-                        {
-                            coord_addr = Some(who_is_coord_msg.coord_id);
-                            break;
-                        } else if line.trim() == "Ack" {
-                            break;
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        println!("Failed to read line: {:?}", e);
-                    }
-                    Err(_) => {
-                        println!("Timeout reading line");
-                    }
-                }
-                break;
-            }
-            Err(_) => {
-                println!("[DRIVER] Could not connect to coordinator at {}", server);
-            }
-        }
-
-        if coord_addr.is_some() {
-            break;
-        }
-    }
-
-    if let Some(coord_ip) = coord_addr {
-        println!("[DRIVER] Found Coordinator at {}", coord_ip);
-        let stream = match TcpStream::connect(coord_ip).await {
-            Ok(stream) => {
-                println!("[DRIVER] Connected to Coordinator");
-                Some(stream)
-            }
-            Err(_) => None,
-        };
-
-        return stream;
-    }
-
-    None
-}
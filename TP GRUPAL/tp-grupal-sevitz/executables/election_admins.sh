@@ -1,7 +1,7 @@
 #!/bin/bash
 
 # this bash script will run the admin for each terminal and redirect the output to a file,
-# the output file will be named "output_$PORT.txt.
+# the output file will be named "output_$NODE_ID.txt.
 # THESE TERMINALS WILL PRINT LOGS RELATED TO ELECTIONS BETWEEN ADMINS
 
 # EXECUTE BEFORE THIS!!!:
@@ -9,14 +9,14 @@
 
 N_TERMINALS=5
 
-STARTING_PORT=8080
+FIRST_NODE_ID=1
 
 COMMANDS=(
-    "cargo run --features election_logs --bin admin 8080"
-    "cargo run --features election_logs --bin admin 8081"
-    "cargo run --features election_logs --bin admin 8082"
-    "cargo run --features election_logs --bin admin 8083"
-    "cargo run --features election_logs --bin admin 8084"
+    "cargo run --features election_logs --bin admin 1"
+    "cargo run --features election_logs --bin admin 2"
+    "cargo run --features election_logs --bin admin 3"
+    "cargo run --features election_logs --bin admin 4"
+    "cargo run --features election_logs --bin admin 5"
 )
 
 TERMINAL="gnome-terminal" # CAN BE CHANGED TO "xterm"
@@ -29,11 +29,11 @@ fi
 for i in $(seq $((N_TERMINALS - 1)) -1 0); do
     if [ $i -lt ${#COMMANDS[@]} ]; then
 
-        PORT=$((STARTING_PORT + $i))
-        OUTPUT="output_$PORT.txt"
+        NODE_ID=$((FIRST_NODE_ID + $i))
+        OUTPUT="output_$NODE_ID.txt"
 
 
-        $TERMINAL --tab --title=Admin_$PORT -- bash -c "${COMMANDS[$i]} | tee $OUTPUT; exec bash"
+        $TERMINAL --tab --title=Admin_$NODE_ID -- bash -c "${COMMANDS[$i]} | tee $OUTPUT; exec bash"
     else
         echo "Commands missing for terminals given."
     fi
@@ -1,7 +1,7 @@
 #!/bin/bash
 
 # this bash script will run the admin for each terminal and redirect the output to a file,
-# the output file will be named "output_$PORT.txt.
+# the output file will be named "output_$NODE_ID.txt.
 # THESE TERMINALS WILL PRINT LOGS RELATED TO TRIPS
 # also will launch 1 payment gateway terminal
 
@@ -10,15 +10,15 @@
 
 N_TERMINALS=5
 
-STARTING_PORT=8080
+FIRST_NODE_ID=1
 
 
 ADMIN_COMMANDS=(
-    "cargo run --features trip_logs --bin admin 8080"
-    "cargo run --features trip_logs --bin admin 8081"
-    "cargo run --features trip_logs --bin admin 8082"
-    "cargo run --features trip_logs --bin admin 8083"
-    "cargo run --features trip_logs --bin admin 8084"
+    "cargo run --features trip_logs --bin admin 1"
+    "cargo run --features trip_logs --bin admin 2"
+    "cargo run --features trip_logs --bin admin 3"
+    "cargo run --features trip_logs --bin admin 4"
+    "cargo run --features trip_logs --bin admin 5"
 )
 GATEWAY_COMMAND="cargo run --bin payment"
 
@@ -35,10 +35,10 @@ $TERMINAL --tab --title=Payment_Gateway -- bash -c "$GATEWAY_COMMAND | tee $GATE
 
 for i in $(seq $((N_TERMINALS - 1)) -1 0); do
     if [ $i -lt ${#ADMIN_COMMANDS[@]} ]; then
-        PORT=$((STARTING_PORT + $i))
-        OUTPUT="output_admin_$PORT.txt"
+        NODE_ID=$((FIRST_NODE_ID + $i))
+        OUTPUT="output_admin_$NODE_ID.txt"
 
-        $TERMINAL --tab --title=Admin_$PORT -- bash -c "${ADMIN_COMMANDS[$i]} | tee $OUTPUT; exec bash"
+        $TERMINAL --tab --title=Admin_$NODE_ID -- bash -c "${ADMIN_COMMANDS[$i]} | tee $OUTPUT; exec bash"
     else
         echo "Commands missing for terminals given."
     fi
@@ -1,18 +1,13 @@
 mod passenger;
 
+use common::cluster::ClusterConfig;
 use passenger::Passenger;
-use std::net::SocketAddr;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut servers: Vec<SocketAddr> = Vec::new();
+    let servers = ClusterConfig::load()?.admin_addrs();
 
-    for port in 8080..=8084 {
-        let server: SocketAddr = format!("127.0.0.1:{}", port).parse()?;
-        servers.push(server);
-    }
-
-    let mut passenger = Passenger::new(servers).await;
+    let mut passenger = Passenger::new(servers).await?;
     passenger.run().await;
 
     Ok(())
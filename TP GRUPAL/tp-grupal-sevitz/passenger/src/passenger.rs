@@ -1,35 +1,44 @@
-use common::messages::{FinishTrip, RejectTrip, RequestTrip, StartTrip, WhoIsCoordinatorResponse};
-use common::tcp_sender::TcpMessage;
-use common::utils::get_rand_f32_tuple;
+use common::messages::{
+    ClientId, FinishTrip, RejectTrip, RequestTrip, StartTrip, TripFare, TripQueued,
+};
+use common::session::{open_session, SessionReader};
+use common::utils::{get_rand_f32_tuple, get_rand_id};
+use std::io;
 use std::net::SocketAddr;
-use std::time::Duration;
-use tokio::io::{split, AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
-use tokio::net::TcpStream;
-use tokio::time::timeout;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedWriteHalf;
 
 /// Passenger struct.
 /// Models a passenger in the system.
 pub struct Passenger {
     servers: Vec<SocketAddr>,
-    reader: Lines<BufReader<OwnedReadHalf>>,
+    reader: SessionReader,
     writer: OwnedWriteHalf,
+    client_id: ClientId,
+    session_token: u64,
 }
 
 impl Passenger {
-    pub async fn new(servers: Vec<SocketAddr>) -> Self {
-        let tcp_stream: Option<TcpStream> = connect_to_coordinator(servers.clone()).await;
-
-        if let Some(stream) = tcp_stream {
-            let (rx, wx) = stream.into_split();
-            Self {
-                servers,
-                reader: BufReader::new(rx).lines(),
-                writer: wx,
-            }
-        } else {
-            panic!("Unable to connect to any server.");
-        }
+    /// Connects to the coordinator and registers a new passenger, retrying if the coordinator can't be reached.
+    pub async fn new(mut servers: Vec<SocketAddr>) -> io::Result<Self> {
+        let client_id = get_rand_id();
+        let session_token = get_rand_id();
+        let (reader, writer) = open_session(&mut servers, client_id, session_token, "PASSENGER")
+            .await
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "Unable to register to the coordinator",
+                )
+            })?;
+
+        Ok(Self {
+            servers,
+            reader,
+            writer,
+            client_id,
+            session_token,
+        })
     }
 
     pub async fn run(&mut self) {
@@ -43,12 +52,18 @@ impl Passenger {
                             self.handle_server_message(message).await;
                         }
                         Ok(None) => {
-                            println!("[PASSENGER] Server closed the connection. Exiting...");
-                            break;
+                            println!("[PASSENGER] Server closed the connection. Resuming session...");
+                            if !self.resume_session().await {
+                                eprintln!("[PASSENGER] Unable to reconnect to any server. Exiting...");
+                                break;
+                            }
                         }
                         Err(e) => {
-                            eprintln!("[PASSENGER] Error reading from server: {}", e);
-                            break;
+                            eprintln!("[PASSENGER] Error reading from server: {}. Resuming session...", e);
+                            if !self.resume_session().await {
+                                eprintln!("[PASSENGER] Unable to reconnect to any server. Exiting...");
+                                break;
+                            }
                         }
                     }
                 }
@@ -76,6 +91,16 @@ impl Passenger {
             self.start_trip(start_trip).await;
         } else if let Ok(reject_trip) = serde_json::from_str::<RejectTrip>(&message) {
             println!("[PASSENGER] Trip rejected: [{:?}]", reject_trip.response);
+        } else if let Ok(trip_fare) = serde_json::from_str::<TripFare>(&message) {
+            println!(
+                "[PASSENGER] Trip fare: ${:.2} (surge x{})",
+                trip_fare.fare, trip_fare.surge_multiplier
+            );
+        } else if let Ok(trip_queued) = serde_json::from_str::<TripQueued>(&message) {
+            println!(
+                "[PASSENGER] Waiting for a driver, position in queue: {}",
+                trip_queued.queue_position
+            );
         } else {
             println!("[PASSENGER] Unknown message: {}", message);
         }
@@ -103,7 +128,8 @@ impl Passenger {
             if (self
                 .writer
                 .write_all(format!("{}\n", trip_finished_ser).as_bytes())
-                .await).is_err()
+                .await)
+                .is_err()
             {
                 println!("[PASSENGER] Failed to send FinishTrip. Attempting to reconnect...");
                 if !self.attempt_reconnect(trip_finished_ser).await {
@@ -136,13 +162,28 @@ impl Passenger {
         }
     }
 
-    async fn attempt_reconnect(&mut self, message: String) -> bool {
-        let coord = connect_to_coordinator(self.servers.clone()).await;
+    /// Connects to the current coordinator and registers again with the same id and session token,
+    /// so the admins bind the passenger (and its trip, if any) to the new connection.
+    async fn resume_session(&mut self) -> bool {
+        match open_session(
+            &mut self.servers,
+            self.client_id,
+            self.session_token,
+            "PASSENGER",
+        )
+        .await
+        {
+            Some((reader, writer)) => {
+                self.reader = reader;
+                self.writer = writer;
+                true
+            }
+            None => false,
+        }
+    }
 
-        if let Some(stream) = coord {
-            let (reader, writer) = stream.into_split();
-            self.reader = BufReader::new(reader).lines();
-            self.writer = writer;
+    async fn attempt_reconnect(&mut self, message: String) -> bool {
+        if self.resume_session().await {
             if let Err(e) = self
                 .writer
                 .write_all(format!("{}\n", message).as_bytes())
@@ -157,64 +198,3 @@ impl Passenger {
         false
     }
 }
-
-async fn connect_to_coordinator(servers: Vec<SocketAddr>) -> Option<TcpStream> {
-    let mut coord_addr: Option<SocketAddr> = None;
-    println!("[PASSENGER] Asking For Coordinator...");
-
-    for server in servers {
-        match TcpStream::connect(server).await {
-            Ok(stream) => {
-                let (reader, mut writer) = split(stream);
-                let msg = TcpMessage("WhoIsCoordinator\n".to_string());
-                if let Err(e) = writer.write_all(msg.0.as_bytes()).await {
-                    eprintln!("Error writing CoordinatorElection message: {}", e);
-                }
-                let mut reader = BufReader::new(reader);
-                let mut line = String::new();
-
-                match timeout(Duration::from_secs(3), reader.read_line(&mut line)).await {
-                    Ok(Ok(_)) => {
-                        if let Ok(who_is_coord_msg) =
-                            serde_json::from_str::<WhoIsCoordinatorResponse>(&line)
-                        {
-                            coord_addr = Some(who_is_coord_msg.coord_id);
-                            break;
-                        } else if line.trim() == "Ack" {
-                            break;
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        println!("Failed to read line: {:?}", e);
-                    }
-                    Err(_) => {
-                        println!("Timeout reading line");
-                    }
-                }
-                break;
-            }
-            Err(_) => {
-                println!("[PASSENGER] Could not connect to coordinator at {}", server);
-            }
-        }
-
-        if coord_addr.is_some() {
-            break;
-        }
-    }
-
-    if let Some(coord_ip) = coord_addr {
-        println!("[PASSENGER] Found Coordinator at {}", coord_ip);
-        let stream = match TcpStream::connect(coord_ip).await {
-            Ok(stream) => {
-                println!("[PASSENGER] Connected to Coordinator");
-                Some(stream)
-            }
-            Err(_) => None,
-        };
-
-        return stream;
-    }
-
-    None
-}
@@ -68,7 +68,8 @@ impl StreamHandler<Result<String, tokio::io::Error>> for PaymentGatewayActor {
                         };
 
                         println!(
-                            "[PAYMENT GATEWAY] Payment [{}] for passenger [{}]",
+                            "[PAYMENT GATEWAY] Payment of ${:.2} [{}] for passenger [{}]",
+                            auth_msg.amount,
                             if authorized { "authorized" } else { "rejected" },
                             auth_msg.passenger_id
                         );
@@ -110,8 +111,8 @@ impl StreamHandler<Result<String, tokio::io::Error>> for PaymentGatewayActor {
                                         );
                                     } else {
                                         println!(
-                                            "[{:?}] Sent PaymentDone to {}",
-                                            addr, payment_msg.passenger_id
+                                            "[{:?}] Sent PaymentDone of ${:.2} to {}",
+                                            addr, payment_msg.amount, payment_msg.passenger_id
                                         );
                                     }
                                     fut::ready(())
@@ -0,0 +1,85 @@
+//! Benchmark de escalado del procesamiento de un único archivo csv grande dividido en rangos.
+//! Genera un archivo replicando las filas de `tests/data` y lo procesa con distintas cantidades de hilos,
+//! informando el tiempo y la aceleración respecto de un hilo.
+//!
+//! Se ejecuta con `cargo bench --bench chunk_scaling`. Con la variable `CHUNK_BENCH_COPIES` se puede indicar
+//! cuántas veces se replican las filas (por defecto 200, unos 30 MB).
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const SOURCE: &str = "tests/data/reviews_recortado.csv";
+const DEFAULT_COPIES: usize = 200;
+const CHUNK_SIZE_MB: &str = "1";
+
+fn main() {
+    let copies = std::env::var("CHUNK_BENCH_COPIES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_COPIES);
+
+    let dir = std::env::temp_dir().join(format!("chunk_scaling_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("no se pudo crear el directorio temporal");
+    let input = dir.join("reviews.csv");
+    let output = dir.join("output.json");
+    generate_input(&input, copies);
+
+    let size_mb = fs::metadata(&input).map(|m| m.len()).unwrap_or(0) as f64 / (1024.0 * 1024.0);
+    println!(
+        "Archivo de {:.1} MB, rangos de {} MB",
+        size_mb, CHUNK_SIZE_MB
+    );
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = 1;
+    let mut baseline = None;
+    while threads <= max_threads {
+        let elapsed = run(&input, &output, threads);
+        let baseline = *baseline.get_or_insert(elapsed);
+        println!(
+            "{:>3} hilos: {:>8.3} s  (x{:.2})",
+            threads,
+            elapsed.as_secs_f64(),
+            baseline.as_secs_f64() / elapsed.as_secs_f64()
+        );
+        threads = if threads == max_threads {
+            threads + 1
+        } else {
+            (threads * 2).min(max_threads)
+        };
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+fn generate_input(path: &Path, copies: usize) {
+    let source = BufReader::new(File::open(SOURCE).expect("no se encontró el csv de prueba"));
+    let mut lines = source.lines().map_while(Result::ok);
+    let header = lines.next().unwrap_or_default();
+    let body: Vec<String> = lines.collect();
+
+    let mut writer = BufWriter::new(File::create(path).expect("no se pudo crear el csv"));
+    writeln!(writer, "{}", header).unwrap();
+    for _ in 0..copies {
+        for line in &body {
+            writeln!(writer, "{}", line).unwrap();
+        }
+    }
+    writer.flush().unwrap();
+}
+
+fn run(input: &Path, output: &Path, threads: usize) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_tp1-mslepowron"))
+        .arg(input)
+        .arg(threads.to_string())
+        .arg(output)
+        .args(["--chunk-size", CHUNK_SIZE_MB])
+        .output()
+        .expect("no se pudo ejecutar el binario")
+        .status;
+    assert!(status.success(), "la ejecución con {} hilos falló", threads);
+    start.elapsed()
+}
@@ -0,0 +1,139 @@
+//! Módulo con la agregación aproximada de juegos, para datasets con demasiados juegos distintos como para
+//! conservarlos a todos en memoria.
+//! Los juegos más reseñados se estiman con Space-Saving, conservando como máximo 'sketch_capacity' juegos, y la
+//! cantidad de autores distintos de cada juego conservado se estima con HyperLogLog.
+use crate::config::Config;
+use crate::game::Game;
+use crate::hyperloglog::{HyperLogLog, RELATIVE_STANDARD_ERROR};
+use crate::space_saving::{Counter, SpaceSaving};
+use crate::top_review::TopReview;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+///Datos de un juego conservado en el modo aproximado.
+/// ###  game
+/// Estadísticas del juego (idiomas, recomendaciones e histograma) acumuladas desde que comenzó a conservarse.
+/// ###  authors
+/// Estimador de la cantidad de autores distintos que reseñaron el juego desde que comenzó a conservarse.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SketchedGame {
+    game: Game,
+    authors: HyperLogLog,
+}
+
+///Estructura que acumula los juegos en el modo aproximado, con memoria acotada por la capacidad del sketch.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ApproximateGames {
+    games: SpaceSaving<SketchedGame>,
+}
+
+impl ApproximateGames {
+    ///Crea una nueva instancia vacía que conservará como máximo 'capacity' juegos.
+    pub fn new(capacity: usize) -> ApproximateGames {
+        ApproximateGames {
+            games: SpaceSaving::new(capacity),
+        }
+    }
+
+    ///Registra una reseña del juego escrita en el idioma indicado, creada en el período indicado y por el autor
+    /// indicado, si se conocen.
+    pub fn add_review(
+        &mut self,
+        game_name: &str,
+        language: &str,
+        review: &TopReview,
+        period: Option<i64>,
+        author: Option<u64>,
+    ) {
+        let sketched = self.games.add(game_name);
+        sketched.game.add_review(language, review, period);
+        if let Some(author) = author {
+            sketched.authors.add(author);
+        }
+    }
+
+    ///Fusiona los juegos de otra instancia.
+    pub fn merge(&mut self, other: ApproximateGames) {
+        self.games.merge(other.games, |current, other| {
+            current.game.merge_game_wiith_other_review(other.game);
+            current.authors.merge(other.authors);
+        });
+    }
+
+    ///Devuelve los top 'n' juegos con mayor cuenta estimada, según la cantidad solicitada en la configuración.
+    /// Además de los datos del modo exacto, cada juego informa:
+    /// - 'review_count': cuenta estimada, que nunca subestima la real.
+    /// - 'review_count_error': cota de la sobreestimación de 'review_count'.
+    /// - 'distinct_authors': cantidad estimada de autores distintos.
+    ///
+    /// Los idiomas, recomendaciones, histograma y autores de un juego solo incluyen las reseñas vistas desde que
+    /// el juego comenzó a conservarse, por lo que pueden quedar por debajo de los valores reales.
+    pub fn top_n_games_reviewed(&self, config: &Config) -> Vec<Value> {
+        let mut top_games: Vec<_> = self.games.entries().collect();
+        top_games.sort_by(|a, b| {
+            b.1.count
+                .cmp(&a.1.count)
+                .then_with(|| config.tie_break.compare(a.0, b.0))
+        });
+        top_games.truncate(config.top_games);
+
+        top_games
+            .into_iter()
+            .map(|(game_name, counter)| game_json(game_name, counter, config))
+            .collect()
+    }
+
+    ///Devuelve los top 'n' juegos conservados con más reseñas escritas en el idioma indicado, según la cantidad
+    /// solicitada en la configuración, con los mismos datos que 'top_n_games_reviewed' más la cantidad de reseñas
+    /// en ese idioma en 'language_review_count'. Como los idiomas de un juego solo incluyen las reseñas vistas
+    /// desde que comenzó a conservarse, esa cantidad puede quedar por debajo de la real.
+    pub fn top_n_games_in_language(&self, language: &str, config: &Config) -> Vec<Value> {
+        let mut top_games: Vec<_> = self
+            .games
+            .entries()
+            .filter_map(|(game_name, counter)| {
+                let count = counter.value.game.language_review_count(language)?;
+                Some((game_name, counter, count))
+            })
+            .collect();
+        top_games.sort_by(|a, b| {
+            b.2.cmp(&a.2)
+                .then_with(|| config.tie_break.compare(a.0, b.0))
+        });
+        top_games.truncate(config.top_games);
+
+        top_games
+            .into_iter()
+            .map(|(game_name, counter, count)| {
+                let mut game_entry = game_json(game_name, counter, config);
+                game_entry["language_review_count"] = json!(count);
+                game_entry
+            })
+            .collect()
+    }
+
+    ///Devuelve los datos del juego con el formato en que se incluyen en la salida, o None si no está conservado.
+    pub fn game_to_json(&self, game_name: &str, config: &Config) -> Option<Value> {
+        let counter = self.games.get(game_name)?;
+        Some(game_json(game_name, counter, config))
+    }
+
+    ///Devuelve la descripción de las cotas de error del modo aproximado, con el formato en que se incluye en
+    /// la salida.
+    pub fn error_bounds(&self) -> Value {
+        json!({
+            "sketch_capacity": self.games.capacity(),
+            "total_reviews": self.games.total(),
+            "max_review_count_error": self.games.max_error(),
+            "distinct_authors_relative_error": RELATIVE_STANDARD_ERROR,
+        })
+    }
+}
+
+fn game_json(game_name: &str, counter: &Counter<SketchedGame>, config: &Config) -> Value {
+    let mut game_entry = counter.value.game.to_json(game_name, config);
+    game_entry["review_count"] = json!(counter.count);
+    game_entry["review_count_error"] = json!(counter.error);
+    game_entry["distinct_authors"] = json!(counter.value.authors.estimate());
+    game_entry
+}
@@ -0,0 +1,162 @@
+//! Módulo para dividir un archivo csv grande en rangos de bytes que pueden parsearse en paralelo.
+//!
+//! Los límites de cada rango se alinean al comienzo de un registro. Cada límite se busca en paralelo a partir de su
+//! posición nominal, sin recorrer el archivo desde el principio: como el texto de las reseñas puede contener saltos
+//! de línea dentro de campos entre comillas, no alcanza con buscar el siguiente salto de línea, y cada candidato se
+//! valida parseando los registros siguientes y verificando que todos tengan la cantidad de campos del encabezado.
+//! Si el candidato cae dentro de un campo entre comillas, el parseo se desincroniza al llegar a la comilla que
+//! cierra el campo, por lo que la validación abarca al menos SYNC_BYTES bytes para que un campo con muchas líneas
+//! parecidas a registros no alcance a engañarla.
+use crate::input::{GZIP_MAGIC, ZSTD_MAGIC};
+use csv::{ReaderBuilder, StringRecord};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+///Cantidad mínima de registros consecutivos que deben tener la cantidad correcta de campos para aceptar un límite.
+const SYNC_RECORDS: usize = 8;
+
+///Cantidad mínima de bytes que deben parsearse correctamente a partir de un candidato para aceptarlo como límite.
+const SYNC_BYTES: u64 = 64 * 1024;
+
+///Cantidad de bytes que se leen por vez al buscar saltos de línea.
+const SCAN_WINDOW: usize = 64 * 1024;
+
+///Rango [start, end) de bytes de un archivo, alineado al comienzo de un registro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+///Plan de lectura de un archivo dividido en rangos.
+/// ###  headers
+/// Encabezado del csv, necesario para deserializar los registros de cada rango.
+/// ###  first_line
+/// Número de línea del archivo en que comienzan los datos, contando desde 1 como el lector de csv. La línea en que
+/// comienza cada rango solo se conoce después de parsear los anteriores.
+/// ###  ranges
+/// Rangos contiguos y alineados al comienzo de un registro que cubren todos los datos.
+pub struct ChunkPlan {
+    pub headers: StringRecord,
+    pub first_line: u64,
+    pub ranges: Vec<ChunkRange>,
+}
+
+///Divide el archivo en rangos de aproximadamente 'chunk_size' bytes, buscando los límites en paralelo en el pool
+/// de rayon actual.
+/// Devuelve None si no conviene dividirlo: si 'chunk_size' es 0, si el archivo no supera ese tamaño o si está
+/// comprimido (en cuyo caso solo puede leerse secuencialmente).
+pub fn plan_chunks(path: &Path, chunk_size: u64) -> io::Result<Option<ChunkPlan>> {
+    if chunk_size == 0 {
+        return Ok(None);
+    }
+    let file_len = std::fs::metadata(path)?.len();
+    if file_len <= chunk_size || is_compressed(path)? {
+        return Ok(None);
+    }
+
+    let mut reader = ReaderBuilder::new().from_reader(BufReader::new(File::open(path)?));
+    let headers = reader.headers().map_err(io::Error::other)?.clone();
+    let data_start = reader.position().byte();
+    let first_line = reader.position().line();
+
+    let nominal_starts = (1..)
+        .map(|i| data_start + i * chunk_size)
+        .take_while(|&start| start < file_len)
+        .collect::<Vec<_>>();
+    let found = nominal_starts
+        .into_par_iter()
+        .map(|nominal| find_record_start(path, nominal, headers.len(), file_len))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // Dos posiciones nominales pueden alinearse al mismo registro si es más largo que 'chunk_size'.
+    let mut boundaries = vec![data_start];
+    boundaries.extend(found.into_iter().flatten());
+    boundaries.dedup();
+    boundaries.push(file_len);
+
+    let ranges = boundaries
+        .windows(2)
+        .map(|window| ChunkRange {
+            start: window[0],
+            end: window[1],
+        })
+        .collect();
+    Ok(Some(ChunkPlan {
+        headers,
+        first_line,
+        ranges,
+    }))
+}
+
+///Abre un lector posicionado al comienzo del rango que no lee más allá de su fin.
+pub fn open_range(path: &Path, range: &ChunkRange) -> io::Result<impl Read> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(range.start))?;
+    Ok(BufReader::new(file.take(range.end - range.start)))
+}
+
+fn is_compressed(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)?.read(&mut magic)?;
+    Ok(magic[..read].starts_with(&GZIP_MAGIC) || magic[..read] == ZSTD_MAGIC)
+}
+
+///Devuelve el comienzo del primer registro válido a partir de 'nominal', es decir, la posición siguiente al primer
+/// salto de línea desde 'nominal - 1' que sea el comienzo de un registro, o None si no hay ninguno antes del fin del
+/// archivo.
+fn find_record_start(
+    path: &Path,
+    nominal: u64,
+    num_fields: usize,
+    file_len: u64,
+) -> io::Result<Option<u64>> {
+    let mut file = File::open(path)?;
+    let mut position = nominal - 1;
+    file.seek(SeekFrom::Start(position))?;
+    let mut buffer = vec![0u8; SCAN_WINDOW];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        for &byte in &buffer[..read] {
+            position += 1;
+            if byte != b'\n' {
+                continue;
+            }
+            if position >= file_len {
+                return Ok(None);
+            }
+            if is_record_start(path, position, num_fields)? {
+                return Ok(Some(position));
+            }
+        }
+    }
+}
+
+///Indica si a partir de 'candidate' pueden parsearse al menos SYNC_RECORDS registros y SYNC_BYTES bytes (o todos
+/// los que queden hasta el fin del archivo) con exactamente 'num_fields' campos cada registro.
+fn is_record_start(path: &Path, candidate: u64, num_fields: usize) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(candidate))?;
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
+
+    let mut record = StringRecord::new();
+    let mut records = 0;
+    while records < SYNC_RECORDS || reader.position().byte() < SYNC_BYTES {
+        match reader.read_record(&mut record) {
+            Ok(true) if record.len() == num_fields => records += 1,
+            Ok(true) => return Ok(false),
+            Ok(false) => return Ok(true),
+            Err(_) => return Ok(false),
+        }
+    }
+    Ok(true)
+}
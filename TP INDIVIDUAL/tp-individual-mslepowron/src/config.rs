@@ -0,0 +1,331 @@
+//! Módulo para manejar las opciones de ejecución recibidas por línea de comandos:
+//! - la forma del reporte de salida: cantidad de juegos, idiomas y reseñas a mostrar, y criterio de desempate.
+//! - el manejo de errores.
+//! - el filtro de reseñas.
+//! - el formato de salida.
+//! - el snapshot para procesamiento incremental.
+//! - la selección de archivos de entrada.
+//! - la división de archivos grandes en rangos.
+//! - el histograma por período.
+//! - el modo de agregación exacto o aproximado.
+//! - el reporte de progreso y tiempos.
+//! - las estadísticas de texto de las reseñas.
+//! - el criterio para puntuar las reseñas.
+//! - el agrupamiento por campos arbitrarios.
+//! - el servicio de consultas.
+use crate::filter::Filter;
+use crate::group_by::{Aggregate, GroupBy};
+use crate::histogram::Granularity;
+use crate::output_writer::OutputFormat;
+use crate::review_error::ReviewError;
+use crate::score::ScoreStrategy;
+use crate::text_stats::StopWords;
+use std::cmp::Ordering;
+use std::time::Duration;
+
+const DEFAULT_TOP_GAMES: usize = 3;
+const DEFAULT_TOP_LANGUAGES: usize = 3;
+const DEFAULT_LANGUAGES_PER_GAME: usize = 3;
+const DEFAULT_REVIEWS_PER_LANGUAGE: usize = 10;
+const DEFAULT_CHUNK_SIZE_MB: u64 = 64;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const DEFAULT_PADRON: u32 = 109454;
+const DEFAULT_SKETCH_CAPACITY: usize = 1000;
+
+///Criterio de desempate cuando dos juegos o idiomas tienen la misma cantidad de reseñas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    NameAsc,
+    NameDesc,
+}
+
+impl TieBreak {
+    ///Compara dos nombres según el criterio de desempate elegido.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            TieBreak::NameAsc => a.cmp(b),
+            TieBreak::NameDesc => b.cmp(a),
+        }
+    }
+
+    fn parse(value: &str) -> Result<TieBreak, ReviewError> {
+        match value {
+            "name-asc" => Ok(TieBreak::NameAsc),
+            "name-desc" => Ok(TieBreak::NameDesc),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Criterio de desempate desconocido '{}'. Valores posibles: name-asc, name-desc",
+                value
+            ))),
+        }
+    }
+}
+
+///Comportamiento ante filas del csv que no pueden procesarse o archivos que no pueden abrirse.
+/// ###  Lenient
+/// Se contabilizan en un resumen de errores que se incluye en la salida y se continúa el procesamiento.
+/// ###  Strict
+/// Se aborta el procesamiento devolviendo un error que indica el archivo y la línea.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    Lenient,
+    Strict,
+}
+
+impl ErrorMode {
+    fn parse(value: &str) -> Result<ErrorMode, ReviewError> {
+        match value {
+            "lenient" => Ok(ErrorMode::Lenient),
+            "strict" => Ok(ErrorMode::Strict),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Modo de errores desconocido '{}'. Valores posibles: lenient, strict",
+                value
+            ))),
+        }
+    }
+}
+
+///Forma de agregar las reseñas por juego.
+/// ###  Exact
+/// Se conservan todos los juegos, por lo que la memoria crece con la cantidad de juegos distintos.
+/// ###  Approximate
+/// Se estiman los juegos más reseñados y sus autores distintos con sketches de memoria acotada, informando las
+/// cotas de error en la salida.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Exact,
+    Approximate,
+}
+
+impl Aggregation {
+    fn parse(value: &str) -> Result<Aggregation, ReviewError> {
+        match value {
+            "exact" => Ok(Aggregation::Exact),
+            "approximate" => Ok(Aggregation::Approximate),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Modo de agregación desconocido '{}'. Valores posibles: exact, approximate",
+                value
+            ))),
+        }
+    }
+}
+
+///Estructura con las opciones de ejecución del procesamiento.
+/// ###  top_games
+/// Cantidad de juegos más reseñados a mostrar.
+/// ###  top_languages
+/// Cantidad de idiomas más utilizados a mostrar.
+/// ###  languages_per_game
+/// Cantidad de idiomas a mostrar por cada juego.
+/// ###  reviews_per_language
+/// Cantidad de reseñas más votadas que se conservan por cada idioma.
+/// ###  tie_break
+/// Criterio de desempate entre juegos o idiomas con la misma cantidad de reseñas.
+/// ###  error_mode
+/// Comportamiento ante filas inválidas o archivos que no pueden abrirse.
+/// ###  filter
+/// Condición que deben cumplir las reseñas para ser agregadas. Si no se indica se procesan todas.
+/// ###  output_format
+/// Formato del archivo de salida. Si no se indica se deduce de la extensión del archivo.
+/// ###  padron
+/// Padrón a incluir en la salida. Si no se indica se incluye el del autor, como en la salida original, y con
+/// `--padron none` la salida no incluye padrón.
+/// ###  snapshot_path
+/// Archivo donde se guarda el resultado agregado para que las próximas ejecuciones solo procesen los archivos
+/// nuevos. Si no se indica se procesan siempre todos los archivos.
+/// ###  include_patterns y exclude_patterns
+/// Patrones glob que deben cumplir los archivos al recorrer un directorio. Si no se indican patrones de inclusión
+/// se procesan los archivos `.csv`, `.csv.gz` y `.csv.zst`.
+/// ###  input_files
+/// Archivos a procesar además de los de la ruta de entrada.
+/// ###  chunk_size_bytes
+/// Tamaño aproximado de los rangos de bytes en que se divide cada archivo csv sin comprimir para parsearlo en
+/// paralelo. Los archivos más chicos se leen enteros. Con 0 no se divide ningún archivo.
+/// ###  histogram
+/// Granularidad del histograma de reseñas por período que se incluye para cada juego e idioma. Si no se indica no
+/// se genera.
+/// ###  aggregation y sketch_capacity
+/// Forma de agregar las reseñas por juego y, en el modo aproximado, cantidad máxima de juegos que se conservan.
+/// ###  progress_interval
+/// Cada cuánto se informa el progreso por stderr. Si no se indica no se informa.
+/// ###  stats_path
+/// Archivo donde se escriben los tiempos de cada etapa por hilo, o `-` para escribirlos por stderr. Si no se
+/// indica no se miden.
+/// ###  top_words
+/// Cantidad de palabras más frecuentes a mostrar por idioma. Si no se indica no se calculan estadísticas de texto.
+/// ###  stop_words
+/// Palabras vacías de cada idioma, que no se cuentan entre las más frecuentes.
+/// ###  score
+/// Criterio con el que se eligen las reseñas más destacadas de cada juego e idioma.
+/// ###  group_by
+/// Campos por los que se agrupan las reseñas y agregaciones que se calculan en cada grupo. Si no se indica no se
+/// agrupa.
+/// ###  serve_address
+/// Dirección en la que se atienden consultas sobre el resultado luego de escribir la salida. Si no se indica el
+/// programa termina al escribir la salida.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub top_games: usize,
+    pub top_languages: usize,
+    pub languages_per_game: usize,
+    pub reviews_per_language: usize,
+    pub tie_break: TieBreak,
+    pub error_mode: ErrorMode,
+    pub filter: Option<Filter>,
+    pub output_format: Option<OutputFormat>,
+    pub padron: Option<u32>,
+    pub snapshot_path: Option<String>,
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    pub input_files: Vec<String>,
+    pub chunk_size_bytes: u64,
+    pub histogram: Option<Granularity>,
+    pub aggregation: Aggregation,
+    pub sketch_capacity: usize,
+    pub progress_interval: Option<Duration>,
+    pub stats_path: Option<String>,
+    pub top_words: Option<usize>,
+    pub stop_words: StopWords,
+    pub score: ScoreStrategy,
+    pub group_by: Option<GroupBy>,
+    pub serve_address: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            top_games: DEFAULT_TOP_GAMES,
+            top_languages: DEFAULT_TOP_LANGUAGES,
+            languages_per_game: DEFAULT_LANGUAGES_PER_GAME,
+            reviews_per_language: DEFAULT_REVIEWS_PER_LANGUAGE,
+            tie_break: TieBreak::NameAsc,
+            error_mode: ErrorMode::Lenient,
+            filter: None,
+            output_format: None,
+            padron: Some(DEFAULT_PADRON),
+            snapshot_path: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            input_files: Vec::new(),
+            chunk_size_bytes: DEFAULT_CHUNK_SIZE_MB * BYTES_PER_MB,
+            histogram: None,
+            aggregation: Aggregation::Exact,
+            sketch_capacity: DEFAULT_SKETCH_CAPACITY,
+            progress_interval: None,
+            stats_path: None,
+            top_words: None,
+            stop_words: StopWords::default(),
+            score: ScoreStrategy::VotesHelpful,
+            group_by: None,
+            serve_address: None,
+        }
+    }
+}
+
+impl Config {
+    ///Construye la configuración a partir de los flags opcionales recibidos por línea de comandos.
+    /// Los flags que no se indiquen toman su valor por defecto.
+    /// Devuelve error si un flag es desconocido, le falta el valor o el valor es inválido.
+    pub fn from_args(args: &[String]) -> Result<Config, ReviewError> {
+        let mut config = Config::default();
+        let mut iter = args.iter();
+        let mut group_keys = None;
+        let mut aggregates = Vec::new();
+
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or_else(|| {
+                ReviewError::InvalidArgument(format!("Falta el valor del flag '{}'", flag))
+            })?;
+
+            match flag.as_str() {
+                "--top-games" => config.top_games = parse_amount(flag, value)?,
+                "--top-languages" => config.top_languages = parse_amount(flag, value)?,
+                "--languages-per-game" => config.languages_per_game = parse_amount(flag, value)?,
+                "--reviews-per-language" => {
+                    config.reviews_per_language = parse_amount(flag, value)?
+                }
+                "--tie-break" => config.tie_break = TieBreak::parse(value)?,
+                "--on-error" => config.error_mode = ErrorMode::parse(value)?,
+                "--filter" => config.filter = Some(Filter::parse(value)?),
+                "--snapshot" => config.snapshot_path = Some(value.clone()),
+                "--include" => config.include_patterns.push(value.clone()),
+                "--exclude" => config.exclude_patterns.push(value.clone()),
+                "--file" => config.input_files.push(value.clone()),
+                "--chunk-size" => config.chunk_size_bytes = parse_megabytes(flag, value)?,
+                "--histogram" => config.histogram = Some(Granularity::parse(value)?),
+                "--aggregation" => config.aggregation = Aggregation::parse(value)?,
+                "--sketch-capacity" => config.sketch_capacity = parse_amount(flag, value)?,
+                "--progress" => config.progress_interval = Some(parse_interval(flag, value)?),
+                "--stats" => config.stats_path = Some(value.clone()),
+                "--text-stats" => config.top_words = Some(parse_amount(flag, value)?),
+                "--stop-words" => config.stop_words.load(value)?,
+                "--score" => config.score = ScoreStrategy::parse(value)?,
+                "--group-by" => group_keys = Some(value.clone()),
+                "--aggregate" => aggregates.push(Aggregate::parse(value)?),
+                "--serve" => config.serve_address = Some(value.clone()),
+                "--format" => config.output_format = Some(OutputFormat::parse(value)?),
+                "--padron" => config.padron = parse_padron(value)?,
+                _ => {
+                    return Err(ReviewError::InvalidArgument(format!(
+                        "Flag desconocido '{}'",
+                        flag
+                    )))
+                }
+            }
+        }
+
+        config.group_by = match group_keys {
+            Some(keys) => Some(GroupBy::new(&keys, aggregates)?),
+            None if aggregates.is_empty() => None,
+            None => {
+                return Err(ReviewError::InvalidArgument(
+                    "El flag '--aggregate' requiere indicar los campos con '--group-by'"
+                        .to_string(),
+                ))
+            }
+        };
+
+        Ok(config)
+    }
+}
+
+fn parse_padron(value: &str) -> Result<Option<u32>, ReviewError> {
+    if value == "none" {
+        return Ok(None);
+    }
+    value.parse().map(Some).map_err(|_| {
+        ReviewError::InvalidArgument(format!(
+            "El padrón debe ser un número entero o 'none', se recibió '{}'",
+            value
+        ))
+    })
+}
+
+fn parse_amount(flag: &str, value: &str) -> Result<usize, ReviewError> {
+    value.parse().map_err(|_| {
+        ReviewError::InvalidArgument(format!(
+            "El valor de '{}' debe ser un número entero no negativo",
+            flag
+        ))
+    })
+}
+
+fn parse_megabytes(flag: &str, value: &str) -> Result<u64, ReviewError> {
+    (parse_amount(flag, value)? as u64)
+        .checked_mul(BYTES_PER_MB)
+        .ok_or_else(|| {
+            ReviewError::InvalidArgument(format!(
+                "El valor de '{}' es demasiado grande, se recibió '{}'",
+                flag, value
+            ))
+        })
+}
+
+fn parse_interval(flag: &str, value: &str) -> Result<Duration, ReviewError> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(ReviewError::InvalidArgument(format!(
+            "El valor de '{}' debe ser una cantidad de segundos mayor a 0",
+            flag
+        ))),
+    }
+}
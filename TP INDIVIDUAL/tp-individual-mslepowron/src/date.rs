@@ -0,0 +1,53 @@
+//! Módulo con funciones para convertir entre timestamps unix (segundos, UTC) y fechas del calendario.
+//! Se implementa la conversión a mano para no agregar dependencias solo para obtener año y mes de una reseña.
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const DAYS_PER_ERA: i64 = 146_097;
+const DAYS_FROM_ERA_START_TO_EPOCH: i64 = 719_468;
+
+///Devuelve el año, mes y día (UTC) correspondientes a un timestamp unix en segundos.
+pub fn date_from_timestamp(timestamp: i64) -> (i64, u32, u32) {
+    let days = timestamp.div_euclid(SECONDS_PER_DAY) + DAYS_FROM_ERA_START_TO_EPOCH;
+    let era = days.div_euclid(DAYS_PER_ERA);
+    let day_of_era = days - era * DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+///Devuelve el timestamp unix en segundos del comienzo (00:00 UTC) del día indicado.
+pub fn timestamp_from_date(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * DAYS_PER_ERA + day_of_era - DAYS_FROM_ERA_START_TO_EPOCH;
+
+    days * SECONDS_PER_DAY
+}
+
+///Interpreta una fecha con formato 'AAAA-MM-DD' (o 'AAAA-MM', o 'AAAA') y devuelve el timestamp unix de su
+/// comienzo. Devuelve None si el formato es inválido.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let mut parts = text.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next().map_or(Some(1), |m| m.parse().ok())?;
+    let day: u32 = parts.next().map_or(Some(1), |d| d.parse().ok())?;
+
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(timestamp_from_date(year, month, day))
+}
@@ -0,0 +1,170 @@
+//! Módulo para registrar las filas del csv que no pudieron procesarse, agrupadas por archivo.
+//! El resumen se acumula en paralelo junto con las reseñas y se incluye en la salida del programa.
+use crate::top_k::TopK;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+
+///Cantidad máxima de filas inválidas que se detallan por archivo. El resto solo se contabiliza.
+const MAX_BAD_ROWS_DETAILED: usize = 100;
+
+///Estructura para almacenar el detalle de una fila inválida del csv.
+/// ###  line
+/// Línea del archivo en la que comienza el registro, si se conoce. En modo estricto los archivos que se leen
+/// divididos en rangos de bytes no informan la línea.
+/// ###  byte
+/// Posición en bytes dentro del archivo en la que comienza el registro, si el error la informa.
+/// ###  error
+/// Descripción del error devuelta por el lector de csv.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BadRow {
+    pub line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte: Option<u64>,
+    pub error: String,
+}
+
+///Las filas se ordenan por posición en el archivo para que el detalle conservado sea siempre el de las primeras
+/// filas inválidas del archivo, sin importar el orden en que las procesaron los hilos.
+impl Ord for BadRow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.byte
+            .cmp(&other.byte)
+            .then_with(|| self.line.cmp(&other.line))
+            .then_with(|| self.error.cmp(&other.error))
+    }
+}
+
+impl PartialOrd for BadRow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+///Estructura para almacenar los errores encontrados en un archivo particular.
+#[derive(Clone, Serialize, Deserialize)]
+struct FileErrors {
+    bad_rows: u64,
+    first_bad_rows: TopK<Reverse<BadRow>>,
+    open_error: Option<String>,
+}
+
+impl FileErrors {
+    fn new() -> FileErrors {
+        FileErrors {
+            bad_rows: 0,
+            first_bad_rows: TopK::new(MAX_BAD_ROWS_DETAILED),
+            open_error: None,
+        }
+    }
+
+    fn merge(&mut self, other: FileErrors) {
+        self.bad_rows += other.bad_rows;
+        self.first_bad_rows.merge(other.first_bad_rows);
+        if self.open_error.is_none() {
+            self.open_error = other.open_error;
+        }
+    }
+}
+
+///Estructura con el reporte de errores de un archivo tal como se incluye en la salida.
+#[derive(Serialize)]
+pub struct FileErrorReport {
+    pub bad_rows: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rows: Vec<BadRow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_error: Option<String>,
+}
+
+///Estructura que acumula los errores de lectura de todos los archivos procesados.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ErrorSummary {
+    files: HashMap<String, FileErrors>,
+}
+
+impl ErrorSummary {
+    ///Crea un resumen de errores vacío.
+    pub fn new() -> ErrorSummary {
+        ErrorSummary {
+            files: HashMap::new(),
+        }
+    }
+
+    ///Registra una fila inválida del archivo indicado.
+    pub fn add_bad_row(&mut self, file: &str, line: Option<u64>, byte: Option<u64>, error: String) {
+        let file_errors = self.file_entry(file);
+        file_errors.bad_rows += 1;
+        file_errors
+            .first_bad_rows
+            .push(Reverse(BadRow { line, byte, error }));
+    }
+
+    ///Suma 'offset' a la línea de las filas inválidas registradas del archivo indicado.
+    pub fn shift_lines(&mut self, file: &str, offset: u64) {
+        let Some(file_errors) = self.files.get_mut(file) else {
+            return;
+        };
+        let rows = std::mem::replace(
+            &mut file_errors.first_bad_rows,
+            TopK::new(MAX_BAD_ROWS_DETAILED),
+        );
+        for Reverse(mut row) in rows.into_sorted_vec() {
+            row.line = row.line.map(|line| line + offset);
+            file_errors.first_bad_rows.push(Reverse(row));
+        }
+    }
+
+    ///Registra que el archivo indicado no pudo abrirse.
+    pub fn add_open_error(&mut self, file: &str, error: String) {
+        self.file_entry(file).open_error = Some(error);
+    }
+
+    ///Indica si se registró que el archivo indicado no pudo abrirse.
+    pub fn open_failed(&self, file: &str) -> bool {
+        self.files
+            .get(file)
+            .is_some_and(|file_errors| file_errors.open_error.is_some())
+    }
+
+    ///Fusiona los errores registrados en otro resumen.
+    pub fn merge(&mut self, other: ErrorSummary) {
+        for (file, other_errors) in other.files {
+            match self.files.get_mut(&file) {
+                Some(file_errors) => file_errors.merge(other_errors),
+                None => {
+                    self.files.insert(file, other_errors);
+                }
+            }
+        }
+    }
+
+    ///Devuelve el reporte de errores por archivo, ordenado por nombre de archivo y con las filas
+    /// ordenadas por posición en el archivo.
+    pub fn report(&self) -> BTreeMap<String, FileErrorReport> {
+        self.files
+            .iter()
+            .map(|(file, errors)| {
+                let rows: Vec<BadRow> = errors
+                    .first_bad_rows
+                    .clone()
+                    .into_sorted_vec()
+                    .into_iter()
+                    .map(|Reverse(row)| row)
+                    .collect();
+                let report = FileErrorReport {
+                    bad_rows: errors.bad_rows,
+                    rows,
+                    open_error: errors.open_error.clone(),
+                };
+                (file.clone(), report)
+            })
+            .collect()
+    }
+
+    fn file_entry(&mut self, file: &str) -> &mut FileErrors {
+        self.files
+            .entry(file.to_string())
+            .or_insert_with(FileErrors::new)
+    }
+}
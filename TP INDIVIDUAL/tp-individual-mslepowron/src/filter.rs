@@ -0,0 +1,373 @@
+//! Módulo para interpretar y evaluar las expresiones de filtro que se aplican a las reseñas antes de agregarlas.
+//!
+//! La sintaxis admite comparaciones entre un campo y un valor, combinadas con `&&`, `||`, `!` y paréntesis:
+//!
+//! ```text
+//! recommended == false && year == 2020
+//! timestamp_created >= "2019-06-01" && playtime_forever > 600
+//! !received_for_free && (language == "spanish" || language == "latam")
+//! ```
+//!
+//! Los campos booleanos también pueden usarse solos como condición (`steam_purchase`, `!recommended`).
+use crate::date::parse_date;
+use crate::review_error::ReviewError;
+use serde::{Deserialize, Serialize};
+
+///Campos de una reseña sobre los que se puede filtrar o agrupar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Field {
+    AppName,
+    Language,
+    TimestampCreated,
+    Year,
+    Month,
+    Recommended,
+    VotesHelpful,
+    VotesFunny,
+    WeightedVoteScore,
+    SteamPurchase,
+    ReceivedForFree,
+    WrittenDuringEarlyAccess,
+    PlaytimeForever,
+}
+
+///Tipo de valor que contiene un campo, para validar las comparaciones al interpretar la expresión.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Text,
+    Number,
+    Bool,
+}
+
+impl Field {
+    ///Interpreta el nombre de un campo, igual al de la columna del csv salvo 'year' y 'month', que se calculan a
+    /// partir de 'timestamp_created'.
+    pub fn parse(name: &str) -> Option<Field> {
+        match name {
+            "app_name" => Some(Field::AppName),
+            "language" => Some(Field::Language),
+            "timestamp_created" => Some(Field::TimestampCreated),
+            "year" => Some(Field::Year),
+            "month" => Some(Field::Month),
+            "recommended" => Some(Field::Recommended),
+            "votes_helpful" => Some(Field::VotesHelpful),
+            "votes_funny" => Some(Field::VotesFunny),
+            "weighted_vote_score" => Some(Field::WeightedVoteScore),
+            "steam_purchase" => Some(Field::SteamPurchase),
+            "received_for_free" => Some(Field::ReceivedForFree),
+            "written_during_early_access" => Some(Field::WrittenDuringEarlyAccess),
+            "playtime_forever" | "author.playtime_forever" => Some(Field::PlaytimeForever),
+            _ => None,
+        }
+    }
+
+    ///Devuelve el nombre del campo, con el que se lo identifica en la salida.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::AppName => "app_name",
+            Field::Language => "language",
+            Field::TimestampCreated => "timestamp_created",
+            Field::Year => "year",
+            Field::Month => "month",
+            Field::Recommended => "recommended",
+            Field::VotesHelpful => "votes_helpful",
+            Field::VotesFunny => "votes_funny",
+            Field::WeightedVoteScore => "weighted_vote_score",
+            Field::SteamPurchase => "steam_purchase",
+            Field::ReceivedForFree => "received_for_free",
+            Field::WrittenDuringEarlyAccess => "written_during_early_access",
+            Field::PlaytimeForever => "playtime_forever",
+        }
+    }
+
+    ///Indica si el campo es numérico.
+    pub fn is_number(&self) -> bool {
+        self.kind() == FieldKind::Number
+    }
+
+    fn kind(&self) -> FieldKind {
+        match self {
+            Field::AppName | Field::Language => FieldKind::Text,
+            Field::TimestampCreated
+            | Field::Year
+            | Field::Month
+            | Field::VotesHelpful
+            | Field::VotesFunny
+            | Field::WeightedVoteScore
+            | Field::PlaytimeForever => FieldKind::Number,
+            Field::Recommended
+            | Field::SteamPurchase
+            | Field::ReceivedForFree
+            | Field::WrittenDuringEarlyAccess => FieldKind::Bool,
+        }
+    }
+}
+
+///Valor de un campo de una reseña particular.
+pub enum FieldValue<'a> {
+    Text(&'a str),
+    Number(f64),
+    Bool(bool),
+}
+
+///Fuente de los valores de los campos sobre los que se evalúa un filtro.
+pub trait Filterable {
+    ///Devuelve el valor del campo, o None si la reseña no lo tiene informado.
+    fn field_value(&self, field: Field) -> Option<FieldValue<'_>>;
+}
+
+///Operadores de comparación admitidos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn apply<T: PartialOrd>(&self, left: T, right: T) -> bool {
+        match self {
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+        }
+    }
+}
+
+///Valor literal contra el que se compara un campo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Literal {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+///Expresión de filtro ya interpretada.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Compare(Field, CompareOp, Literal),
+}
+
+impl Filter {
+    ///Interpreta una expresión de filtro.
+    /// Devuelve error si la expresión no respeta la sintaxis, usa un campo desconocido o compara un campo con
+    /// un valor de otro tipo.
+    pub fn parse(expression: &str) -> Result<Filter, ReviewError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let filter = parser.parse_or()?;
+
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(filter_error(format!("token inesperado {:?}", token))),
+        }
+    }
+
+    ///Indica si la reseña cumple la condición. Las comparaciones sobre campos no informados no se cumplen.
+    pub fn matches<T: Filterable>(&self, review: &T) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(review) && right.matches(review),
+            Filter::Or(left, right) => left.matches(review) || right.matches(review),
+            Filter::Not(inner) => !inner.matches(review),
+            Filter::Compare(field, op, literal) => match (review.field_value(*field), literal) {
+                (Some(FieldValue::Text(value)), Literal::Text(expected)) => {
+                    op.apply(value, expected.as_str())
+                }
+                (Some(FieldValue::Number(value)), Literal::Number(expected)) => {
+                    op.apply(value, *expected)
+                }
+                (Some(FieldValue::Bool(value)), Literal::Bool(expected)) => {
+                    op.apply(value, *expected)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Text(String),
+    Number(f64),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    LeftParen,
+    RightParen,
+}
+
+fn filter_error(message: String) -> ReviewError {
+    ReviewError::InvalidArgument(format!("Filtro inválido: {}", message))
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, ReviewError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Compare(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Compare(CompareOp::Ne), 2),
+            ('<', Some('=')) => (Token::Compare(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Compare(CompareOp::Ge), 2),
+            ('<', _) => (Token::Compare(CompareOp::Lt), 1),
+            ('>', _) => (Token::Compare(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('"', _) | ('\'', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| filter_error("texto sin cerrar".to_string()))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::Text(text), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let length = chars[i..]
+                    .iter()
+                    .skip(1)
+                    .take_while(|ch| ch.is_ascii_digit() || **ch == '.')
+                    .count()
+                    + 1;
+                let text: String = chars[i..i + length].iter().collect();
+                let number = text
+                    .parse()
+                    .map_err(|_| filter_error(format!("número inválido '{}'", text)))?;
+                (Token::Number(number), length)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let length = chars[i..]
+                    .iter()
+                    .take_while(|ch| ch.is_alphanumeric() || **ch == '_' || **ch == '.')
+                    .count();
+                let text: String = chars[i..i + length].iter().collect();
+                (Token::Ident(text), length)
+            }
+            (c, _) => return Err(filter_error(format!("carácter inesperado '{}'", c))),
+        };
+
+        tokens.push(token);
+        i += length;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, ReviewError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, ReviewError> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            let right = self.parse_unary()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, ReviewError> {
+        match self.next() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LeftParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(inner),
+                    _ => Err(filter_error("falta cerrar un paréntesis".to_string())),
+                }
+            }
+            Some(Token::Ident(name)) => self.parse_comparison(&name),
+            Some(token) => Err(filter_error(format!("token inesperado {:?}", token))),
+            None => Err(filter_error("la expresión está incompleta".to_string())),
+        }
+    }
+
+    fn parse_comparison(&mut self, name: &str) -> Result<Filter, ReviewError> {
+        let field = Field::parse(name)
+            .ok_or_else(|| filter_error(format!("campo desconocido '{}'", name)))?;
+
+        let op = match self.peek() {
+            Some(Token::Compare(op)) => *op,
+            _ if field.kind() == FieldKind::Bool => {
+                return Ok(Filter::Compare(field, CompareOp::Eq, Literal::Bool(true)))
+            }
+            _ => {
+                return Err(filter_error(format!(
+                    "falta el operador de comparación para '{}'",
+                    name
+                )))
+            }
+        };
+        self.next();
+
+        let literal = match (field.kind(), self.next()) {
+            (FieldKind::Text, Some(Token::Text(text))) => Literal::Text(text),
+            (FieldKind::Number, Some(Token::Number(number))) => Literal::Number(number),
+            (FieldKind::Bool, Some(Token::Ident(value))) if value == "true" => Literal::Bool(true),
+            (FieldKind::Bool, Some(Token::Ident(value))) if value == "false" => {
+                Literal::Bool(false)
+            }
+            (FieldKind::Number, Some(Token::Text(date))) if field == Field::TimestampCreated => {
+                let timestamp = parse_date(&date)
+                    .ok_or_else(|| filter_error(format!("fecha inválida '{}'", date)))?;
+                Literal::Number(timestamp as f64)
+            }
+            (_, token) => {
+                return Err(filter_error(format!(
+                    "valor inválido para '{}': {:?}",
+                    name, token
+                )))
+            }
+        };
+
+        Ok(Filter::Compare(field, op, literal))
+    }
+}
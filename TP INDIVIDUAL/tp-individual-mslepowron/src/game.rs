@@ -1,95 +1,180 @@
 //! Módulo para manejar los datos estadísticos sobre la reseña de un juego
 
+use crate::config::Config;
+use crate::histogram::Histogram;
 use crate::language::Language;
-use serde::Serialize;
+use crate::sentiment::Sentiment;
+use crate::top_review::TopReview;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
+///Cantidad de reseñas más votadas que se conservan por cada idioma de un juego.
+const TOP_REVIEWS_PER_GAME_LANGUAGE: usize = 1;
+
 ///Estructura para almacenar los datos de un Juego que obtuvo una reseña.
 /// Se contabiliza la cantidad de reseñas que se hicieron para un Juego en particular y
-/// se almacenan los idiomas en los que se realizó la review de un Juego en particular
-#[derive(Clone, Serialize)]
+/// se almacenan los idiomas en los que se realizó la review de un Juego en particular, además de cuántas
+/// reseñas recomiendan o no el juego y, si se pidió, cuántas reseñas se crearon en cada período
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
     reviews: u32,
     languages: HashMap<String, Language>,
+    sentiment: Sentiment,
+    #[serde(default)]
+    histogram: Histogram,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
-    ///Funcion para crear una nueva instancia de Game
-    pub fn new(reviews: u32, languages: HashMap<String, Language>) -> Game {
-        Game { reviews, languages }
+    ///Funcion para crear una nueva instancia de Game sin reseñas
+    pub fn new() -> Game {
+        Game {
+            reviews: 0,
+            languages: HashMap::new(),
+            sentiment: Sentiment::new(),
+            histogram: Histogram::new(),
+        }
+    }
+
+    ///Registra una reseña del juego escrita en el idioma indicado y creada en el período indicado.
+    /// Solo se clona el texto de la reseña si es necesario conservarla.
+    pub fn add_review(&mut self, language: &str, review: &TopReview, period: Option<i64>) {
+        self.reviews += 1;
+        self.sentiment.add_review(review);
+        self.histogram.add(period);
+
+        let language_data = match self.languages.get_mut(language) {
+            Some(language_data) => language_data,
+            None => self
+                .languages
+                .entry(language.to_string())
+                .or_insert_with(|| Language::new(TOP_REVIEWS_PER_GAME_LANGUAGE)),
+        };
+        language_data.add_review_ref(review, period);
     }
 
     ///Funcion para unificar la informacion de la reseña de un Game con otro, si son el mismo juego segun el nombre
     /// Se aumenta la cantidad de reviews hechas para ese juego y se unifica la informacion de los idiomas
     /// utilizados para hacer reseñas de ese Game
-    pub fn merge_game_wiith_other_review(&mut self, other: &Game) {
+    pub fn merge_game_wiith_other_review(&mut self, other: Game) {
         self.reviews += other.reviews;
+        self.sentiment.merge(other.sentiment);
+        self.histogram.merge(other.histogram);
 
-        for (language, other_lang_data) in &other.languages {
-            self.languages
-                .entry(language.clone())
-                .and_modify(|lang_info| {
-                    lang_info.review_count += other_lang_data.review_count;
-                    lang_info
-                        .top_reviews
-                        .extend(other_lang_data.top_reviews.clone());
-
-                    if let Some(max_review) = lang_info
-                        .top_reviews
-                        .iter()
-                        .max_by_key(|r| r.votes_helpful)
-                        .cloned()
-                    {
-                        lang_info.top_reviews = vec![max_review];
-                    }
-                })
-                .or_insert_with(|| other_lang_data.clone());
+        for (language, other_lang_data) in other.languages {
+            match self.languages.get_mut(&language) {
+                Some(lang_info) => lang_info.merge_language_with_other_review(other_lang_data),
+                None => {
+                    self.languages.insert(language, other_lang_data);
+                }
+            }
         }
     }
 
-    ///Devuelve los top 'n' juegos más populares, dada una cantidad solicitada, junto con los top 3 idiomas utilizados
-    /// para realizar reseñas de ese juego.
-    pub fn top_n_games_reviewed(
-        games: HashMap<String, Game>,
-        num_games_to_display: usize,
-    ) -> Vec<Value> {
-        let mut top_games: Vec<_> = games.into_iter().collect();
+    ///Devuelve los top 'n' juegos más populares, según la cantidad solicitada en la configuración, junto con
+    /// los idiomas más utilizados para realizar reseñas de ese juego.
+    pub fn top_n_games_reviewed(games: &HashMap<String, Game>, config: &Config) -> Vec<Value> {
+        let mut top_games: Vec<_> = games.iter().collect();
         let mut top_games_output: Vec<Value> = Vec::new();
 
-        top_games.sort_by(|a, b| b.1.reviews.cmp(&a.1.reviews).then_with(|| a.0.cmp(&b.0)));
-        top_games.truncate(num_games_to_display);
-
-        for (game_name, game) in &mut top_games {
-            let mut top_game_langs: Vec<_> = game.languages.iter().collect();
-            top_game_langs.sort_by(|a, b| {
-                b.1.review_count
-                    .cmp(&a.1.review_count)
-                    .then_with(|| a.0.cmp(b.0))
-            });
-            top_game_langs.truncate(3);
-
-            let mut game_languages_output = Vec::new();
-
-            for (language, language_data) in top_game_langs {
-                if let Some(top_review) = language_data.top_reviews.first() {
-                    let language_entry = json!({
-                        "language": language,
-                        "review_count": language_data.review_count,
-                        "top_review": top_review.text.clone(),
-                        "top_review_votes": top_review.votes_helpful,
-                    });
-                    game_languages_output.push(language_entry);
-                }
-            }
-            let game_entry = json!({
-                "game": game_name,
-                "review_count": game.reviews,
-                "languages": game_languages_output,
-            });
-            top_games_output.push(game_entry);
+        top_games.sort_by(|a, b| {
+            b.1.reviews
+                .cmp(&a.1.reviews)
+                .then_with(|| config.tie_break.compare(a.0, b.0))
+        });
+        top_games.truncate(config.top_games);
+
+        for (game_name, game) in top_games {
+            top_games_output.push(game.to_json(game_name, config));
         }
 
         top_games_output
     }
+
+    ///Devuelve los top 'n' juegos con más reseñas escritas en el idioma indicado, según la cantidad solicitada en
+    /// la configuración. Cada juego incluye además la cantidad de reseñas en ese idioma en 'language_review_count'.
+    pub fn top_n_games_in_language(
+        games: &HashMap<String, Game>,
+        language: &str,
+        config: &Config,
+    ) -> Vec<Value> {
+        let mut top_games: Vec<_> = games
+            .iter()
+            .filter_map(|(game_name, game)| {
+                let count = game.language_review_count(language)?;
+                Some((game_name, game, count))
+            })
+            .collect();
+
+        top_games.sort_by(|a, b| {
+            b.2.cmp(&a.2)
+                .then_with(|| config.tie_break.compare(a.0, b.0))
+        });
+        top_games.truncate(config.top_games);
+
+        top_games
+            .into_iter()
+            .map(|(game_name, game, count)| {
+                let mut game_entry = game.to_json(game_name, config);
+                game_entry["language_review_count"] = json!(count);
+                game_entry
+            })
+            .collect()
+    }
+
+    ///Devuelve la cantidad de reseñas del juego escritas en el idioma indicado, o None si no tiene ninguna.
+    pub fn language_review_count(&self, language: &str) -> Option<u32> {
+        self.languages
+            .get(language)
+            .map(|language_data| language_data.review_count)
+    }
+
+    ///Devuelve los datos del juego con el formato en que se incluyen en la salida, junto con los idiomas más
+    /// utilizados para realizar reseñas de ese juego.
+    pub fn to_json(&self, game_name: &str, config: &Config) -> Value {
+        let mut top_game_langs: Vec<_> = self.languages.iter().collect();
+        top_game_langs.sort_by(|a, b| {
+            b.1.review_count
+                .cmp(&a.1.review_count)
+                .then_with(|| config.tie_break.compare(a.0, b.0))
+        });
+        top_game_langs.truncate(config.languages_per_game);
+
+        let mut game_languages_output = Vec::new();
+
+        for (language, language_data) in top_game_langs {
+            if let Some(top_review) = language_data.top_reviews.best() {
+                let mut language_entry = json!({
+                    "language": language,
+                    "review_count": language_data.review_count,
+                    "top_review": top_review.text.clone(),
+                    "top_review_votes": top_review.votes_helpful,
+                    "sentiment": language_data.sentiment.to_json(),
+                });
+                if let Some(score) = top_review.score {
+                    language_entry["top_review_score"] = json!(score);
+                }
+                if let Some(granularity) = config.histogram {
+                    language_entry["histogram"] = language_data.histogram.to_json(granularity);
+                }
+                game_languages_output.push(language_entry);
+            }
+        }
+        let mut game_entry = json!({
+            "game": game_name,
+            "review_count": self.reviews,
+            "languages": game_languages_output,
+            "sentiment": self.sentiment.to_json(),
+        });
+        if let Some(granularity) = config.histogram {
+            game_entry["histogram"] = self.histogram.to_json(granularity);
+        }
+        game_entry
+    }
 }
@@ -0,0 +1,347 @@
+//! Módulo con un agrupamiento genérico de las reseñas: se agrupan según los valores de los campos elegidos (por
+//! ejemplo `app_name`, `language`, `recommended` o `year`) y en cada grupo se calculan las agregaciones elegidas.
+//! Cada hilo acumula sus grupos en un GroupedReviews parcial y los parciales se fusionan en el reduce del modelo
+//! fork-join. Las agregaciones son sumas o rankings acotados, por lo que la fusión es asociativa y conmutativa.
+use crate::filter::{Field, FieldValue, Filterable};
+use crate::review_error::ReviewError;
+use crate::top_k::TopK;
+use crate::top_review::TopReview;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+///Agregación que se calcula en cada grupo.
+/// ###  Count
+/// Cantidad de reseñas del grupo.
+/// ###  Sum
+/// Suma de un campo numérico. Las reseñas que no tienen el campo informado no suman.
+/// ###  TopK
+/// Las 'k' reseñas con mayor valor de un campo numérico, desempatando por votos útiles y luego por texto. Las
+/// reseñas que no tienen el campo informado no se consideran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Aggregate {
+    Count,
+    Sum(Field),
+    TopK(usize, Field),
+}
+
+impl Aggregate {
+    ///Interpreta una agregación con el formato `count`, `sum:<campo>` o `top:<k>:<campo>`.
+    pub fn parse(spec: &str) -> Result<Aggregate, ReviewError> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let aggregate = match parts.as_slice() {
+            ["count"] => Aggregate::Count,
+            ["sum", field] => Aggregate::Sum(number_field(field)?),
+            ["top", k, field] => {
+                let k = k.parse().map_err(|_| {
+                    ReviewError::InvalidArgument(format!(
+                        "La cantidad de reseñas de '{}' debe ser un número entero no negativo",
+                        spec
+                    ))
+                })?;
+                Aggregate::TopK(k, number_field(field)?)
+            }
+            _ => {
+                return Err(ReviewError::InvalidArgument(format!(
+                "Agregación inválida '{}'. Formatos posibles: count, sum:<campo>, top:<k>:<campo>",
+                spec
+            )))
+            }
+        };
+        Ok(aggregate)
+    }
+
+    ///Devuelve el nombre con el que se incluye la agregación en cada grupo de la salida. Incluye todos los
+    /// parámetros de la agregación, para que dos agregaciones distintas nunca tengan el mismo nombre.
+    pub fn name(&self) -> String {
+        match self {
+            Aggregate::Count => "count".to_string(),
+            Aggregate::Sum(field) => format!("sum_{}", field.name()),
+            Aggregate::TopK(k, field) => format!("top_{}_{}", k, field.name()),
+        }
+    }
+
+    fn accumulator(&self) -> Accumulator {
+        match self {
+            Aggregate::Count => Accumulator::Count(0),
+            Aggregate::Sum(_) => Accumulator::Sum(0.0),
+            Aggregate::TopK(k, _) => Accumulator::TopK(TopK::new(*k)),
+        }
+    }
+}
+
+fn number_field(name: &str) -> Result<Field, ReviewError> {
+    match Field::parse(name) {
+        Some(field) if field.is_number() => Ok(field),
+        Some(_) => Err(ReviewError::InvalidArgument(format!(
+            "El campo '{}' no es numérico y no puede sumarse ni rankearse",
+            name
+        ))),
+        None => Err(ReviewError::InvalidArgument(format!(
+            "Campo desconocido '{}'",
+            name
+        ))),
+    }
+}
+
+///Definición de un agrupamiento: los campos que forman la clave de cada grupo y las agregaciones a calcular.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupBy {
+    pub keys: Vec<Field>,
+    pub aggregates: Vec<Aggregate>,
+}
+
+impl GroupBy {
+    ///Crea un agrupamiento a partir de la lista de campos separados por coma y de las agregaciones indicadas.
+    /// Si no se indican agregaciones se cuenta la cantidad de reseñas de cada grupo.
+    pub fn new(keys: &str, aggregates: Vec<Aggregate>) -> Result<GroupBy, ReviewError> {
+        let keys = keys
+            .split(',')
+            .map(|name| {
+                Field::parse(name.trim()).ok_or_else(|| {
+                    ReviewError::InvalidArgument(format!("Campo desconocido '{}'", name.trim()))
+                })
+            })
+            .collect::<Result<Vec<Field>, ReviewError>>()?;
+        let aggregates = if aggregates.is_empty() {
+            vec![Aggregate::Count]
+        } else {
+            aggregates
+        };
+        Ok(GroupBy { keys, aggregates })
+    }
+}
+
+///Número usado como parte de la clave de un grupo, comparable y con hash a partir de su representación binaria.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KeyNumber(f64);
+
+impl PartialEq for KeyNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for KeyNumber {}
+
+impl PartialOrd for KeyNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for KeyNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+///Valor de un campo de la clave de un grupo. Las reseñas que no tienen informado el campo forman su propio grupo.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum KeyValue {
+    Missing,
+    Bool(bool),
+    Number(KeyNumber),
+    Text(String),
+}
+
+impl KeyValue {
+    fn of(value: Option<FieldValue<'_>>) -> KeyValue {
+        match value {
+            None => KeyValue::Missing,
+            Some(FieldValue::Bool(value)) => KeyValue::Bool(value),
+            // Se normaliza -0.0 para que quede en el mismo grupo que 0.0.
+            Some(FieldValue::Number(value)) => KeyValue::Number(KeyNumber(value + 0.0)),
+            Some(FieldValue::Text(value)) => KeyValue::Text(value.to_string()),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            KeyValue::Missing => Value::Null,
+            KeyValue::Bool(value) => json!(value),
+            KeyValue::Number(KeyNumber(value)) => number_json(*value),
+            KeyValue::Text(value) => json!(value),
+        }
+    }
+}
+
+///Estado parcial de una agregación en un grupo.
+#[derive(Clone, Serialize, Deserialize)]
+enum Accumulator {
+    Count(u64),
+    Sum(f64),
+    TopK(TopK<TopReview>),
+}
+
+impl Accumulator {
+    fn merge(&mut self, other: Accumulator) {
+        match (self, other) {
+            (Accumulator::Count(count), Accumulator::Count(other)) => *count += other,
+            (Accumulator::Sum(sum), Accumulator::Sum(other)) => *sum += other,
+            (Accumulator::TopK(top), Accumulator::TopK(other)) => top.merge(other),
+            _ => {}
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Accumulator::Count(count) => json!(count),
+            Accumulator::Sum(sum) => number_json(*sum),
+            Accumulator::TopK(top) => top
+                .sorted()
+                .into_iter()
+                .map(|review| {
+                    json!({
+                        "review": review.text,
+                        "votes": review.votes_helpful,
+                        "value": review.score.map(number_json),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+///Estructura que acumula los grupos de reseñas de un agrupamiento.
+/// Como las claves de los grupos no son strings, en el snapshot los grupos se guardan como una lista de pares.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GroupedReviews {
+    #[serde(
+        serialize_with = "serialize_groups",
+        deserialize_with = "deserialize_groups"
+    )]
+    groups: Groups,
+}
+
+impl GroupedReviews {
+    ///Crea una nueva instancia sin grupos.
+    pub fn new() -> GroupedReviews {
+        GroupedReviews::default()
+    }
+
+    ///Acumula una reseña, cuyo texto es 'text', en el grupo que corresponde a sus valores de los campos clave.
+    /// El texto solo se clona si la reseña queda entre las mejores de alguna agregación TopK.
+    pub fn add<T: Filterable>(&mut self, group_by: &GroupBy, text: &str, review: &T) {
+        let key: Vec<KeyValue> = group_by
+            .keys
+            .iter()
+            .map(|field| KeyValue::of(review.field_value(*field)))
+            .collect();
+        let accumulators = self.groups.entry(key).or_insert_with(|| {
+            group_by
+                .aggregates
+                .iter()
+                .map(Aggregate::accumulator)
+                .collect()
+        });
+
+        for (aggregate, accumulator) in group_by.aggregates.iter().zip(accumulators) {
+            match (aggregate, accumulator) {
+                (Aggregate::Count, Accumulator::Count(count)) => *count += 1,
+                (Aggregate::Sum(field), Accumulator::Sum(sum)) => {
+                    if let Some(value) = number(review, *field) {
+                        *sum += value;
+                    }
+                }
+                (Aggregate::TopK(_, field), Accumulator::TopK(top)) => {
+                    if let Some(value) = number(review, *field) {
+                        let votes_helpful = number(review, Field::VotesHelpful).unwrap_or(0.0);
+                        let recommended = match review.field_value(Field::Recommended) {
+                            Some(FieldValue::Bool(recommended)) => Some(recommended),
+                            _ => None,
+                        };
+                        // Sin texto el candidato gana todos los desempates, por lo que si no es aceptado
+                        // tampoco lo sería con su texto y se evita clonarlo.
+                        let mut candidate = TopReview::new(
+                            String::new(),
+                            votes_helpful as u32,
+                            recommended,
+                            Some(value),
+                        );
+                        if top.accepts(&candidate) {
+                            candidate.text = text.to_string();
+                            top.push(candidate);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    ///Fusiona los grupos de otra instancia.
+    pub fn merge(&mut self, other: GroupedReviews) {
+        for (key, other_accumulators) in other.groups {
+            match self.groups.get_mut(&key) {
+                Some(accumulators) => {
+                    for (accumulator, other) in accumulators.iter_mut().zip(other_accumulators) {
+                        accumulator.merge(other);
+                    }
+                }
+                None => {
+                    self.groups.insert(key, other_accumulators);
+                }
+            }
+        }
+    }
+
+    ///Devuelve los grupos con el formato en que se incluyen en la salida, ordenados por clave. Cada grupo tiene
+    /// un objeto 'key' con el valor de cada campo clave y un campo por agregación.
+    pub fn to_json(&self, group_by: &GroupBy) -> Vec<Value> {
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+
+        groups
+            .into_iter()
+            .map(|(key, accumulators)| {
+                let key: Map<String, Value> = group_by
+                    .keys
+                    .iter()
+                    .zip(key)
+                    .map(|(field, value)| (field.name().to_string(), value.to_json()))
+                    .collect();
+                let mut group = json!({ "key": key });
+                for (aggregate, accumulator) in group_by.aggregates.iter().zip(accumulators) {
+                    group[aggregate.name()] = accumulator.to_json();
+                }
+                group
+            })
+            .collect()
+    }
+}
+
+type Groups = HashMap<Vec<KeyValue>, Vec<Accumulator>>;
+
+fn serialize_groups<S: Serializer>(groups: &Groups, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(groups)
+}
+
+fn deserialize_groups<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Groups, D::Error> {
+    let entries: Vec<(Vec<KeyValue>, Vec<Accumulator>)> = Vec::deserialize(deserializer)?;
+    Ok(entries.into_iter().collect())
+}
+
+fn number<T: Filterable>(review: &T, field: Field) -> Option<f64> {
+    match review.field_value(field) {
+        Some(FieldValue::Number(value)) => Some(value),
+        _ => None,
+    }
+}
+
+///Devuelve los números enteros sin parte decimal, para que campos como el año se vean como en el csv.
+fn number_json(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
@@ -0,0 +1,92 @@
+//! Módulo para contabilizar las reseñas de un juego o idioma por período (mes o año) según su fecha de creación,
+//! de forma de poder graficar la evolución del volumen de reseñas.
+use crate::date::date_from_timestamp;
+use crate::review_error::ReviewError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+///Granularidad de los períodos en que se agrupan las reseñas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Granularity {
+    Month,
+    Year,
+}
+
+impl Granularity {
+    ///Interpreta la granularidad recibida por línea de comandos.
+    pub fn parse(value: &str) -> Result<Granularity, ReviewError> {
+        match value {
+            "month" => Ok(Granularity::Month),
+            "year" => Ok(Granularity::Year),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Granularidad de histograma inválida '{}', se esperaba 'month' o 'year'",
+                value
+            ))),
+        }
+    }
+
+    ///Devuelve el período al que pertenece un timestamp unix. Los períodos son números consecutivos y crecientes
+    /// en el tiempo, por lo que pueden usarse como clave ordenada sin formatear la fecha por cada reseña.
+    pub fn period(&self, timestamp: i64) -> i64 {
+        let (year, month, _) = date_from_timestamp(timestamp);
+        match self {
+            Granularity::Month => year * 12 + i64::from(month) - 1,
+            Granularity::Year => year,
+        }
+    }
+
+    ///Devuelve la etiqueta de un período con formato 'AAAA-MM' o 'AAAA', según la granularidad.
+    pub fn label(&self, period: i64) -> String {
+        match self {
+            Granularity::Month => {
+                format!(
+                    "{:04}-{:02}",
+                    period.div_euclid(12),
+                    period.rem_euclid(12) + 1
+                )
+            }
+            Granularity::Year => format!("{:04}", period),
+        }
+    }
+}
+
+///Estructura para almacenar la cantidad de reseñas por período.
+/// Las reseñas sin fecha de creación no se contabilizan. La fusión suma los contadores de cada período, por lo
+/// que es asociativa y conmutativa.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Histogram {
+    counts: BTreeMap<i64, u32>,
+}
+
+impl Histogram {
+    ///Crea un histograma vacío.
+    pub fn new() -> Histogram {
+        Histogram::default()
+    }
+
+    ///Registra una reseña en el período indicado, si tiene uno.
+    pub fn add(&mut self, period: Option<i64>) {
+        if let Some(period) = period {
+            *self.counts.entry(period).or_insert(0) += 1;
+        }
+    }
+
+    ///Fusiona los contadores de otro histograma.
+    pub fn merge(&mut self, other: Histogram) {
+        for (period, count) in other.counts {
+            *self.counts.entry(period).or_insert(0) += count;
+        }
+    }
+
+    ///Devuelve el histograma con el formato en que se incluye en la salida: un objeto con la cantidad de reseñas
+    /// por período, ordenado cronológicamente.
+    pub fn to_json(&self, granularity: Granularity) -> Value {
+        let counts: Map<String, Value> = self
+            .counts
+            .iter()
+            .map(|(period, count)| (granularity.label(*period), Value::from(*count)))
+            .collect();
+        Value::Object(counts)
+    }
+}
@@ -0,0 +1,85 @@
+//! Módulo con el algoritmo HyperLogLog para estimar la cantidad de elementos distintos de un flujo usando
+//! memoria fija (2^PRECISION bytes por instancia), sin importar cuántos elementos se agreguen.
+//! La fusión toma el máximo de cada registro, por lo que es asociativa, conmutativa e idempotente: el resultado
+//! es el mismo sin importar cómo se repartieron los elementos entre los hilos.
+use serde::{Deserialize, Serialize};
+
+///Cantidad de bits del hash que eligen el registro. Con 12 bits se usan 4096 registros.
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+///Error relativo estándar de la estimación: 1.04 / sqrt(cantidad de registros), alrededor del 1.6%.
+pub const RELATIVE_STANDARD_ERROR: f64 = 1.04 / 64.0;
+
+///Estructura que estima la cantidad de elementos distintos agregados.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    ///Crea una nueva instancia sin elementos.
+    pub fn new() -> HyperLogLog {
+        HyperLogLog {
+            registers: vec![0; REGISTERS],
+        }
+    }
+
+    ///Agrega un elemento identificado por un número. Agregar varias veces el mismo elemento no cambia la estimación.
+    pub fn add(&mut self, item: u64) {
+        let hash = mix(item);
+        let register = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[register] {
+            self.registers[register] = rank;
+        }
+    }
+
+    ///Fusiona los elementos de otra instancia.
+    pub fn merge(&mut self, other: HyperLogLog) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers) {
+            *register = (*register).max(other_register);
+        }
+    }
+
+    ///Devuelve la cantidad estimada de elementos distintos agregados.
+    /// Para cantidades chicas, donde la estimación de HyperLogLog tiene sesgo, se usa el conteo lineal de
+    /// registros vacíos.
+    pub fn estimate(&self) -> u64 {
+        let registers = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / registers);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum();
+        let estimate = alpha * registers * registers / sum;
+
+        let empty = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        if estimate <= 2.5 * registers && empty > 0 {
+            return (registers * (registers / empty as f64).ln()).round() as u64;
+        }
+        estimate.round() as u64
+    }
+}
+
+///Mezcla los bits del identificador (finalizador de MurmurHash3) para que los valores consecutivos, como los ids
+/// de Steam, se distribuyan uniformemente entre los registros.
+fn mix(mut value: u64) -> u64 {
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    value ^= value >> 33;
+    value
+}
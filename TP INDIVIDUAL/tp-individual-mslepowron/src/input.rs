@@ -0,0 +1,203 @@
+//! Módulo para resolver las fuentes de reseñas a procesar: archivos sueltos, directorios recorridos
+//! recursivamente con patrones de inclusión/exclusión, o la entrada estándar.
+//! Los archivos comprimidos con gzip o zstd se descomprimen de forma transparente al leerlos.
+use crate::config::Config;
+use crate::review_error::ReviewError;
+use flate2::read::MultiGzDecoder;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::HashSet;
+use std::fs::{read_dir, File};
+use std::io::{self, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+///Ruta que indica que las reseñas deben leerse de la entrada estándar.
+pub const STDIN_PATH: &str = "-";
+
+///Patrones de archivos que se procesan al recorrer un directorio si no se indica ninguno.
+const DEFAULT_INCLUDE_PATTERNS: [&str; 3] = ["*.csv", "*.csv.gz", "*.csv.zst"];
+
+///Primeros bytes de un archivo comprimido con gzip.
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+///Primeros bytes de un archivo comprimido con zstd.
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+///Fuente de la que se leen reseñas en formato csv.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    ///Nombre con el que se identifica la fuente en el resumen de errores.
+    pub fn name(&self) -> String {
+        match self {
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Stdin => "<stdin>".to_string(),
+        }
+    }
+
+    ///Clave con la que se registra la fuente en el snapshot: la ruta canónica del archivo, para que `./data` y
+    /// `data` correspondan al mismo registro. Devuelve None para la entrada estándar, que no se registra, y para
+    /// los archivos cuya ruta no puede resolverse.
+    pub fn snapshot_key(&self) -> Option<String> {
+        let path = self.path()?.canonicalize().ok()?;
+        Some(path.display().to_string())
+    }
+
+    ///Ruta del archivo, si la fuente es un archivo.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            InputSource::File(path) => Some(path),
+            InputSource::Stdin => None,
+        }
+    }
+
+    ///Abre la fuente para leerla. Si el contenido está comprimido con gzip o zstd (se detecta por los primeros
+    /// bytes, no por la extensión) se devuelve un lector que lo descomprime.
+    pub fn open(&self) -> io::Result<Box<dyn Read + Send>> {
+        decompress(self.open_raw()?)
+    }
+
+    ///Abre la fuente para leer su contenido tal como está, sin descomprimirlo.
+    pub fn open_raw(&self) -> io::Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            InputSource::File(path) => Box::new(BufReader::new(File::open(path)?)),
+            InputSource::Stdin => Box::new(BufReader::new(io::stdin())),
+        })
+    }
+}
+
+///Resuelve las fuentes a procesar a partir de la ruta de entrada y de los archivos y patrones de la configuración.
+/// - Si la ruta es `-` se lee de la entrada estándar.
+/// - Si la ruta es un archivo se procesa ese archivo.
+/// - Si la ruta es un directorio se recorre recursivamente, procesando los archivos cuya ruta relativa coincida
+///   con algún patrón de inclusión y con ninguno de exclusión.
+///
+/// Los archivos indicados explícitamente en la configuración se agregan siempre, salvo que ya estén incluidos: cada
+/// archivo se procesa una sola vez aunque se llegue a él por distintas rutas.
+/// Devuelve error si la ruta de entrada no existe o algún patrón es inválido.
+pub fn collect_inputs(input_path: &str, config: &Config) -> Result<Vec<InputSource>, ReviewError> {
+    let mut inputs = Vec::new();
+    let path = Path::new(input_path);
+
+    if input_path == STDIN_PATH {
+        inputs.push(InputSource::Stdin);
+    } else if path.is_dir() {
+        let include = build_glob_set(&config.include_patterns, &DEFAULT_INCLUDE_PATTERNS)?;
+        let exclude = build_glob_set(&config.exclude_patterns, &[])?;
+        let mut files = Vec::new();
+        walk_directory(path, path, &include, &exclude, &mut files)?;
+        files.sort();
+        inputs.extend(files.into_iter().map(InputSource::File));
+    } else if path.is_file() {
+        inputs.push(InputSource::File(path.to_path_buf()));
+    } else {
+        return Err(ReviewError::DirectoryNotFound(format!(
+            "No existe la ruta de entrada '{}'",
+            input_path
+        )));
+    }
+
+    inputs.extend(
+        config
+            .input_files
+            .iter()
+            .map(|file| InputSource::File(PathBuf::from(file))),
+    );
+    Ok(deduplicate(inputs))
+}
+
+///Quita las fuentes repetidas, conservando la primera aparición. Dos archivos son el mismo si tienen la misma ruta
+/// canónica, o la misma ruta si no puede resolverse.
+fn deduplicate(inputs: Vec<InputSource>) -> Vec<InputSource> {
+    let mut seen = HashSet::new();
+    inputs
+        .into_iter()
+        .filter(|input| {
+            let identity = input
+                .path()
+                .map(|path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+            seen.insert(identity)
+        })
+        .collect()
+}
+
+fn build_glob_set(patterns: &[String], defaults: &[&str]) -> Result<GlobSet, ReviewError> {
+    let mut builder = GlobSetBuilder::new();
+    let patterns: Vec<&str> = if patterns.is_empty() {
+        defaults.to_vec()
+    } else {
+        patterns.iter().map(String::as_str).collect()
+    };
+
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            ReviewError::InvalidArgument(format!("Patrón inválido '{}': {}", pattern, e))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| ReviewError::InvalidArgument(format!("Patrones inválidos: {}", e)))
+}
+
+///Recorre el directorio recursivamente. No se siguen los enlaces simbólicos a directorios para evitar ciclos.
+fn walk_directory(
+    root: &Path,
+    dir: &Path,
+    include: &GlobSet,
+    exclude: &GlobSet,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ReviewError> {
+    let entries = read_dir(dir).map_err(|e| {
+        ReviewError::DirectoryNotFound(format!(
+            "Error al leer el directorio '{}': {}",
+            dir.display(),
+            e
+        ))
+    })?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            walk_directory(root, &path, include, exclude, files)?;
+        } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if include.is_match(relative) && !exclude.is_match(relative) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+///Devuelve un lector que descomprime el contenido de 'reader' si está comprimido con gzip o zstd (según sus
+/// primeros bytes), o que lo lee tal como está si no.
+pub fn decompress<'a>(
+    mut reader: Box<dyn Read + Send + 'a>,
+) -> io::Result<Box<dyn Read + Send + 'a>> {
+    let mut magic = [0u8; 4];
+    let mut read = 0;
+    while read < magic.len() {
+        match reader.read(&mut magic[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+
+    let header = magic[..read].to_vec();
+    let reader = Cursor::new(header).chain(reader);
+
+    if magic[..read].starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if magic[..read] == ZSTD_MAGIC {
+        Ok(Box::new(zstd::Decoder::new(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
@@ -1,74 +1,113 @@
 //! Módulo para manejar los datos estadísticos sobre los idiomas en los que se realizan las reseñas.
 use std::collections::HashMap;
 
+use crate::config::Config;
+use crate::histogram::Histogram;
+use crate::sentiment::Sentiment;
+use crate::top_k::TopK;
 use crate::top_review::TopReview;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 ///Estructura para almacenar los datos de un idioma en el que se escribió una reseña.
 /// ###  review_count
 /// Se contabiliza la cantidad de reseñas que se escribieron en un idioma en particular
 /// ###  top_reviews
-/// Se almacenan las reseñas más votadas por usuarios que fueron escritas en un idioma en particualr
+/// Se almacenan las reseñas más votadas por usuarios que fueron escritas en un idioma en particualr.
+/// Solo se conservan tantas como la capacidad indicada al crear el idioma.
+/// ###  sentiment
+/// Se contabilizan las reseñas que recomiendan o no el juego, junto con la más votada de cada tipo.
+/// ###  histogram
+/// Se contabilizan las reseñas por período de creación, si se pidió el histograma en la configuración.
 pub struct Language {
     pub review_count: u32,
-    pub top_reviews: Vec<TopReview>,
+    pub top_reviews: TopK<TopReview>,
+    pub sentiment: Sentiment,
+    #[serde(default)]
+    pub histogram: Histogram,
 }
 
 impl Language {
-    ///Funcion para crear una nueva instancia de Language
-    pub fn new(review_count: u32, review_in_language: Vec<TopReview>) -> Language {
+    ///Funcion para crear una nueva instancia de Language sin reseñas, que conservará como máximo
+    /// 'max_top_reviews' reseñas más votadas.
+    pub fn new(max_top_reviews: usize) -> Language {
         Language {
-            review_count,
-            top_reviews: review_in_language,
+            review_count: 0,
+            top_reviews: TopK::new(max_top_reviews),
+            sentiment: Sentiment::new(),
+            histogram: Histogram::new(),
+        }
+    }
+
+    ///Registra una reseña escrita en este idioma, creada en el período indicado.
+    pub fn add_review(&mut self, review: TopReview, period: Option<i64>) {
+        self.review_count += 1;
+        self.sentiment.add_review(&review);
+        self.histogram.add(period);
+        self.top_reviews.push(review);
+    }
+
+    ///Registra una reseña escrita en este idioma, clonando el texto solo si es necesario conservarla.
+    pub fn add_review_ref(&mut self, review: &TopReview, period: Option<i64>) {
+        self.review_count += 1;
+        self.sentiment.add_review(review);
+        self.histogram.add(period);
+        if self.top_reviews.accepts(review) {
+            self.top_reviews.push(review.clone());
         }
     }
 
     ///Funcion para unificar la informacion del Language de una reseña con otro, si son el mismo idioma segun su nombre
     /// Se aumenta la cantidad de reviews hechas en ese idioma y se unifica la informacion de las reseñas mas populares
     /// votadas por los usuarios que fueron escritas en ese idoma.
-    pub fn merge_language_with_other_review(&mut self, other: &Language) {
+    pub fn merge_language_with_other_review(&mut self, other: Language) {
         self.review_count += other.review_count;
-        self.top_reviews.extend(other.top_reviews.clone());
-
-        self.top_reviews
-            .sort_by_key(|r| std::cmp::Reverse(r.votes_helpful));
-        self.top_reviews.truncate(10);
+        self.top_reviews.merge(other.top_reviews);
+        self.sentiment.merge(other.sentiment);
+        self.histogram.merge(other.histogram);
     }
 
-    ///Devuelve los top 'n' idiomas más populares en los que se escribieron más reseñasn, dada una cantidad solicitada,
-    /// junto con las top 10 reseñas de cada idioma.
-    pub fn top_n_languages(
-        languages: HashMap<String, Language>,
-        num_languages_to_display: usize,
-    ) -> Vec<Value> {
-        let mut top_languages: Vec<_> = languages.into_iter().collect();
+    ///Devuelve los top 'n' idiomas más populares en los que se escribieron más reseñas, según la cantidad
+    /// solicitada en la configuración, junto con las reseñas más votadas de cada idioma.
+    pub fn top_n_languages(languages: &HashMap<String, Language>, config: &Config) -> Vec<Value> {
+        let mut top_languages: Vec<_> = languages.iter().collect();
         let mut top_languages_output: Vec<Value> = Vec::new();
 
         top_languages.sort_by(|a, b| {
             b.1.review_count
                 .cmp(&a.1.review_count)
-                .then_with(|| a.0.cmp(&b.0))
+                .then_with(|| config.tie_break.compare(a.0, b.0))
         });
-        top_languages.truncate(num_languages_to_display);
+        top_languages.truncate(config.top_languages);
 
-        for (language, lang_data) in &mut top_languages {
+        for (language, lang_data) in top_languages {
             let mut top_reviews_json = Vec::new();
-            lang_data.top_reviews.truncate(10);
 
-            for review in lang_data.top_reviews.iter().take(10) {
-                let review_entry = json!({
-                    "review": review.text.clone(),
+            for review in lang_data
+                .top_reviews
+                .sorted()
+                .into_iter()
+                .take(config.reviews_per_language)
+            {
+                let mut review_entry = json!({
+                    "review": review.text,
                     "votes": review.votes_helpful,
                 });
+                if let Some(score) = review.score {
+                    review_entry["score"] = json!(score);
+                }
                 top_reviews_json.push(review_entry);
             }
-            let language_entry = json!({
+            let mut language_entry = json!({
                 "language": language,
                 "review_count": lang_data.review_count,
                 "top_reviews": top_reviews_json,
+                "sentiment": lang_data.sentiment.to_json(),
             });
+            if let Some(granularity) = config.histogram {
+                language_entry["histogram"] = lang_data.histogram.to_json(granularity);
+            }
 
             top_languages_output.push(language_entry);
         }
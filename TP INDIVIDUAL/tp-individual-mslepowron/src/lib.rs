@@ -3,11 +3,31 @@
 //! Se presenta la implementación de una aplicación para procesar datos de juegos utilizando un modelo Fork-Join.
 //!
 //! Los siguientes son módulos para la creación de estructuras que permitirán el manejo de los datos.
+pub mod approximate;
+pub mod chunking;
+pub mod config;
+pub mod date;
+pub mod error_summary;
+pub mod filter;
 pub mod game;
+pub mod group_by;
+pub mod histogram;
+pub mod hyperloglog;
+pub mod input;
 pub mod language;
+pub mod memory;
+pub mod monitor;
 pub mod output_data;
+pub mod output_writer;
 pub mod processor;
+pub mod query_service;
 pub mod review_error;
 pub mod review_record;
 pub mod review_result;
+pub mod score;
+pub mod sentiment;
+pub mod snapshot;
+pub mod space_saving;
+pub mod text_stats;
+pub mod top_k;
 pub mod top_review;
@@ -1,23 +1,24 @@
 use std::env;
 use std::time::Instant;
 
-mod game;
-mod language;
-mod output_data;
-mod processor;
-mod review_error;
-mod review_record;
-mod review_result;
-mod top_review;
-use processor::fork_join;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::memory;
+use tp1_mslepowron::processor::fork_join;
+use tp1_mslepowron::query_service::QueryService;
 
 const ARGUMENTS_REQUIRED: usize = 4;
+const SERVE_SNAPSHOT: &str = "--serve-snapshot";
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != ARGUMENTS_REQUIRED {
-        eprintln!("Faltan o sobran argumentos. El formato esperado es -> <input-path> <num-threads> <output-file-name>");
+    if args.get(1).map(String::as_str) == Some(SERVE_SNAPSHOT) {
+        serve_snapshot(&args[2..]);
+        return;
+    }
+
+    if args.len() < ARGUMENTS_REQUIRED {
+        eprintln!("Faltan argumentos. El formato esperado es -> <input-path> <num-threads> <output-file-name> [opciones]");
         return;
     }
 
@@ -30,14 +31,53 @@ fn main() {
         }
     };
     let output_path = &args[3];
+    let config = match Config::from_args(&args[ARGUMENTS_REQUIRED..]) {
+        Ok(config) => config,
+        Err(e) => {
+            e.display_error();
+            return;
+        }
+    };
 
     let start = Instant::now();
 
-    match fork_join(amount_threads, path.to_string(), output_path.to_string()) {
-        Ok(_) => {
+    match fork_join(
+        amount_threads,
+        path.to_string(),
+        output_path.to_string(),
+        &config,
+    ) {
+        Ok(written) => {
             println!("{:?}", start.elapsed());
-            println!("Process completed. Output file generated.")
+            if let Some(peak) = memory::peak_memory_kb() {
+                println!("Peak memory: {} KB", peak);
+            }
+            println!("Process completed. Output files generated:");
+            for path in written {
+                println!("  {}", path);
+            }
         }
         Err(e) => e.display_error(),
     }
 }
+
+/// Atiende consultas sobre el resultado guardado en un snapshot, sin procesar ningún archivo.
+fn serve_snapshot(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!("Faltan argumentos. El formato esperado es -> --serve-snapshot <snapshot> <dirección> [opciones]");
+        return;
+    }
+
+    let config = match Config::from_args(&args[2..]) {
+        Ok(config) => config,
+        Err(e) => {
+            e.display_error();
+            return;
+        }
+    };
+    let result =
+        QueryService::from_snapshot(&args[0], config).and_then(|service| service.serve(&args[1]));
+    if let Err(e) = result {
+        e.display_error();
+    }
+}
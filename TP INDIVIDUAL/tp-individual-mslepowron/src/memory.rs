@@ -0,0 +1,18 @@
+//! Módulo para consultar el uso de memoria del proceso.
+use std::fs::read_to_string;
+
+const PROC_STATUS_PATH: &str = "/proc/self/status";
+const PEAK_MEMORY_FIELD: &str = "VmHWM:";
+
+///Devuelve el pico de memoria residente del proceso en KB.
+/// Devuelve None si la plataforma no expone esa información (solo se soporta Linux).
+pub fn peak_memory_kb() -> Option<u64> {
+    let status = read_to_string(PROC_STATUS_PATH).ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with(PEAK_MEMORY_FIELD))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
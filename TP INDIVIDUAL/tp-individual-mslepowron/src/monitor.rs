@@ -0,0 +1,240 @@
+//! Módulo para seguir el avance del procesamiento y medir el tiempo de cada etapa.
+//! Los contadores son atómicos, por lo que los hilos del pool los actualizan sin bloquearse entre sí y otro hilo
+//! puede consultar el progreso mientras se procesa.
+use serde_json::{json, Value};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+///Etapas del procesamiento cuyo tiempo se mide por hilo.
+/// ###  Parse
+/// Lectura y deserialización de las filas del csv.
+/// ###  Map
+/// Acumulación de cada reseña en el resultado parcial del hilo.
+/// ###  Reduce
+/// Fusión de los resultados parciales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Map,
+    Reduce,
+}
+
+///Tiempos acumulados por un hilo, en nanosegundos.
+#[derive(Default)]
+struct ThreadStats {
+    rows: AtomicU64,
+    parse_nanos: AtomicU64,
+    map_nanos: AtomicU64,
+    reduce_nanos: AtomicU64,
+}
+
+///Estado del progreso en un momento dado.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressSnapshot {
+    pub rows: u64,
+    pub bytes: u64,
+    pub files_completed: u64,
+    pub files_total: u64,
+    pub elapsed: Duration,
+}
+
+impl ProgressSnapshot {
+    ///Cantidad de filas procesadas por segundo desde el comienzo.
+    pub fn rows_per_sec(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.rows as f64 / seconds
+    }
+}
+
+impl fmt::Display for ProgressSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Progreso: {}/{} archivos, {} filas, {:.1} MB leídos, {:.0} filas/s",
+            self.files_completed,
+            self.files_total,
+            self.rows,
+            self.bytes as f64 / BYTES_PER_MB,
+            self.rows_per_sec()
+        )
+    }
+}
+
+///Estructura compartida por los hilos del procesamiento para registrar el progreso y, si se pidió, el tiempo de
+/// cada etapa por hilo.
+/// ###  threads
+/// Tiempos por hilo del pool, más uno para los hilos externos al pool. Es None si no se miden tiempos, en cuyo
+/// caso medir una etapa no tiene costo.
+pub struct Monitor {
+    start: Instant,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    files_completed: AtomicU64,
+    files_total: AtomicU64,
+    serialization_nanos: AtomicU64,
+    threads: Option<Vec<ThreadStats>>,
+}
+
+impl Monitor {
+    ///Crea un monitor para un pool de 'threads' hilos. Si 'collect_stats' es falso solo se registra el progreso.
+    pub fn new(threads: usize, collect_stats: bool) -> Monitor {
+        Monitor {
+            start: Instant::now(),
+            rows: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            files_completed: AtomicU64::new(0),
+            files_total: AtomicU64::new(0),
+            serialization_nanos: AtomicU64::new(0),
+            threads: collect_stats.then(|| (0..=threads).map(|_| ThreadStats::default()).collect()),
+        }
+    }
+
+    ///Registra filas leídas del csv, válidas o no.
+    pub fn add_rows(&self, rows: u64) {
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    ///Registra bytes leídos de la entrada (antes de descomprimir).
+    pub fn add_bytes(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    ///Registra la cantidad de fuentes a procesar.
+    pub fn set_files_total(&self, files: u64) {
+        self.files_total.store(files, Ordering::Relaxed);
+    }
+
+    ///Registra que se terminó de procesar una fuente.
+    pub fn file_completed(&self) {
+        self.files_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ///Registra el tiempo que llevó escribir la salida.
+    pub fn record_serialization(&self, elapsed: Duration) {
+        self.serialization_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    ///Ejecuta 'f' midiendo su tiempo como parte de la etapa indicada, en el hilo actual.
+    pub fn time<R>(&self, stage: Stage, f: impl FnOnce() -> R) -> R {
+        let Some(threads) = &self.threads else {
+            return f();
+        };
+        let start = Instant::now();
+        let result = f();
+        let nanos = start.elapsed().as_nanos() as u64;
+
+        let slot = rayon::current_thread_index()
+            .map_or(threads.len() - 1, |index| index.min(threads.len() - 1));
+        let stats = &threads[slot];
+        let counter = match stage {
+            Stage::Parse => &stats.parse_nanos,
+            Stage::Map => {
+                stats.rows.fetch_add(1, Ordering::Relaxed);
+                &stats.map_nanos
+            }
+            Stage::Reduce => &stats.reduce_nanos,
+        };
+        counter.fetch_add(nanos, Ordering::Relaxed);
+        result
+    }
+
+    ///Devuelve el progreso hasta el momento.
+    pub fn progress(&self) -> ProgressSnapshot {
+        ProgressSnapshot {
+            rows: self.rows.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            files_completed: self.files_completed.load(Ordering::Relaxed),
+            files_total: self.files_total.load(Ordering::Relaxed),
+            elapsed: self.start.elapsed(),
+        }
+    }
+
+    ///Llama a 'callback' con el progreso cada 'interval' hasta que 'stop' reciba un mensaje o se cierre, y una
+    /// última vez al terminar.
+    pub fn report_progress(
+        &self,
+        interval: Duration,
+        stop: Receiver<()>,
+        mut callback: impl FnMut(ProgressSnapshot),
+    ) {
+        while let Err(RecvTimeoutError::Timeout) = stop.recv_timeout(interval) {
+            callback(self.progress());
+        }
+        callback(self.progress());
+    }
+
+    ///Devuelve los totales y los tiempos por etapa de cada hilo en milisegundos, o None si no se midieron.
+    /// Los hilos sin actividad se omiten; el último corresponde a los hilos externos al pool.
+    pub fn stats_json(&self) -> Option<Value> {
+        let threads = self.threads.as_ref()?;
+        let progress = self.progress();
+
+        let thread_stats: Vec<Value> = threads
+            .iter()
+            .enumerate()
+            .filter(|(_, stats)| {
+                stats.rows.load(Ordering::Relaxed) > 0
+                    || stats.parse_nanos.load(Ordering::Relaxed) > 0
+                    || stats.reduce_nanos.load(Ordering::Relaxed) > 0
+            })
+            .map(|(index, stats)| {
+                let thread = if index == threads.len() - 1 {
+                    json!("external")
+                } else {
+                    json!(index)
+                };
+                json!({
+                    "thread": thread,
+                    "rows": stats.rows.load(Ordering::Relaxed),
+                    "parse_ms": millis(&stats.parse_nanos),
+                    "map_ms": millis(&stats.map_nanos),
+                    "reduce_ms": millis(&stats.reduce_nanos),
+                })
+            })
+            .collect();
+
+        Some(json!({
+            "rows": progress.rows,
+            "bytes": progress.bytes,
+            "files": progress.files_completed,
+            "rows_per_sec": progress.rows_per_sec(),
+            "total_ms": progress.elapsed.as_secs_f64() * 1000.0,
+            "serialization_ms": millis(&self.serialization_nanos),
+            "threads": thread_stats,
+        }))
+    }
+}
+
+fn millis(nanos: &AtomicU64) -> f64 {
+    nanos.load(Ordering::Relaxed) as f64 / 1_000_000.0
+}
+
+///Lector que registra en el monitor los bytes que se leen a través de él.
+pub struct CountingReader<'a, R> {
+    inner: R,
+    monitor: &'a Monitor,
+}
+
+impl<'a, R: Read> CountingReader<'a, R> {
+    ///Crea un lector que cuenta los bytes leídos de 'inner'.
+    pub fn new(inner: R, monitor: &'a Monitor) -> CountingReader<'a, R> {
+        CountingReader { inner, monitor }
+    }
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.monitor.add_bytes(read as u64);
+        Ok(read)
+    }
+}
@@ -1,41 +1,99 @@
 //! Módulo para el manejo del resultado final del analisis realizado de las reviews.
+use crate::error_summary::FileErrorReport;
+use crate::output_writer::OutputFormat;
 use crate::review_error::ReviewError;
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Serialize)]
 ///Estructura para almacenar las reviews procesadas, según los juegos e idiomas más popuares.
+/// El padrón se incluye si la configuración lo tiene, como ocurre por defecto, y si hubo filas o archivos que no
+/// pudieron procesarse se incluye el detalle por archivo en 'errors'. En el modo de agregación aproximado se
+/// incluyen las cotas de error de las estimaciones en 'approximation', y si se pidieron estadísticas de texto o un
+/// agrupamiento, se incluyen en 'text_stats' y 'groups'.
 pub struct OutputData {
-    padron: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    padron: Option<u32>,
     top_games: Vec<Value>,
     top_languages: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    approximation: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_stats: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    errors: BTreeMap<String, FileErrorReport>,
 }
 
 impl OutputData {
     ///Crea una nueva instancia de OutputData
-    pub fn new(top_games: Vec<Value>, top_languages: Vec<Value>) -> OutputData {
+    pub fn new(
+        padron: Option<u32>,
+        top_games: Vec<Value>,
+        top_languages: Vec<Value>,
+        approximation: Option<Value>,
+        text_stats: Option<Value>,
+        groups: Option<Vec<Value>>,
+        errors: BTreeMap<String, FileErrorReport>,
+    ) -> OutputData {
         OutputData {
-            padron: 109454,
+            padron,
             top_games,
             top_languages,
+            approximation,
+            text_stats,
+            groups,
+            errors,
         }
     }
 
-    ///Almacena la informacion procesada que se encuentra en la estructura en un archivo .json.
+    ///Devuelve el padrón incluido en la salida, si fue indicado.
+    pub fn padron(&self) -> Option<u32> {
+        self.padron
+    }
+
+    ///Devuelve los juegos más reseñados, ordenados de mayor a menor.
+    pub fn top_games(&self) -> &[Value] {
+        &self.top_games
+    }
+
+    ///Devuelve los idiomas más utilizados, ordenados de mayor a menor.
+    pub fn top_languages(&self) -> &[Value] {
+        &self.top_languages
+    }
+
+    ///Devuelve las cotas de error de las estimaciones, si se usó el modo de agregación aproximado.
+    pub fn approximation(&self) -> Option<&Value> {
+        self.approximation.as_ref()
+    }
+
+    ///Devuelve las estadísticas de texto de los juegos e idiomas incluidos, si se pidieron.
+    pub fn text_stats(&self) -> Option<&Value> {
+        self.text_stats.as_ref()
+    }
+
+    ///Devuelve los grupos del agrupamiento, ordenados por clave, si se pidió.
+    pub fn groups(&self) -> Option<&[Value]> {
+        self.groups.as_deref()
+    }
+
+    ///Devuelve el detalle de errores de lectura por archivo.
+    pub fn errors(&self) -> &BTreeMap<String, FileErrorReport> {
+        &self.errors
+    }
+
+    ///Almacena la informacion procesada que se encuentra en la estructura en 'output_path', con el formato
+    /// indicado o, si no se indica, el que corresponda a la extensión del archivo. Devuelve las rutas de los
+    /// archivos escritos.
     ///Retorna error si falla la serialización o la escritura del archivo.
-    pub fn save_output_as_json(self, output_path: String) -> Result<(), ReviewError> {
-        match serde_json::to_string_pretty(&self) {
-            Ok(json) => match std::fs::write(output_path, json) {
-                Ok(_) => Ok(()),
-                Err(e) => Err(ReviewError::OutputWriteError(format!(
-                    "Falló al escribir el archivo json de salida '{}'",
-                    e
-                ))),
-            },
-            Err(e) => Err(ReviewError::OutputWriteError(format!(
-                "Falló la serializacion del archivo json de salida: {}",
-                e
-            ))),
-        }
+    pub fn save_output(
+        &self,
+        output_path: &str,
+        format: Option<OutputFormat>,
+    ) -> Result<Vec<String>, ReviewError> {
+        let format = format.unwrap_or_else(|| OutputFormat::from_path(output_path));
+        format.writer().write(self, output_path)
     }
 }
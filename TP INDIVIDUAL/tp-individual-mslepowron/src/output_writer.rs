@@ -0,0 +1,653 @@
+//! Módulo con los distintos formatos en los que se puede escribir el resultado del análisis de las reseñas.
+//! Cada formato implementa el trait OutputWriter, y el formato a utilizar se elige por flag o según la
+//! extensión del archivo de salida.
+use crate::output_data::OutputData;
+use crate::review_error::ReviewError;
+use csv::Writer;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+///Formatos de salida disponibles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Ndjson,
+    Csv,
+    Markdown,
+}
+
+impl OutputFormat {
+    ///Interpreta el nombre de un formato recibido por línea de comandos.
+    pub fn parse(value: &str) -> Result<OutputFormat, ReviewError> {
+        match value {
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Formato de salida desconocido '{}'. Valores posibles: json, ndjson, csv, markdown",
+                value
+            ))),
+        }
+    }
+
+    ///Deduce el formato a partir de la extensión del archivo de salida. Si la extensión no es conocida se
+    /// utiliza json.
+    pub fn from_path(output_path: &str) -> OutputFormat {
+        let extension = Path::new(output_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("ndjson") | Some("jsonl") => OutputFormat::Ndjson,
+            Some("csv") => OutputFormat::Csv,
+            Some("md") | Some("markdown") => OutputFormat::Markdown,
+            _ => OutputFormat::Json,
+        }
+    }
+
+    ///Devuelve el escritor correspondiente al formato.
+    pub fn writer(&self) -> Box<dyn OutputWriter> {
+        match self {
+            OutputFormat::Json => Box::new(JsonWriter),
+            OutputFormat::Ndjson => Box::new(NdjsonWriter),
+            OutputFormat::Csv => Box::new(CsvWriter),
+            OutputFormat::Markdown => Box::new(MarkdownWriter),
+        }
+    }
+}
+
+///Trait que deben implementar los escritores de la salida del programa.
+pub trait OutputWriter {
+    ///Escribe el resultado en 'output_path' y devuelve las rutas de los archivos escritos, que pueden ser
+    /// distintas de 'output_path' si el formato escribe más de un archivo.
+    ///Retorna error si falla la serialización o la escritura.
+    fn write(&self, output: &OutputData, output_path: &str) -> Result<Vec<String>, ReviewError>;
+}
+
+///Escribe el resultado como un único documento json indentado.
+pub struct JsonWriter;
+
+impl OutputWriter for JsonWriter {
+    fn write(&self, output: &OutputData, output_path: &str) -> Result<Vec<String>, ReviewError> {
+        let json = serde_json::to_string_pretty(output).map_err(|e| {
+            ReviewError::OutputWriteError(format!(
+                "Falló la serializacion del archivo json de salida: {}",
+                e
+            ))
+        })?;
+        std::fs::write(output_path, json).map_err(|e| write_error(output_path, e))?;
+        Ok(vec![output_path.to_string()])
+    }
+}
+
+///Escribe el resultado como json por líneas: un objeto por juego, idioma, estadísticas de texto de un juego o
+/// idioma, grupo del agrupamiento y archivo con errores, con un campo 'type' que indica de cuál se trata.
+pub struct NdjsonWriter;
+
+impl OutputWriter for NdjsonWriter {
+    fn write(&self, output: &OutputData, output_path: &str) -> Result<Vec<String>, ReviewError> {
+        let file = File::create(output_path).map_err(|e| write_error(output_path, e))?;
+        let mut writer = BufWriter::new(file);
+
+        let mut lines = Vec::new();
+        if output.padron().is_some() || output.approximation().is_some() {
+            let mut meta = json!({ "type": "meta" });
+            if let Some(padron) = output.padron() {
+                meta["padron"] = json!(padron);
+            }
+            if let Some(approximation) = output.approximation() {
+                meta["approximation"] = approximation.clone();
+            }
+            lines.push(meta);
+        }
+        for game in output.top_games() {
+            lines.push(tagged("game", game));
+        }
+        for language in output.top_languages() {
+            lines.push(tagged("language", language));
+        }
+        if let Some(text_stats) = output.text_stats() {
+            for game in as_slice(&text_stats["games"]) {
+                lines.push(tagged("game_text", game));
+            }
+            for language in as_slice(&text_stats["languages"]) {
+                lines.push(tagged("language_text", language));
+            }
+        }
+        for group in output.groups().unwrap_or_default() {
+            lines.push(tagged("group", group));
+        }
+        for (file_name, report) in output.errors() {
+            let mut line = json!({ "type": "file_errors", "file": file_name });
+            merge_fields(&mut line, &json!(report));
+            lines.push(line);
+        }
+
+        for line in lines {
+            writeln!(writer, "{}", line).map_err(|e| write_error(output_path, e))?;
+        }
+        writer.flush().map_err(|e| write_error(output_path, e))?;
+        Ok(vec![output_path.to_string()])
+    }
+}
+
+///Escribe el resultado como tablas csv planas, una por archivo, nombradas a partir del archivo de salida:
+/// `<nombre>_games.csv`, `<nombre>_game_languages.csv`, `<nombre>_languages.csv`, `<nombre>_top_reviews.csv` y,
+/// si hubo filas inválidas, `<nombre>_errors.csv`. En el modo aproximado la tabla de juegos incluye el error de la
+/// cuenta y los autores distintos estimados. Si se pidieron estadísticas de texto se agregan
+/// `<nombre>_text_stats.csv` y `<nombre>_top_words.csv`, y si se pidió un agrupamiento con algún grupo,
+/// `<nombre>_groups.csv`, con una columna por campo clave y por agregación.
+/// No se escribe ningún archivo en la ruta de salida en sí, por eso se devuelven las rutas de las tablas.
+pub struct CsvWriter;
+
+impl OutputWriter for CsvWriter {
+    fn write(&self, output: &OutputData, output_path: &str) -> Result<Vec<String>, ReviewError> {
+        let mut written = Vec::new();
+        let approximate = output.approximation().is_some();
+        let games: Vec<Vec<String>> = output
+            .top_games()
+            .iter()
+            .map(|game| {
+                let mut row = vec![field(&game["game"]), field(&game["review_count"])];
+                row.extend(sentiment_fields(&game["sentiment"]));
+                if approximate {
+                    row.extend(APPROXIMATE_GAME_HEADER.iter().map(|key| field(&game[*key])));
+                }
+                row
+            })
+            .collect();
+        let mut games_header = GAMES_HEADER.to_vec();
+        if approximate {
+            games_header.extend(APPROXIMATE_GAME_HEADER);
+        }
+        written.push(write_table(
+            &table_path(output_path, "games"),
+            &games_header,
+            games,
+        )?);
+
+        let mut game_languages = Vec::new();
+        for game in output.top_games() {
+            for language in as_slice(&game["languages"]) {
+                let mut row = vec![
+                    field(&game["game"]),
+                    field(&language["language"]),
+                    field(&language["review_count"]),
+                    field(&language["top_review"]),
+                    field(&language["top_review_votes"]),
+                ];
+                row.extend(sentiment_fields(&language["sentiment"]));
+                game_languages.push(row);
+            }
+        }
+        written.push(write_table(
+            &table_path(output_path, "game_languages"),
+            &GAME_LANGUAGES_HEADER,
+            game_languages,
+        )?);
+
+        let languages: Vec<Vec<String>> = output
+            .top_languages()
+            .iter()
+            .map(|language| {
+                let mut row = vec![
+                    field(&language["language"]),
+                    field(&language["review_count"]),
+                ];
+                row.extend(sentiment_fields(&language["sentiment"]));
+                row
+            })
+            .collect();
+        written.push(write_table(
+            &table_path(output_path, "languages"),
+            &LANGUAGES_HEADER,
+            languages,
+        )?);
+
+        let mut top_reviews = Vec::new();
+        for language in output.top_languages() {
+            for (rank, review) in as_slice(&language["top_reviews"]).iter().enumerate() {
+                top_reviews.push(vec![
+                    field(&language["language"]),
+                    (rank + 1).to_string(),
+                    field(&review["review"]),
+                    field(&review["votes"]),
+                ]);
+            }
+        }
+        written.push(write_table(
+            &table_path(output_path, "top_reviews"),
+            &TOP_REVIEWS_HEADER,
+            top_reviews,
+        )?);
+
+        let mut histogram = Vec::new();
+        for game in output.top_games() {
+            histogram.extend(histogram_rows(
+                &game["game"],
+                &Value::Null,
+                &game["histogram"],
+            ));
+            for language in as_slice(&game["languages"]) {
+                histogram.extend(histogram_rows(
+                    &game["game"],
+                    &language["language"],
+                    &language["histogram"],
+                ));
+            }
+        }
+        for language in output.top_languages() {
+            histogram.extend(histogram_rows(
+                &Value::Null,
+                &language["language"],
+                &language["histogram"],
+            ));
+        }
+        if !histogram.is_empty() {
+            written.push(write_table(
+                &table_path(output_path, "histogram"),
+                &HISTOGRAM_HEADER,
+                histogram,
+            )?);
+        }
+
+        if let Some(text_stats) = output.text_stats() {
+            let mut stats = Vec::new();
+            let mut top_words = Vec::new();
+            for game in as_slice(&text_stats["games"]) {
+                stats.push(text_stats_row(&game["game"], &Value::Null, game));
+            }
+            for language in as_slice(&text_stats["languages"]) {
+                stats.push(text_stats_row(
+                    &Value::Null,
+                    &language["language"],
+                    language,
+                ));
+                for (rank, word) in as_slice(&language["top_words"]).iter().enumerate() {
+                    top_words.push(vec![
+                        field(&language["language"]),
+                        (rank + 1).to_string(),
+                        field(&word["word"]),
+                        field(&word["count"]),
+                    ]);
+                }
+            }
+            written.push(write_table(
+                &table_path(output_path, "text_stats"),
+                &TEXT_STATS_HEADER,
+                stats,
+            )?);
+            written.push(write_table(
+                &table_path(output_path, "top_words"),
+                &TOP_WORDS_HEADER,
+                top_words,
+            )?);
+        }
+
+        if let Some((header, rows)) = group_table(output.groups().unwrap_or_default()) {
+            let header: Vec<&str> = header.iter().map(String::as_str).collect();
+            written.push(write_table(
+                &table_path(output_path, "groups"),
+                &header,
+                rows,
+            )?);
+        }
+
+        if !output.errors().is_empty() {
+            let mut errors = Vec::new();
+            for (file_name, report) in output.errors() {
+                if let Some(open_error) = &report.open_error {
+                    errors.push(vec![
+                        file_name.clone(),
+                        String::new(),
+                        String::new(),
+                        open_error.clone(),
+                    ]);
+                }
+                for row in &report.rows {
+                    errors.push(vec![
+                        file_name.clone(),
+                        row.line.map_or(String::new(), |line| line.to_string()),
+                        row.byte.map_or(String::new(), |byte| byte.to_string()),
+                        row.error.clone(),
+                    ]);
+                }
+            }
+            written.push(write_table(
+                &table_path(output_path, "errors"),
+                &ERRORS_HEADER,
+                errors,
+            )?);
+        }
+
+        Ok(written)
+    }
+}
+
+///Escribe el resultado como un reporte legible en Markdown.
+pub struct MarkdownWriter;
+
+impl OutputWriter for MarkdownWriter {
+    fn write(&self, output: &OutputData, output_path: &str) -> Result<Vec<String>, ReviewError> {
+        let mut report = String::new();
+        // Escribir en un String no puede fallar, por eso se ignoran los resultados de writeln!.
+        let _ = writeln!(report, "# Análisis de reseñas de Steam\n");
+        if let Some(padron) = output.padron() {
+            let _ = writeln!(report, "Padrón: {}\n", padron);
+        }
+
+        if let Some(approximation) = output.approximation() {
+            let _ = writeln!(
+                report,
+                "> Modo aproximado: las cuentas de reseñas pueden sobreestimar hasta {} reseñas y los autores \
+                 distintos tienen un error relativo de ±{:.1}%.\n",
+                field(&approximation["max_review_count_error"]),
+                approximation["distinct_authors_relative_error"]
+                    .as_f64()
+                    .unwrap_or(0.0)
+                    * 100.0
+            );
+        }
+
+        let _ = writeln!(report, "## Juegos más reseñados\n");
+        for (position, game) in output.top_games().iter().enumerate() {
+            let _ = writeln!(
+                report,
+                "### {}. {} ({} reseñas{})\n",
+                position + 1,
+                text(&game["game"]),
+                field(&game["review_count"]),
+                ratio_suffix(&game["sentiment"])
+            );
+            if let Some(periods) = game["histogram"].as_object() {
+                let periods: Vec<String> = periods
+                    .iter()
+                    .map(|(period, count)| format!("{}: {}", period, count))
+                    .collect();
+                let _ = writeln!(report, "Reseñas por período: {}\n", periods.join(", "));
+            }
+            let _ = writeln!(report, "| Idioma | Reseñas | Reseña más votada | Votos |");
+            let _ = writeln!(report, "|---|---|---|---|");
+            for language in as_slice(&game["languages"]) {
+                let _ = writeln!(
+                    report,
+                    "| {} | {} | {} | {} |",
+                    text(&language["language"]),
+                    field(&language["review_count"]),
+                    table_cell(&language["top_review"]),
+                    field(&language["top_review_votes"])
+                );
+            }
+            let _ = writeln!(report);
+        }
+
+        let _ = writeln!(report, "## Idiomas más utilizados\n");
+        for (position, language) in output.top_languages().iter().enumerate() {
+            let _ = writeln!(
+                report,
+                "### {}. {} ({} reseñas{})\n",
+                position + 1,
+                text(&language["language"]),
+                field(&language["review_count"]),
+                ratio_suffix(&language["sentiment"])
+            );
+            for review in as_slice(&language["top_reviews"]) {
+                let _ = writeln!(
+                    report,
+                    "- **{} votos**: {}",
+                    field(&review["votes"]),
+                    table_cell(&review["review"])
+                );
+            }
+            let _ = writeln!(report);
+        }
+
+        if let Some(text_stats) = output.text_stats() {
+            let _ = writeln!(report, "## Estadísticas de texto\n");
+            let _ = writeln!(
+                report,
+                "| Juego o idioma | Reseñas | Largo promedio | Con emojis | Arte ASCII |"
+            );
+            let _ = writeln!(report, "|---|---|---|---|---|");
+            let games = as_slice(&text_stats["games"])
+                .iter()
+                .map(|game| (&game["game"], game));
+            let languages = as_slice(&text_stats["languages"])
+                .iter()
+                .map(|language| (&language["language"], language));
+            for (name, entry) in games.chain(languages) {
+                let _ = writeln!(
+                    report,
+                    "| {} | {} | {:.1} | {} | {} |",
+                    text(name),
+                    field(&entry["review_count"]),
+                    entry["average_length"].as_f64().unwrap_or(0.0),
+                    field(&entry["emoji_reviews"]),
+                    field(&entry["ascii_art_reviews"])
+                );
+            }
+            let _ = writeln!(report);
+            for language in as_slice(&text_stats["languages"]) {
+                let words: Vec<String> = as_slice(&language["top_words"])
+                    .iter()
+                    .map(|word| format!("{} ({})", text(&word["word"]), field(&word["count"])))
+                    .collect();
+                let _ = writeln!(
+                    report,
+                    "- Palabras más frecuentes en {}: {}",
+                    text(&language["language"]),
+                    words.join(", ")
+                );
+            }
+            let _ = writeln!(report);
+        }
+
+        if let Some(groups) = output.groups() {
+            let _ = writeln!(report, "## Agrupamiento\n");
+            match group_table(groups) {
+                Some((header, rows)) => {
+                    let _ = writeln!(report, "| {} |", header.join(" | "));
+                    let _ = writeln!(report, "|{}", "---|".repeat(header.len()));
+                    for row in rows {
+                        let cells: Vec<String> = row
+                            .iter()
+                            .map(|cell| escape_cell(&cell.replace(['\r', '\n'], " ")))
+                            .collect();
+                        let _ = writeln!(report, "| {} |", cells.join(" | "));
+                    }
+                }
+                None => {
+                    let _ = writeln!(report, "No hay reseñas en ningún grupo.");
+                }
+            }
+            let _ = writeln!(report);
+        }
+
+        if !output.errors().is_empty() {
+            let _ = writeln!(report, "## Errores de lectura\n");
+            let _ = writeln!(report, "| Archivo | Filas inválidas |");
+            let _ = writeln!(report, "|---|---|");
+            for (file_name, file_errors) in output.errors() {
+                let _ = writeln!(
+                    report,
+                    "| {} | {} |",
+                    escape_cell(file_name),
+                    file_errors.bad_rows
+                );
+            }
+        }
+
+        std::fs::write(output_path, report).map_err(|e| write_error(output_path, e))?;
+        Ok(vec![output_path.to_string()])
+    }
+}
+
+const SENTIMENT_HEADER: [&str; 3] = ["recommended", "not_recommended", "recommended_ratio"];
+const GAMES_HEADER: [&str; 5] = [
+    "game",
+    "review_count",
+    "recommended",
+    "not_recommended",
+    "recommended_ratio",
+];
+const APPROXIMATE_GAME_HEADER: [&str; 2] = ["review_count_error", "distinct_authors"];
+const GAME_LANGUAGES_HEADER: [&str; 8] = [
+    "game",
+    "language",
+    "review_count",
+    "top_review",
+    "top_review_votes",
+    "recommended",
+    "not_recommended",
+    "recommended_ratio",
+];
+const LANGUAGES_HEADER: [&str; 5] = [
+    "language",
+    "review_count",
+    "recommended",
+    "not_recommended",
+    "recommended_ratio",
+];
+const TOP_REVIEWS_HEADER: [&str; 4] = ["language", "rank", "review", "votes"];
+const ERRORS_HEADER: [&str; 4] = ["file", "line", "byte", "error"];
+const HISTOGRAM_HEADER: [&str; 4] = ["game", "language", "period", "review_count"];
+const TEXT_STATS_HEADER: [&str; 6] = [
+    "game",
+    "language",
+    "review_count",
+    "average_length",
+    "emoji_reviews",
+    "ascii_art_reviews",
+];
+const TOP_WORDS_HEADER: [&str; 4] = ["language", "rank", "word", "count"];
+
+fn write_error(output_path: &str, e: impl std::fmt::Display) -> ReviewError {
+    ReviewError::OutputWriteError(format!(
+        "Falló al escribir el archivo de salida '{}': {}",
+        output_path, e
+    ))
+}
+
+fn tagged(kind: &str, value: &Value) -> Value {
+    let mut line = json!({ "type": kind });
+    merge_fields(&mut line, value);
+    line
+}
+
+fn merge_fields(target: &mut Value, source: &Value) {
+    if let (Some(target), Some(source)) = (target.as_object_mut(), source.as_object()) {
+        for (key, value) in source {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+fn table_path(output_path: &str, table: &str) -> String {
+    let path = Path::new(output_path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("output");
+    path.with_file_name(format!("{}_{}.csv", stem, table))
+        .display()
+        .to_string()
+}
+
+///Escribe una tabla csv en 'path' y devuelve su ruta.
+fn write_table(path: &str, header: &[&str], rows: Vec<Vec<String>>) -> Result<String, ReviewError> {
+    let mut writer = Writer::from_path(path).map_err(|e| write_error(path, e))?;
+    writer
+        .write_record(header)
+        .map_err(|e| write_error(path, e))?;
+    for row in rows {
+        writer.write_record(row).map_err(|e| write_error(path, e))?;
+    }
+    writer.flush().map_err(|e| write_error(path, e))?;
+    Ok(path.to_string())
+}
+
+fn as_slice(value: &Value) -> &[Value] {
+    value.as_array().map_or(&[], |values| values.as_slice())
+}
+
+///Devuelve el valor como texto plano para una celda, sin comillas en el caso de los strings.
+fn field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn text(value: &Value) -> String {
+    escape_cell(&field(value))
+}
+
+///Devuelve una fila por período del histograma. Las reseñas de un juego tienen el idioma vacío y las de un idioma
+/// sin juego tienen el juego vacío.
+fn histogram_rows(game: &Value, language: &Value, histogram: &Value) -> Vec<Vec<String>> {
+    histogram.as_object().map_or(Vec::new(), |periods| {
+        periods
+            .iter()
+            .map(|(period, count)| vec![field(game), field(language), period.clone(), field(count)])
+            .collect()
+    })
+}
+
+///Devuelve la fila de estadísticas de texto de un juego, con el idioma vacío, o de un idioma, con el juego vacío.
+fn text_stats_row(game: &Value, language: &Value, stats: &Value) -> Vec<String> {
+    let mut row = vec![field(game), field(language)];
+    row.extend(TEXT_STATS_HEADER[2..].iter().map(|key| field(&stats[*key])));
+    row
+}
+
+///Devuelve el encabezado y las filas de la tabla de grupos: una columna por campo clave seguida de una por
+/// agregación. Las agregaciones TopK se escriben como json en una sola celda. Devuelve None si no hay grupos.
+fn group_table(groups: &[Value]) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    let first = groups.first()?.as_object()?;
+    let keys: Vec<String> = first["key"]
+        .as_object()
+        .map_or(Vec::new(), |key| key.keys().cloned().collect());
+    let aggregates: Vec<String> = first
+        .keys()
+        .filter(|name| *name != "key")
+        .cloned()
+        .collect();
+
+    let rows = groups
+        .iter()
+        .map(|group| {
+            let key_cells = keys.iter().map(|name| field(&group["key"][name]));
+            let aggregate_cells = aggregates.iter().map(|name| field(&group[name]));
+            key_cells.chain(aggregate_cells).collect()
+        })
+        .collect();
+    Some((keys.into_iter().chain(aggregates).collect(), rows))
+}
+
+fn sentiment_fields(sentiment: &Value) -> Vec<String> {
+    SENTIMENT_HEADER
+        .iter()
+        .map(|key| field(&sentiment[*key]))
+        .collect()
+}
+
+fn ratio_suffix(sentiment: &Value) -> String {
+    match sentiment["recommended_ratio"].as_f64() {
+        Some(ratio) => format!(", {:.1}% recomendado", ratio * 100.0),
+        None => String::new(),
+    }
+}
+
+fn table_cell(value: &Value) -> String {
+    escape_cell(&field(value).replace(['\r', '\n'], " "))
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
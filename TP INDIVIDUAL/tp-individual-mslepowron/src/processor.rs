@@ -1,51 +1,418 @@
 //! Modulo que se encarga del procesaminto de la informacion comprendida en el/los csv, realizando este
-//! proceso de manera concurrente, siguiendo un modelo fork-join
+//! proceso de manera concurrente, siguiendo un modelo fork-join.
+//! Cada hilo acumula las reseñas que lee en un ReviewResult parcial (fold) y luego los parciales se
+//! unifican entre sí (reduce), por lo que no se reserva memoria nueva por cada fila del csv.
+use crate::chunking::{open_range, plan_chunks, ChunkPlan};
+use crate::config::{Config, ErrorMode};
+use crate::input::{collect_inputs, decompress, InputSource};
+use crate::monitor::{CountingReader, Monitor, Stage};
+use crate::output_data::OutputData;
+use crate::query_service::QueryService;
 use crate::review_error::ReviewError;
 use crate::review_record::ReviewRecord;
 use crate::review_result::ReviewResult;
-use csv::Reader;
+use crate::snapshot::{FileFingerprint, Snapshot};
+use csv::{Reader, ReaderBuilder};
 use rayon::prelude::*;
-use std::fs::{read_dir, File};
-use std::path::PathBuf;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+///Cantidad de filas que se leen de una fuente o de un rango antes de informarlas en el progreso, para no actualizar
+/// el contador compartido por cada fila.
+const ROWS_PER_PROGRESS_UPDATE: u64 = 4096;
 
 /// Procesa multiples archivos CSV con resenas que siguen el formato de steam_reviews.csv del dataset de kaggle: https://www.kaggle.com/datasets/najzeko/steam-reviews-2021
-/// Guarda los resultados consolidados de las renas analizadas en un archivo JSON de salida.
-/// Devuelve Error en caso de que no se pueda leer el directorio provisto o halla algun error al leer los files del directorio.
-pub fn fork_join(threads: usize, dir_path: String, output_path: String) -> Result<(), ReviewError> {
-    rayon::ThreadPoolBuilder::new()
+/// La entrada puede ser un directorio (que se recorre recursivamente), un archivo o `-` para leer de la entrada
+/// estándar, y los archivos pueden estar comprimidos con gzip o zstd.
+/// Guarda los resultados consolidados de las renas analizadas en un archivo de salida, con la forma del reporte y
+/// el formato definidos en 'config'.
+/// El procesamiento se realiza en un pool propio de 'threads' hilos que se libera al terminar, por lo que puede
+/// llamarse varias veces en un mismo proceso.
+/// Si la configuración lo indica, se informa el progreso por stderr periódicamente, se escriben los tiempos de
+/// cada etapa por hilo y, luego de escribir la salida, se atienden consultas sobre el resultado hasta que se
+/// interrumpa el proceso.
+/// Devuelve las rutas de los archivos de salida escritos, que con el formato csv son una tabla por archivo.
+/// Devuelve Error en caso de que no exista la ruta de entrada o no se pueda leer el directorio provisto, o en modo estricto si algún archivo no
+/// puede abrirse o tiene filas inválidas.
+pub fn fork_join(
+    threads: usize,
+    input_path: String,
+    output_path: String,
+    config: &Config,
+) -> Result<Vec<String>, ReviewError> {
+    let pool = build_pool(threads)?;
+    let monitor = Monitor::new(pool.current_num_threads(), config.stats_path.is_some());
+
+    let result = thread::scope(|scope| {
+        let (stop, stopped) = mpsc::channel::<()>();
+        if let Some(interval) = config.progress_interval {
+            let monitor = &monitor;
+            scope.spawn(move || {
+                monitor.report_progress(interval, stopped, |progress| eprintln!("{}", progress))
+            });
+        }
+        let result = aggregate(&pool, &input_path, config, &monitor);
+        drop(stop);
+        result
+    })?;
+
+    let serialization_start = Instant::now();
+    let output_data = result.get_top_results(config);
+    let written = output_data.save_output(&output_path, config.output_format)?;
+    monitor.record_serialization(serialization_start.elapsed());
+
+    if let Some(stats_path) = &config.stats_path {
+        write_stats(&monitor, stats_path)?;
+    }
+
+    if let Some(address) = &config.serve_address {
+        drop(pool);
+        QueryService::new(result, config.clone()).serve(address)?;
+    }
+
+    Ok(written)
+}
+
+/// Procesa las reseñas de 'input_path' en un pool propio de 'threads' hilos y devuelve el reporte en memoria,
+/// sin escribir ningún archivo de salida.
+/// Devuelve error si no puede crearse el pool o si falla el procesamiento.
+pub fn process_with_threads(
+    threads: usize,
+    input_path: &str,
+    config: &Config,
+) -> Result<OutputData, ReviewError> {
+    let pool = build_pool(threads)?;
+    process(&pool, input_path, config)
+}
+
+/// Procesa las reseñas de 'input_path' usando los hilos del pool recibido y devuelve el reporte en memoria.
+/// Permite reutilizar un mismo pool en varias ejecuciones o compartirlo con el resto de una aplicación.
+/// Devuelve Error en caso de que no exista la ruta de entrada o no se pueda leer el directorio provisto, o en modo
+/// estricto si algún archivo no puede abrirse o tiene filas inválidas.
+pub fn process(
+    pool: &ThreadPool,
+    input_path: &str,
+    config: &Config,
+) -> Result<OutputData, ReviewError> {
+    let monitor = Monitor::new(pool.current_num_threads(), false);
+    process_monitored(pool, input_path, config, &monitor)
+}
+
+/// Igual que 'process', pero registrando en 'monitor' el progreso y, si el monitor lo indica, el tiempo de cada
+/// etapa por hilo. Otro hilo puede consultar el progreso del monitor mientras se procesa.
+pub fn process_monitored(
+    pool: &ThreadPool,
+    input_path: &str,
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<OutputData, ReviewError> {
+    let result = aggregate(pool, input_path, config, monitor)?;
+    Ok(result.get_top_results(config))
+}
+
+/// Igual que 'process_monitored', pero devuelve el resultado agregado en lugar del reporte, para poder armar
+/// distintos reportes o consultarlo sin volver a procesar las reseñas (por ejemplo, con un QueryService).
+pub fn aggregate(
+    pool: &ThreadPool,
+    input_path: &str,
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<ReviewResult, ReviewError> {
+    let inputs = collect_inputs(input_path, config)?;
+    monitor.set_files_total(inputs.len() as u64);
+
+    pool.install(|| match &config.snapshot_path {
+        Some(snapshot_path) => process_incrementally(inputs, snapshot_path, config, monitor),
+        None => process_inputs(&inputs, config, monitor),
+    })
+}
+
+fn build_pool(threads: usize) -> Result<ThreadPool, ReviewError> {
+    ThreadPoolBuilder::new()
         .num_threads(threads)
-        .build_global()
-        .map_err(|e| ReviewError::ThreadPoolBuildError(e.to_string()))?;
-
-    match read_dir(&dir_path) {
-        Ok(dir_entries) => {
-            let result = dir_entries
-                .flatten()
-                .map(|d| d.path())
-                .collect::<Vec<PathBuf>>()
-                .par_iter()
-                .flat_map(|path| {
-                    let file = File::open(path);
-
-                    let reader = Reader::from_reader(file.unwrap());
-                    reader.into_deserialize::<ReviewRecord>().par_bridge()
-                })
-                .map(|res| match res {
-                    Ok(record) => record.process_review(),
-                    Err(_e) => ReviewResult::new(),
+        .build()
+        .map_err(|e| ReviewError::ThreadPoolBuildError(e.to_string()))
+}
+
+/// Escribe los tiempos medidos en formato json en 'stats_path', o por stderr si es `-`.
+fn write_stats(monitor: &Monitor, stats_path: &str) -> Result<(), ReviewError> {
+    let Some(stats) = monitor.stats_json() else {
+        return Ok(());
+    };
+    let write_error = |e: String| {
+        ReviewError::OutputWriteError(format!(
+            "Falló al escribir las estadísticas en '{}': {}",
+            stats_path, e
+        ))
+    };
+    let content = serde_json::to_string_pretty(&stats).map_err(|e| write_error(e.to_string()))?;
+
+    if stats_path == "-" {
+        writeln!(io::stderr(), "{}", content).map_err(|e| write_error(e.to_string()))
+    } else {
+        std::fs::write(stats_path, content).map_err(|e| write_error(e.to_string()))
+    }
+}
+
+/// Procesa en paralelo las reseñas de todas las fuentes indicadas y unifica sus resultados.
+fn process_inputs(
+    inputs: &[InputSource],
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<ReviewResult, ReviewError> {
+    inputs
+        .par_iter()
+        .map(|input| {
+            let result = process_input(input, config, monitor);
+            monitor.file_completed();
+            result
+        })
+        .try_reduce(ReviewResult::new, |acc, partial| {
+            Ok(monitor.time(Stage::Reduce, || acc.reduce(partial)))
+        })
+}
+
+/// Procesa solo los archivos que no estén registrados en el snapshot con el mismo tamaño y fecha de modificación,
+/// registra el resultado de cada uno en el snapshot y devuelve el resultado de todos los archivos registrados.
+/// Si un archivo ya procesado cambió, su resultado anterior se reemplaza por el nuevo. Los archivos se identifican
+/// por su ruta canónica, por lo que distintas formas de escribir la misma ruta corresponden al mismo archivo.
+/// La entrada estándar y los archivos que no pudieron abrirse no se registran: se procesan en cada ejecución y su
+/// resultado solo se incluye en el de esa ejecución. Los archivos registrados que ya no forman parte de la entrada
+/// se descartan del snapshot.
+fn process_incrementally(
+    inputs: Vec<InputSource>,
+    snapshot_path: &str,
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<ReviewResult, ReviewError> {
+    let mut snapshot = Snapshot::load_or_new(snapshot_path, config)?;
+
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    for input in inputs {
+        let key = input.snapshot_key();
+        if let Some(key) = &key {
+            seen.insert(key.clone());
+        }
+        let fingerprint = input.path().and_then(FileFingerprint::of);
+        if key
+            .as_ref()
+            .is_some_and(|key| snapshot.contains(key, fingerprint))
+        {
+            continue;
+        }
+        pending.push((input, key, fingerprint));
+    }
+
+    let results = pending
+        .par_iter()
+        .map(|(input, _, _)| {
+            let result = process_input(input, config, monitor);
+            monitor.file_completed();
+            result
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut unrecorded = ReviewResult::new();
+    for ((input, key, fingerprint), result) in pending.into_iter().zip(results) {
+        match (key, fingerprint) {
+            (Some(key), Some(fingerprint)) if !result.open_failed(&input.name()) => {
+                snapshot.record(key, fingerprint, result)
+            }
+            _ => unrecorded = unrecorded.reduce(result),
+        }
+    }
+    snapshot.prune(&seen);
+    snapshot.save(snapshot_path)?;
+
+    Ok(snapshot.into_result().reduce(unrecorded))
+}
+
+/// Procesa las reseñas de una fuente CSV, repartiendo sus filas entre los hilos del pool.
+/// Si la fuente es un archivo sin comprimir más grande que el tamaño de rango configurado, se divide en rangos de
+/// bytes alineados al comienzo de un registro y cada rango se parsea en paralelo; si no, las filas se leen de forma
+/// secuencial y se reparten entre los hilos.
+/// En modo tolerante los errores de apertura y las filas inválidas se registran en el resumen de errores del
+/// resultado; en modo estricto se devuelve el primer error encontrado.
+fn process_input(
+    input: &InputSource,
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<ReviewResult, ReviewError> {
+    let file_name = input.name();
+
+    if let Some(path) = input.path() {
+        match plan_chunks(path, config.chunk_size_bytes) {
+            Ok(Some(plan)) => return process_chunks(path, &file_name, &plan, config, monitor),
+            Ok(None) => {}
+            Err(e) => return open_failed(&file_name, e, config),
+        }
+    }
+
+    let file = match input
+        .open_raw()
+        .and_then(|raw| decompress(Box::new(CountingReader::new(raw, monitor))))
+    {
+        Ok(file) => file,
+        Err(e) => return open_failed(&file_name, e, config),
+    };
+
+    let mut records = Reader::from_reader(file).into_deserialize::<ReviewRecord>();
+    let mut pending_rows = 0;
+    let rows = std::iter::from_fn(move || {
+        let next = monitor.time(Stage::Parse, || records.next());
+        match next {
+            Some(_) => count_row(monitor, &mut pending_rows),
+            None => {
+                monitor.add_rows(pending_rows);
+                pending_rows = 0;
+            }
+        }
+        next
+    });
+
+    rows.par_bridge()
+        .try_fold(ReviewResult::new, |mut acc, res| {
+            match res {
+                Ok(record) => monitor.time(Stage::Map, || record.process_review(&mut acc, config)),
+                Err(e) => {
+                    let line = e.position().map(|pos| pos.line());
+                    let byte = e.position().map(|pos| pos.byte());
+                    bad_row(&mut acc, &file_name, line, byte, e, config)?;
+                }
+            }
+            Ok(acc)
+        })
+        .try_reduce(ReviewResult::new, |acc, partial| {
+            Ok(monitor.time(Stage::Reduce, || acc.reduce(partial)))
+        })
+}
+
+/// Procesa en paralelo cada rango de bytes del archivo y unifica sus resultados.
+/// Las posiciones que informa el lector de cada rango son relativas a su comienzo. Los bytes se desplazan según el
+/// byte en que comienza el rango, pero la línea en que comienza cada rango solo se conoce al terminar de parsear los
+/// anteriores, por lo que cada rango devuelve cuántas líneas tiene y las líneas de sus filas inválidas se desplazan
+/// antes de unificar los resultados. En modo estricto el error se informa con el byte en que comienza la fila.
+fn process_chunks(
+    path: &Path,
+    file_name: &str,
+    plan: &ChunkPlan,
+    config: &Config,
+    monitor: &Monitor,
+) -> Result<ReviewResult, ReviewError> {
+    let partials = plan
+        .ranges
+        .par_iter()
+        .map(|chunk| {
+            let range = match open_range(path, chunk) {
+                Ok(range) => CountingReader::new(range, monitor),
+                Err(e) => return open_failed(file_name, e, config).map(|result| (result, 0)),
+            };
+
+            let mut acc = ReviewResult::new();
+            let mut reader = ReaderBuilder::new().has_headers(false).from_reader(range);
+            let mut records = reader.records();
+            let mut pending_rows = 0;
+            while let Some(deserialized) = monitor.time(Stage::Parse, || {
+                records.next().map(|res| {
+                    res.and_then(|record| record.deserialize::<ReviewRecord>(Some(&plan.headers)))
                 })
-                .reduce(ReviewResult::new, |mut acc, map| {
-                    acc = acc.reduce(&map);
-                    acc
-                });
+            }) {
+                count_row(monitor, &mut pending_rows);
+                match deserialized {
+                    Ok(record) => {
+                        monitor.time(Stage::Map, || record.process_review(&mut acc, config))
+                    }
+                    Err(e) => {
+                        let line = match config.error_mode {
+                            ErrorMode::Strict => None,
+                            ErrorMode::Lenient => e.position().map(|pos| pos.line()),
+                        };
+                        let byte = e.position().map(|pos| chunk.start + pos.byte());
+                        bad_row(&mut acc, file_name, line, byte, e, config)?;
+                    }
+                }
+            }
+            monitor.add_rows(pending_rows);
+            Ok((acc, reader.position().line() - 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-            let output_data = result.get_top_results(3, 3);
-            output_data.save_output_as_json(output_path)?;
+    let mut first_lines = Vec::with_capacity(partials.len());
+    let mut next_line = plan.first_line;
+    for (_, lines) in &partials {
+        first_lines.push(next_line);
+        next_line += lines;
+    }
+
+    Ok(partials
+        .into_par_iter()
+        .zip(first_lines)
+        .map(|((mut partial, _), first_line)| {
+            partial.shift_bad_row_lines(file_name, first_line - 1);
+            partial
+        })
+        .reduce(ReviewResult::new, |acc, partial| {
+            monitor.time(Stage::Reduce, || acc.reduce(partial))
+        }))
+}
 
+/// Suma una fila a las pendientes de informar, y las informa en el progreso al llegar a ROWS_PER_PROGRESS_UPDATE.
+fn count_row(monitor: &Monitor, pending_rows: &mut u64) {
+    *pending_rows += 1;
+    if *pending_rows == ROWS_PER_PROGRESS_UPDATE {
+        monitor.add_rows(*pending_rows);
+        *pending_rows = 0;
+    }
+}
+
+/// Maneja una fuente que no pudo abrirse: en modo estricto devuelve el error y en modo tolerante lo registra en un
+/// resultado vacío.
+fn open_failed(
+    file_name: &str,
+    e: io::Error,
+    config: &Config,
+) -> Result<ReviewResult, ReviewError> {
+    match config.error_mode {
+        ErrorMode::Strict => Err(ReviewError::FileOpenError(format!("{}: {}", file_name, e))),
+        ErrorMode::Lenient => {
+            let mut result = ReviewResult::new();
+            result.add_open_error(file_name, e.to_string());
+            Ok(result)
+        }
+    }
+}
+
+/// Maneja una fila inválida: en modo estricto devuelve el error, indicando la línea o la posición en bytes en la
+/// que comienza la fila, y en modo tolerante la registra en el resultado.
+fn bad_row(
+    acc: &mut ReviewResult,
+    file_name: &str,
+    line: Option<u64>,
+    byte: Option<u64>,
+    e: csv::Error,
+    config: &Config,
+) -> Result<(), ReviewError> {
+    match config.error_mode {
+        ErrorMode::Strict => {
+            let location = match (line, byte) {
+                (Some(line), _) => format!("línea {}", line),
+                (None, Some(byte)) => format!("byte {}", byte),
+                (None, None) => "línea desconocida".to_string(),
+            };
+            Err(ReviewError::CsvReadError(format!(
+                "{} ({}): {}",
+                file_name, location, e
+            )))
+        }
+        ErrorMode::Lenient => {
+            acc.add_bad_row(file_name, line, byte, e.to_string());
             Ok(())
         }
-        Err(_e) => Err(ReviewError::DirectoryNotFound(
-            "Error al leer el directorio.".to_string(),
-        )),
     }
 }
@@ -0,0 +1,294 @@
+//! Módulo con un servicio de consultas sobre el resultado agregado, para consultar los datos de forma interactiva
+//! sin volver a procesar el dataset. El resultado se carga una sola vez y se atienden consultas HTTP GET que
+//! devuelven json, con las mismas funciones que arman el reporte del programa:
+//! - `/`: el reporte completo, como el archivo de salida.
+//! - `/games?top=N&language=X`: los N juegos más reseñados, o con más reseñas en el idioma X si se indica.
+//! - `/languages?top=N&reviews=M`: los N idiomas más utilizados con sus M reseñas más destacadas.
+//! - `/languages?game=Y&top=N`: los N idiomas más utilizados en las reseñas del juego Y.
+//! - `/game?name=Y&languages=N`: los datos del juego Y con sus N idiomas más utilizados.
+//!
+//! Los parámetros que no se indican toman los valores de la configuración con la que se procesó el dataset.
+//! El resultado puede venir de procesar el dataset o de un snapshot guardado por una ejecución anterior.
+use crate::config::Config;
+use crate::review_error::ReviewError;
+use crate::review_result::ReviewResult;
+use crate::snapshot::Snapshot;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+///Largo máximo que se lee de la línea de pedido y de los encabezados de una consulta.
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+///Tiempo máximo de espera al leer una consulta o escribir su respuesta, para que un cliente que no envía la
+/// consulta completa no ocupe un hilo indefinidamente.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+///Cantidad máxima de conexiones que se atienden a la vez. Las demás esperan a ser aceptadas.
+const MAX_CONCURRENT_CONNECTIONS: usize = 16;
+
+///Respuesta a una consulta: el código de estado HTTP y el cuerpo en json.
+#[derive(Debug)]
+pub struct QueryResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl QueryResponse {
+    fn ok(body: Value) -> QueryResponse {
+        QueryResponse { status: 200, body }
+    }
+
+    fn error(status: u16, message: String) -> QueryResponse {
+        QueryResponse {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+///Servicio que responde consultas sobre un resultado agregado.
+/// ###  result
+/// Resultado agregado del dataset, que no se modifica mientras se atienden consultas.
+/// ###  config
+/// Configuración con la que se procesó el dataset, usada como base de cada consulta.
+pub struct QueryService {
+    result: ReviewResult,
+    config: Config,
+}
+
+impl QueryService {
+    ///Crea un servicio que responde consultas sobre 'result', procesado con la configuración indicada.
+    pub fn new(result: ReviewResult, config: Config) -> QueryService {
+        QueryService { result, config }
+    }
+
+    ///Crea un servicio que responde consultas sobre el resultado guardado en el snapshot 'snapshot_path', sin
+    /// procesar ningún archivo.
+    /// Devuelve error si el snapshot no existe, no puede leerse o fue generado con otras opciones de agregación
+    /// que las de 'config'.
+    pub fn from_snapshot(snapshot_path: &str, config: Config) -> Result<QueryService, ReviewError> {
+        let result = Snapshot::load(snapshot_path, &config)?.into_result();
+        Ok(QueryService::new(result, config))
+    }
+
+    ///Responde una consulta a partir de su ruta y parámetros, por ejemplo `/games?top=5&language=spanish`.
+    pub fn query(&self, target: &str) -> QueryResponse {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params = match parse_params(query) {
+            Ok(params) => params,
+            Err(message) => return QueryResponse::error(400, message),
+        };
+        let result = match path {
+            "/" => self.report(&params),
+            "/games" => self.games(&params),
+            "/languages" => self.languages(&params),
+            "/game" => self.game(&params),
+            _ => Err(QueryResponse::error(
+                404,
+                format!("Ruta desconocida '{}'", path),
+            )),
+        };
+        result.unwrap_or_else(|response| response)
+    }
+
+    ///Atiende consultas HTTP en 'address' (por ejemplo `127.0.0.1:8080`) hasta que se interrumpa el proceso.
+    /// Devuelve error si no puede escucharse en esa dirección.
+    pub fn serve(&self, address: &str) -> Result<(), ReviewError> {
+        let listener = TcpListener::bind(address).map_err(|e| {
+            ReviewError::ServiceError(format!(
+                "No se pudo escuchar consultas en '{}': {}",
+                address, e
+            ))
+        })?;
+        self.serve_on(listener);
+        Ok(())
+    }
+
+    ///Atiende consultas HTTP de las conexiones que acepte 'listener' hasta que se interrumpa el proceso.
+    /// Las conexiones se reparten entre MAX_CONCURRENT_CONNECTIONS hilos que aceptan del mismo 'listener', por lo
+    /// que nunca se atienden más conexiones a la vez. Los errores de una conexión, incluido el vencimiento del
+    /// tiempo de espera, se informan por stderr sin afectar a las demás.
+    pub fn serve_on(&self, listener: TcpListener) {
+        if let Ok(address) = listener.local_addr() {
+            eprintln!("Atendiendo consultas en http://{}", address);
+        }
+        thread::scope(|scope| {
+            for _ in 0..MAX_CONCURRENT_CONNECTIONS {
+                let listener = &listener;
+                scope.spawn(move || {
+                    for stream in listener.incoming() {
+                        match stream {
+                            Ok(stream) => {
+                                if let Err(e) = self.handle(stream) {
+                                    eprintln!("Error al atender una consulta: {}", e);
+                                }
+                            }
+                            Err(e) => eprintln!("Error al aceptar una conexión: {}", e),
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    fn handle(&self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some("GET"), Some(target)) => self.query(target),
+            (Some(_), Some(_)) => {
+                QueryResponse::error(405, "Solo se admiten consultas GET".to_string())
+            }
+            _ => QueryResponse::error(400, "Consulta HTTP inválida".to_string()),
+        };
+        write_response(&stream, &response)
+    }
+
+    fn report(&self, params: &HashMap<String, String>) -> Result<QueryResponse, QueryResponse> {
+        check_params(params, &[])?;
+        let output = self.result.get_top_results(&self.config);
+        Ok(QueryResponse::ok(json!(output)))
+    }
+
+    fn games(&self, params: &HashMap<String, String>) -> Result<QueryResponse, QueryResponse> {
+        check_params(params, &["top", "language"])?;
+        let mut config = self.config.clone();
+        config.top_games = amount(params, "top", config.top_games)?;
+        let games = match params.get("language") {
+            Some(language) => self.result.top_games_in_language(language, &config),
+            None => self.result.top_games(&config),
+        };
+        Ok(QueryResponse::ok(json!(games)))
+    }
+
+    fn languages(&self, params: &HashMap<String, String>) -> Result<QueryResponse, QueryResponse> {
+        check_params(params, &["top", "reviews", "game"])?;
+        let mut config = self.config.clone();
+        match params.get("game") {
+            Some(game_name) => {
+                config.languages_per_game = amount(params, "top", config.languages_per_game)?;
+                let game = self.find_game(game_name, &config)?;
+                Ok(QueryResponse::ok(game["languages"].clone()))
+            }
+            None => {
+                config.top_languages = amount(params, "top", config.top_languages)?;
+                config.reviews_per_language =
+                    amount(params, "reviews", config.reviews_per_language)?;
+                Ok(QueryResponse::ok(json!(self.result.top_languages(&config))))
+            }
+        }
+    }
+
+    fn game(&self, params: &HashMap<String, String>) -> Result<QueryResponse, QueryResponse> {
+        check_params(params, &["name", "languages"])?;
+        let mut config = self.config.clone();
+        config.languages_per_game = amount(params, "languages", config.languages_per_game)?;
+        let game_name = params
+            .get("name")
+            .ok_or_else(|| QueryResponse::error(400, "Falta el parámetro 'name'".to_string()))?;
+        Ok(QueryResponse::ok(self.find_game(game_name, &config)?))
+    }
+
+    fn find_game(&self, game_name: &str, config: &Config) -> Result<Value, QueryResponse> {
+        self.result.game(game_name, config).ok_or_else(|| {
+            QueryResponse::error(404, format!("No hay reseñas del juego '{}'", game_name))
+        })
+    }
+}
+
+///Interpreta los parámetros de la consulta, decodificando los caracteres escapados.
+fn parse_params(query: &str) -> Result<HashMap<String, String>, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+///Decodifica un componente de la URL: `+` es un espacio y `%XX` un byte en hexadecimal.
+fn decode(component: &str) -> Result<String, String> {
+    let invalid = || format!("Parámetro mal codificado '{}'", component);
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        match bytes[position] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = component
+                    .get(position + 1..position + 3)
+                    .ok_or_else(invalid)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                position += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        position += 1;
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+fn check_params(params: &HashMap<String, String>, allowed: &[&str]) -> Result<(), QueryResponse> {
+    match params.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(QueryResponse::error(
+            400,
+            format!("Parámetro desconocido '{}'", key),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn amount(
+    params: &HashMap<String, String>,
+    name: &str,
+    default: usize,
+) -> Result<usize, QueryResponse> {
+    match params.get(name) {
+        Some(value) => value.parse().map_err(|_| {
+            QueryResponse::error(
+                400,
+                format!(
+                    "El parámetro '{}' debe ser un número entero no negativo",
+                    name
+                ),
+            )
+        }),
+        None => Ok(default),
+    }
+}
+
+fn write_response(mut stream: &TcpStream, response: &QueryResponse) -> std::io::Result<()> {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -1,42 +1,126 @@
 //! Módulo para almacenar los datos de cada record del csv (cada review) que son necesarios para calcular el output del programa
-use crate::game::Game;
-use crate::language::Language;
+use crate::config::Config;
+use crate::date::date_from_timestamp;
+use crate::filter::{Field, FieldValue, Filterable};
 use crate::review_result::ReviewResult;
+use crate::score::ScoreInputs;
 use crate::top_review::TopReview;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer};
 
 /// Estructura que representa los datos de una review del/los CSV original que se recibe como input.
-/// Se almacenan unicamente los datos mínimos e indispensables para poder procesar las reviews, el resto se descartan.
+/// Se almacenan unicamente los datos mínimos e indispensables para poder procesar y filtrar las reviews, el resto
+/// se descartan. Los campos que solo se usan para filtrar son opcionales: si faltan o no pueden interpretarse la
+/// review se procesa igual y las condiciones sobre ese campo no se cumplen. Lo mismo ocurre con los campos que solo
+/// se usan para puntuar la review, que se toman como 0.
 #[derive(Debug, Deserialize)]
 pub struct ReviewRecord {
     app_name: String,
     language: String,
     review: String,
     votes_helpful: u32,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    votes_funny: Option<u32>,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    weighted_vote_score: Option<f64>,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    timestamp_created: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    recommended: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    steam_purchase: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    received_for_free: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    written_during_early_access: Option<bool>,
+    #[serde(
+        rename = "author.playtime_forever",
+        default,
+        deserialize_with = "csv::invalid_option"
+    )]
+    playtime_forever: Option<f64>,
+    #[serde(
+        rename = "author.steamid",
+        default,
+        deserialize_with = "csv::invalid_option"
+    )]
+    author_steamid: Option<u64>,
 }
 
 impl ReviewRecord {
-    ///Procesa los datos del CSV almacenados en la misma, y transforma la review cruda en una estructura
-    /// ReviewResult para poder calcular las estadísticas necesarias.
-    pub fn process_review(self) -> ReviewResult {
-        let mut game_map = HashMap::new();
-        let mut language_map = HashMap::new();
+    ///Procesa los datos del CSV almacenados en la misma, acumulando la review cruda en el ReviewResult
+    /// parcial del hilo que la leyó para poder calcular las estadísticas necesarias.
+    /// Si la configuración incluye un filtro y la review no lo cumple, se descarta.
+    pub fn process_review(self, result: &mut ReviewResult, config: &Config) {
+        if let Some(filter) = &config.filter {
+            if !filter.matches(&self) {
+                return;
+            }
+        }
 
-        let top_review = TopReview::new(self.review.clone(), self.votes_helpful);
+        result.add_to_groups(&self.review, &self, config);
 
-        // datos del idioma de la review para desp levantar en la seccion de languages
-        let language = Language::new(1, vec![top_review.clone()]);
-        language_map.insert(self.language.clone(), language);
-
-        // datos del juego; incluye en language del juego xq asi desp puedo calcular los top idiomas de resenas de ese juego y demas.
-        let game_language = Language::new(1, vec![top_review]);
-        let mut game_language_map = HashMap::new();
-        game_language_map.insert(self.language, game_language);
-
-        let game = Game::new(1, game_language_map);
-        game_map.insert(self.app_name, game);
+        let period = config
+            .histogram
+            .zip(self.timestamp_created)
+            .map(|(granularity, timestamp)| granularity.period(timestamp));
+        let score = config.score.score(&ScoreInputs {
+            votes_helpful: self.votes_helpful,
+            votes_funny: self.votes_funny,
+            weighted_vote_score: self.weighted_vote_score,
+            timestamp_created: self.timestamp_created,
+        });
+        let top_review = TopReview::new(self.review, self.votes_helpful, self.recommended, score);
+        result.add_review(
+            self.app_name,
+            self.language,
+            top_review,
+            period,
+            self.author_steamid,
+            config,
+        );
+    }
+}
 
-        ReviewResult::load_review_data(game_map, language_map)
+impl Filterable for ReviewRecord {
+    fn field_value(&self, field: Field) -> Option<FieldValue<'_>> {
+        match field {
+            Field::AppName => Some(FieldValue::Text(&self.app_name)),
+            Field::Language => Some(FieldValue::Text(&self.language)),
+            Field::TimestampCreated => self
+                .timestamp_created
+                .map(|timestamp| FieldValue::Number(timestamp as f64)),
+            Field::Year => self
+                .timestamp_created
+                .map(|timestamp| FieldValue::Number(date_from_timestamp(timestamp).0 as f64)),
+            Field::Month => self
+                .timestamp_created
+                .map(|timestamp| FieldValue::Number(f64::from(date_from_timestamp(timestamp).1))),
+            Field::Recommended => self.recommended.map(FieldValue::Bool),
+            Field::VotesHelpful => Some(FieldValue::Number(f64::from(self.votes_helpful))),
+            Field::VotesFunny => self
+                .votes_funny
+                .map(|votes| FieldValue::Number(f64::from(votes))),
+            Field::WeightedVoteScore => self.weighted_vote_score.map(FieldValue::Number),
+            Field::SteamPurchase => self.steam_purchase.map(FieldValue::Bool),
+            Field::ReceivedForFree => self.received_for_free.map(FieldValue::Bool),
+            Field::WrittenDuringEarlyAccess => {
+                self.written_during_early_access.map(FieldValue::Bool)
+            }
+            Field::PlaytimeForever => self.playtime_forever.map(FieldValue::Number),
+        }
     }
 }
+
+///Interpreta los booleanos del dataset, que están escritos como 'True'/'False'. Cualquier otro valor se
+/// considera no informado.
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Ok(match value.to_ascii_lowercase().as_str() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    })
+}
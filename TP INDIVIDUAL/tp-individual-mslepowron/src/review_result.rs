@@ -1,16 +1,38 @@
-//! Módulo para almacenar todos los datos de una reseña particular.
-//! Permite unificar diferentes instancias de reseñas que aplican a un mismo juego para recopilar la
+//! Módulo para almacenar los datos agregados de las reseñas procesadas.
+//! Permite acumular reseñas de a una y unificar diferentes instancias parciales para recopilar la
 //! información de todo el archivo.
+use crate::approximate::ApproximateGames;
+use crate::config::{Aggregation, Config};
+use crate::error_summary::ErrorSummary;
+use crate::filter::Filterable;
 use crate::game::Game;
+use crate::group_by::GroupedReviews;
 use crate::language::Language;
 use crate::output_data::OutputData;
+use crate::text_stats::TextAnalysis;
+use crate::top_review::TopReview;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-///Estructura que representa los resultados agregados de reseñas, organizados por juego y por idioma.
+///Estructura que representa los resultados agregados de reseñas, organizados por juego y por idioma,
+/// junto con el resumen de las filas que no pudieron procesarse.
+/// En el modo de agregación aproximado los juegos se acumulan en 'approximate_games' en lugar de 'game'.
+/// Si se pidieron estadísticas de texto, se acumulan en 'text', y si se pidió un agrupamiento, sus grupos se
+/// acumulan en 'groups'.
+/// Puede serializarse completa (incluyendo las reseñas más votadas conservadas) para guardarla en un snapshot.
+#[derive(Serialize, Deserialize)]
 pub struct ReviewResult {
     game: HashMap<String, Game>,
+    #[serde(default)]
+    approximate_games: Option<ApproximateGames>,
     language: HashMap<String, Language>,
+    #[serde(default)]
+    text: Option<TextAnalysis>,
+    #[serde(default)]
+    groups: Option<GroupedReviews>,
+    errors: ErrorSummary,
 }
 
 impl Default for ReviewResult {
@@ -24,62 +46,228 @@ impl ReviewResult {
     pub fn new() -> ReviewResult {
         ReviewResult {
             game: HashMap::new(),
+            approximate_games: None,
             language: HashMap::new(),
+            text: None,
+            groups: None,
+            errors: ErrorSummary::new(),
         }
     }
 
-    ///Permite almacenar en la estructura los datos de una reseña particular.
-    pub fn load_review_data(
-        game: HashMap<String, Game>,
-        language: HashMap<String, Language>,
-    ) -> ReviewResult {
-        ReviewResult { game, language }
+    ///Acumula en la estructura los datos de una reseña particular, creada en el período indicado y escrita por
+    /// el autor indicado, si se conocen.
+    /// Las claves solo se reservan la primera vez que aparece un juego o idioma, y el texto de la reseña
+    /// solo se clona si queda entre las más votadas.
+    pub fn add_review(
+        &mut self,
+        game_name: String,
+        language: String,
+        review: TopReview,
+        period: Option<i64>,
+        author: Option<u64>,
+        config: &Config,
+    ) {
+        if let Some(top_words) = config.top_words {
+            let game_name =
+                (config.aggregation == Aggregation::Exact).then_some(game_name.as_str());
+            self.text
+                .get_or_insert_with(|| TextAnalysis::new(top_words))
+                .add_review(game_name, &language, &review.text, &config.stop_words);
+        }
+
+        match config.aggregation {
+            Aggregation::Exact => self
+                .game
+                .entry(game_name)
+                .or_default()
+                .add_review(&language, &review, period),
+            Aggregation::Approximate => self
+                .approximate_games
+                .get_or_insert_with(|| ApproximateGames::new(config.sketch_capacity))
+                .add_review(&game_name, &language, &review, period, author),
+        }
+
+        match self.language.get_mut(&language) {
+            Some(language_data) => language_data.add_review(review, period),
+            None => {
+                let mut language_data = Language::new(config.reviews_per_language);
+                language_data.add_review(review, period);
+                self.language.insert(language, language_data);
+            }
+        }
+    }
+
+    ///Acumula una reseña, cuyo texto es 'text', en el grupo que le corresponde según el agrupamiento de la
+    /// configuración. Si no se pidió un agrupamiento no hace nada.
+    pub fn add_to_groups<T: Filterable>(&mut self, text: &str, review: &T, config: &Config) {
+        if let Some(group_by) = &config.group_by {
+            self.groups
+                .get_or_insert_with(GroupedReviews::new)
+                .add(group_by, text, review);
+        }
+    }
+
+    ///Registra una fila del archivo 'file' que no pudo procesarse.
+    pub fn add_bad_row(&mut self, file: &str, line: Option<u64>, byte: Option<u64>, error: String) {
+        self.errors.add_bad_row(file, line, byte, error);
+    }
+
+    ///Suma 'offset' a la línea de las filas inválidas registradas del archivo 'file'. Se usa para informar las
+    /// líneas de un rango de bytes respecto del comienzo del archivo.
+    pub fn shift_bad_row_lines(&mut self, file: &str, offset: u64) {
+        self.errors.shift_lines(file, offset);
+    }
+
+    ///Registra que el archivo 'file' no pudo abrirse.
+    pub fn add_open_error(&mut self, file: &str, error: String) {
+        self.errors.add_open_error(file, error);
+    }
+
+    ///Indica si se registró que el archivo 'file' no pudo abrirse.
+    pub fn open_failed(&self, file: &str) -> bool {
+        self.errors.open_failed(file)
     }
 
     ///Fusiona dos ReviewResult combinando estadísticas de juegos e idiomas.
-    /// Devuelve un ReviewResult con los datos acumulados.
-    pub fn reduce(mut self, other: &ReviewResult) -> ReviewResult {
-        self = self.reduce_game_stats(other);
-        self = self.reduce_language_stats(other);
+    /// Devuelve un ReviewResult con los datos acumulados. Los datos de 'other' se mueven sin clonarse.
+    pub fn reduce(mut self, mut other: ReviewResult) -> ReviewResult {
+        if self.game.len() < other.game.len() {
+            std::mem::swap(&mut self, &mut other);
+        }
+        self = self.reduce_game_stats(other.game);
+        self.approximate_games = merge_optional(
+            self.approximate_games,
+            other.approximate_games,
+            ApproximateGames::merge,
+        );
+        self = self.reduce_language_stats(other.language);
+        self.text = merge_optional(self.text, other.text, TextAnalysis::merge);
+        self.groups = merge_optional(self.groups, other.groups, GroupedReviews::merge);
+        self.errors.merge(other.errors);
         self
     }
 
     ///Unifica los datos del Game de la ReviewResult con el game de otra.
-    /// Si el Game ya está registrado, se actualizan sus valores. Si no está registrado, se crea con los datos contenidos en 'other'
-    fn reduce_game_stats(mut self, other: &ReviewResult) -> ReviewResult {
-        for (game_name, other_game) in &other.game {
-            self.game
-                .entry(game_name.clone())
-                .and_modify(|current_game| current_game.merge_game_wiith_other_review(other_game))
-                .or_insert_with(|| other_game.clone());
+    /// Si el Game ya está registrado, se actualizan sus valores. Si no está registrado, se inserta el de 'other'
+    fn reduce_game_stats(mut self, other: HashMap<String, Game>) -> ReviewResult {
+        for (game_name, other_game) in other {
+            match self.game.get_mut(&game_name) {
+                Some(current_game) => current_game.merge_game_wiith_other_review(other_game),
+                None => {
+                    self.game.insert(game_name, other_game);
+                }
+            }
         }
         self
     }
 
     ///Unifica los datos del Language de la ReviewResult con el language de otra reseña.
-    /// Si el Language ya está registrado, se actualizan sus valores. Si no está registrado, se crea con los datos contenidos en 'other'
-    fn reduce_language_stats(mut self, other: &ReviewResult) -> ReviewResult {
-        for (language, other_language_data) in &other.language {
-            self.language
-                .entry(language.clone())
-                .and_modify(|language_data| {
+    /// Si el Language ya está registrado, se actualizan sus valores. Si no está registrado, se inserta el de 'other'
+    fn reduce_language_stats(mut self, other: HashMap<String, Language>) -> ReviewResult {
+        for (language, other_language_data) in other {
+            match self.language.get_mut(&language) {
+                Some(language_data) => {
                     language_data.merge_language_with_other_review(other_language_data)
-                })
-                .or_insert_with(|| other_language_data.clone());
+                }
+                None => {
+                    self.language.insert(language, other_language_data);
+                }
+            }
         }
         self
     }
 
-    ///Devuelve los top 'n' juegos e idiomas más populares, dada una cantidad solicitada de cada uno.
-    pub fn get_top_results(
-        self,
-        num_games_to_display: usize,
-        num_languages_to_display: usize,
-    ) -> OutputData {
-        let top_games: Vec<Value> = Game::top_n_games_reviewed(self.game, num_games_to_display);
-        let top_languages: Vec<Value> =
-            Language::top_n_languages(self.language, num_languages_to_display);
+    ///Devuelve los top 'n' juegos e idiomas más populares, según las cantidades solicitadas en la configuración.
+    /// En el modo aproximado se incluyen además las cotas de error de las estimaciones, y si se pidieron, las
+    /// estadísticas de texto de los juegos e idiomas incluidos y los grupos del agrupamiento.
+    /// El resultado no se consume, por lo que puede seguir consultándose o fusionándose.
+    pub fn get_top_results(&self, config: &Config) -> OutputData {
+        let top_games = self.top_games(config);
+        let top_languages = self.top_languages(config);
+        let error_bounds = match config.aggregation {
+            Aggregation::Exact => None,
+            Aggregation::Approximate => Some(self.approximate_games(config).error_bounds()),
+        };
+        let text_stats = config.top_words.map(|top_words| {
+            let empty = TextAnalysis::new(top_words);
+            self.text
+                .as_ref()
+                .unwrap_or(&empty)
+                .to_json(&top_games, &top_languages, config)
+        });
+        let groups = config.group_by.as_ref().map(|group_by| match &self.groups {
+            Some(groups) => groups.to_json(group_by),
+            None => Vec::new(),
+        });
 
-        OutputData::new(top_games, top_languages)
+        OutputData::new(
+            config.padron,
+            top_games,
+            top_languages,
+            error_bounds,
+            text_stats,
+            groups,
+            self.errors.report(),
+        )
+    }
+
+    ///Devuelve los top 'n' juegos más reseñados, según la cantidad solicitada en la configuración.
+    pub fn top_games(&self, config: &Config) -> Vec<Value> {
+        match config.aggregation {
+            Aggregation::Exact => Game::top_n_games_reviewed(&self.game, config),
+            Aggregation::Approximate => self.approximate_games(config).top_n_games_reviewed(config),
+        }
+    }
+
+    ///Devuelve los top 'n' juegos con más reseñas escritas en el idioma indicado, según la cantidad solicitada
+    /// en la configuración.
+    pub fn top_games_in_language(&self, language: &str, config: &Config) -> Vec<Value> {
+        match config.aggregation {
+            Aggregation::Exact => Game::top_n_games_in_language(&self.game, language, config),
+            Aggregation::Approximate => self
+                .approximate_games(config)
+                .top_n_games_in_language(language, config),
+        }
+    }
+
+    ///Devuelve los top 'n' idiomas más utilizados, según la cantidad solicitada en la configuración.
+    pub fn top_languages(&self, config: &Config) -> Vec<Value> {
+        Language::top_n_languages(&self.language, config)
+    }
+
+    ///Devuelve los datos de un juego con el formato en que se incluyen en la salida, incluyendo sus idiomas más
+    /// utilizados, o None si el juego no tiene reseñas (o no se conservó, en el modo aproximado).
+    pub fn game(&self, game_name: &str, config: &Config) -> Option<Value> {
+        match config.aggregation {
+            Aggregation::Exact => self
+                .game
+                .get(game_name)
+                .map(|game| game.to_json(game_name, config)),
+            Aggregation::Approximate => self
+                .approximate_games(config)
+                .game_to_json(game_name, config),
+        }
+    }
+
+    fn approximate_games(&self, config: &Config) -> Cow<'_, ApproximateGames> {
+        match &self.approximate_games {
+            Some(games) => Cow::Borrowed(games),
+            None => Cow::Owned(ApproximateGames::new(config.sketch_capacity)),
+        }
+    }
+}
+
+///Fusiona dos datos opcionales con 'merge'. Si solo uno de los dos está presente, se conserva ese.
+fn merge_optional<T>(
+    current: Option<T>,
+    other: Option<T>,
+    merge: impl FnOnce(&mut T, T),
+) -> Option<T> {
+    match (current, other) {
+        (Some(mut current), Some(other)) => {
+            merge(&mut current, other);
+            Some(current)
+        }
+        (current, other) => current.or(other),
     }
 }
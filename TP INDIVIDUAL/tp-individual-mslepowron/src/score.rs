@@ -0,0 +1,69 @@
+//! Módulo con los criterios para puntuar las reseñas al elegir las más destacadas de cada juego e idioma.
+//! El puntaje se calcula una sola vez al leer la reseña y se guarda junto a ella, por lo que la fusión de
+//! resultados parciales compara puntajes sin volver a calcularlos.
+use crate::review_error::ReviewError;
+use serde::{Deserialize, Serialize};
+
+///Criterio con el que se puntúan las reseñas.
+/// ###  VotesHelpful
+/// Cantidad de votos que marcaron la reseña como útil. Es el criterio original, por lo que no se guarda un puntaje
+/// aparte ni se agrega a la salida.
+/// ###  VotesFunny
+/// Cantidad de votos que marcaron la reseña como graciosa.
+/// ###  WeightedVoteScore
+/// Puntaje de utilidad calculado por Steam, entre 0 y 1.
+/// ###  Combined
+/// Votos útiles más la mitad de los graciosos, multiplicados por 0.5 + el puntaje de Steam, de forma que el puntaje
+/// de Steam puede reducir los votos a la mitad o aumentarlos un 50%.
+/// ###  Recency
+/// Fecha de creación: las reseñas más nuevas primero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreStrategy {
+    VotesHelpful,
+    VotesFunny,
+    WeightedVoteScore,
+    Combined,
+    Recency,
+}
+
+///Datos de una reseña que pueden usarse para puntuarla. Los que no están informados en el csv se toman como 0.
+pub struct ScoreInputs {
+    pub votes_helpful: u32,
+    pub votes_funny: Option<u32>,
+    pub weighted_vote_score: Option<f64>,
+    pub timestamp_created: Option<i64>,
+}
+
+impl ScoreStrategy {
+    ///Interpreta el criterio recibido por línea de comandos.
+    pub fn parse(value: &str) -> Result<ScoreStrategy, ReviewError> {
+        match value {
+            "votes-helpful" => Ok(ScoreStrategy::VotesHelpful),
+            "votes-funny" => Ok(ScoreStrategy::VotesFunny),
+            "weighted-vote-score" => Ok(ScoreStrategy::WeightedVoteScore),
+            "combined" => Ok(ScoreStrategy::Combined),
+            "recency" => Ok(ScoreStrategy::Recency),
+            _ => Err(ReviewError::InvalidArgument(format!(
+                "Criterio de puntaje desconocido '{}'. Valores posibles: votes-helpful, votes-funny, \
+                 weighted-vote-score, combined, recency",
+                value
+            ))),
+        }
+    }
+
+    ///Devuelve el puntaje de la reseña, o None con el criterio VotesHelpful, en el que se comparan directamente
+    /// los votos útiles.
+    pub fn score(&self, inputs: &ScoreInputs) -> Option<f64> {
+        let votes_funny = f64::from(inputs.votes_funny.unwrap_or(0));
+        let weighted_vote_score = inputs.weighted_vote_score.unwrap_or(0.0);
+        match self {
+            ScoreStrategy::VotesHelpful => None,
+            ScoreStrategy::VotesFunny => Some(votes_funny),
+            ScoreStrategy::WeightedVoteScore => Some(weighted_vote_score),
+            ScoreStrategy::Combined => Some(
+                (f64::from(inputs.votes_helpful) + votes_funny / 2.0) * (0.5 + weighted_vote_score),
+            ),
+            ScoreStrategy::Recency => Some(inputs.timestamp_created.unwrap_or(0) as f64),
+        }
+    }
+}
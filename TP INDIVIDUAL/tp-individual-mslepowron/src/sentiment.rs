@@ -0,0 +1,82 @@
+//! Módulo para manejar las estadísticas de recomendación de las reseñas de un juego o idioma.
+use crate::top_review::TopReview;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+///Estructura para almacenar cuántas reseñas recomiendan o no un juego, junto con la reseña más votada de cada tipo.
+/// Las reseñas que no informan si recomiendan el juego no se contabilizan.
+/// La fusión suma los contadores y se queda con la mejor reseña de cada tipo, por lo que es asociativa y
+/// conmutativa: el resultado no depende de cómo se repartieron las reseñas entre los hilos.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Sentiment {
+    pub recommended: u32,
+    pub not_recommended: u32,
+    pub top_positive: Option<TopReview>,
+    pub top_negative: Option<TopReview>,
+}
+
+impl Sentiment {
+    ///Crea una nueva instancia sin reseñas.
+    pub fn new() -> Sentiment {
+        Sentiment::default()
+    }
+
+    ///Registra una reseña. Solo se clona el texto si pasa a ser la más votada de su tipo.
+    pub fn add_review(&mut self, review: &TopReview) {
+        let (count, top) = match review.recommended {
+            Some(true) => (&mut self.recommended, &mut self.top_positive),
+            Some(false) => (&mut self.not_recommended, &mut self.top_negative),
+            None => return,
+        };
+        *count += 1;
+        if top.as_ref().is_none_or(|current| review > current) {
+            *top = Some(review.clone());
+        }
+    }
+
+    ///Fusiona las estadísticas de otra instancia.
+    pub fn merge(&mut self, other: Sentiment) {
+        self.recommended += other.recommended;
+        self.not_recommended += other.not_recommended;
+        self.top_positive = best_review(self.top_positive.take(), other.top_positive);
+        self.top_negative = best_review(self.top_negative.take(), other.top_negative);
+    }
+
+    ///Devuelve la proporción de reseñas que recomiendan el juego, o None si no hay reseñas que lo informen.
+    pub fn recommended_ratio(&self) -> Option<f64> {
+        let total = self.recommended + self.not_recommended;
+        if total == 0 {
+            return None;
+        }
+        Some(f64::from(self.recommended) / f64::from(total))
+    }
+
+    ///Devuelve las estadísticas con el formato en que se incluyen en la salida.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "recommended": self.recommended,
+            "not_recommended": self.not_recommended,
+            "recommended_ratio": self.recommended_ratio(),
+            "top_positive_review": self.top_positive.as_ref().map(review_json),
+            "top_negative_review": self.top_negative.as_ref().map(review_json),
+        })
+    }
+}
+
+fn best_review(current: Option<TopReview>, other: Option<TopReview>) -> Option<TopReview> {
+    match (current, other) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn review_json(review: &TopReview) -> Value {
+    let mut entry = json!({
+        "review": review.text,
+        "votes": review.votes_helpful,
+    });
+    if let Some(score) = review.score {
+        entry["score"] = json!(score);
+    }
+    entry
+}
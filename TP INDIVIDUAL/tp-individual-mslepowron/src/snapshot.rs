@@ -0,0 +1,193 @@
+//! Módulo para guardar y recuperar el resultado de cada archivo procesado en una ejecución, de forma que una
+//! ejecución posterior sobre el mismo directorio solo procese los archivos nuevos o modificados y los fusione con
+//! el resultado anterior.
+use crate::config::{Aggregation, Config};
+use crate::filter::Filter;
+use crate::group_by::GroupBy;
+use crate::histogram::Granularity;
+use crate::review_error::ReviewError;
+use crate::review_result::ReviewResult;
+use crate::score::ScoreStrategy;
+use crate::text_stats::StopWords;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{metadata, rename, write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+///Datos que identifican una versión particular de un archivo procesado.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub modified_secs: u64,
+    pub modified_nanos: u32,
+}
+
+impl FileFingerprint {
+    ///Obtiene la huella del archivo a partir de su tamaño y fecha de modificación.
+    /// Devuelve None si no pueden leerse los metadatos del archivo.
+    pub fn of(path: &Path) -> Option<FileFingerprint> {
+        let metadata = metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(FileFingerprint {
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+///Resultado parcial de un archivo procesado, junto con la huella que tenía al procesarse.
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    fingerprint: FileFingerprint,
+    result: ReviewResult,
+}
+
+///Estructura que se persiste en el archivo de snapshot.
+/// ###  reviews_per_language, filter, histogram, sketch_capacity, stop_words, score y group_by
+/// Opciones con las que se generó el resultado. Si cambian, el resultado guardado no puede reutilizarse.
+/// ###  files
+/// Archivos ya procesados, identificados por su ruta canónica, con su huella y su resultado parcial. Se guarda el
+/// resultado de cada archivo por separado para poder reemplazarlo si el archivo cambia.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    reviews_per_language: usize,
+    filter: Option<Filter>,
+    #[serde(default)]
+    histogram: Option<Granularity>,
+    #[serde(default)]
+    sketch_capacity: Option<usize>,
+    #[serde(default)]
+    stop_words: Option<StopWords>,
+    #[serde(default)]
+    score: Option<ScoreStrategy>,
+    #[serde(default)]
+    group_by: Option<GroupBy>,
+    files: BTreeMap<String, SnapshotFile>,
+}
+
+impl Snapshot {
+    ///Crea un snapshot vacío para la configuración indicada.
+    pub fn new(config: &Config) -> Snapshot {
+        Snapshot {
+            reviews_per_language: config.reviews_per_language,
+            filter: config.filter.clone(),
+            histogram: config.histogram,
+            sketch_capacity: sketch_capacity(config),
+            stop_words: stop_words(config),
+            score: score(config),
+            group_by: config.group_by.clone(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    ///Carga el snapshot guardado en 'path', o crea uno vacío si el archivo no existe.
+    /// Devuelve error si el archivo no puede leerse o interpretarse, o si fue generado con otras opciones de
+    /// agregación que las de 'config'.
+    pub fn load_or_new(path: &str, config: &Config) -> Result<Snapshot, ReviewError> {
+        if !Path::new(path).exists() {
+            return Ok(Snapshot::new(config));
+        }
+        Snapshot::load(path, config)
+    }
+
+    ///Carga el snapshot guardado en 'path'.
+    /// Devuelve error si el archivo no existe, no puede leerse o interpretarse, o si fue generado con otras
+    /// opciones de agregación que las de 'config'.
+    pub fn load(path: &str, config: &Config) -> Result<Snapshot, ReviewError> {
+        let content = std::fs::read(path).map_err(|e| {
+            ReviewError::SnapshotError(format!("No se pudo leer el snapshot '{}': {}", path, e))
+        })?;
+        let snapshot: Snapshot = serde_json::from_slice(&content).map_err(|e| {
+            ReviewError::SnapshotError(format!(
+                "No se pudo interpretar el snapshot '{}': {}",
+                path, e
+            ))
+        })?;
+
+        if snapshot.reviews_per_language != config.reviews_per_language
+            || snapshot.filter != config.filter
+            || snapshot.histogram != config.histogram
+            || snapshot.sketch_capacity != sketch_capacity(config)
+            || snapshot.stop_words != stop_words(config)
+            || snapshot.score != score(config)
+            || snapshot.group_by != config.group_by
+        {
+            return Err(ReviewError::SnapshotError(format!(
+                "El snapshot '{}' fue generado con otro filtro, cantidad de reseñas por idioma, histograma, modo de agregación, estadísticas de texto, criterio de puntaje o agrupamiento",
+                path
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    ///Indica si el archivo ya fue incluido en el resultado con la misma huella.
+    pub fn contains(&self, file: &str, fingerprint: Option<FileFingerprint>) -> bool {
+        fingerprint.is_some_and(|fingerprint| {
+            self.files
+                .get(file)
+                .is_some_and(|recorded| recorded.fingerprint == fingerprint)
+        })
+    }
+
+    ///Descarta los archivos registrados que no están en 'files', es decir, los que ya no forman parte de la
+    /// entrada, para que su resultado deje de incluirse.
+    pub fn prune(&mut self, files: &HashSet<String>) {
+        self.files.retain(|file, _| files.contains(file));
+    }
+
+    ///Registra el resultado de procesar un archivo con la huella indicada. Si el archivo ya estaba registrado, su
+    /// resultado anterior se descarta y se reemplaza por el nuevo.
+    pub fn record(&mut self, file: String, fingerprint: FileFingerprint, result: ReviewResult) {
+        self.files.insert(
+            file,
+            SnapshotFile {
+                fingerprint,
+                result,
+            },
+        );
+    }
+
+    ///Guarda el snapshot en 'path'. Se escribe primero un archivo temporal y luego se renombra, para no dejar
+    /// un snapshot corrupto si la escritura se interrumpe.
+    pub fn save(&self, path: &str) -> Result<(), ReviewError> {
+        let content = serde_json::to_vec(self).map_err(|e| {
+            ReviewError::SnapshotError(format!("Falló la serialización del snapshot: {}", e))
+        })?;
+        let temporary_path = format!("{}.tmp", path);
+        write(&temporary_path, content)
+            .and_then(|_| rename(&temporary_path, path))
+            .map_err(|e| {
+                ReviewError::SnapshotError(format!(
+                    "No se pudo escribir el snapshot '{}': {}",
+                    path, e
+                ))
+            })
+    }
+
+    ///Devuelve el resultado agregado de todos los archivos incluidos en el snapshot.
+    pub fn into_result(self) -> ReviewResult {
+        self.files
+            .into_values()
+            .fold(ReviewResult::new(), |acc, file| acc.reduce(file.result))
+    }
+}
+
+///Capacidad del sketch de juegos si la configuración usa el modo aproximado, o None en el modo exacto.
+fn sketch_capacity(config: &Config) -> Option<usize> {
+    match config.aggregation {
+        Aggregation::Exact => None,
+        Aggregation::Approximate => Some(config.sketch_capacity),
+    }
+}
+
+///Palabras vacías si la configuración pide estadísticas de texto, o None si no se calculan.
+fn stop_words(config: &Config) -> Option<StopWords> {
+    config.top_words.map(|_| config.stop_words.clone())
+}
+
+///Criterio de puntaje si la configuración no usa el original (votos útiles), o None si lo usa.
+fn score(config: &Config) -> Option<ScoreStrategy> {
+    (config.score != ScoreStrategy::VotesHelpful).then_some(config.score)
+}
@@ -0,0 +1,209 @@
+//! Módulo con el algoritmo Space-Saving para estimar los elementos más frecuentes de un flujo usando memoria
+//! acotada. Se conservan como máximo 'capacity' contadores; cuando aparece un elemento nuevo y no hay lugar,
+//! reemplaza al de menor cuenta heredando esa cuenta como error.
+//!
+//! Garantías, siendo N la cantidad total de elementos contados y m la capacidad:
+//! - La cuenta de un elemento conservado nunca subestima la real y la sobreestima en a lo sumo su 'error'.
+//! - El error de cualquier elemento es a lo sumo N / m, por lo que todo elemento con frecuencia real mayor a
+//!   N / m está entre los conservados.
+//!
+//! La fusión de dos instancias mantiene las mismas garantías, por lo que puede usarse en el reduce del modelo
+//! fork-join. El resultado exacto de la fusión depende de cómo se repartieron los elementos entre los hilos,
+//! pero siempre dentro de las cotas anteriores.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+///Contador de un elemento conservado, junto con los datos asociados a ese elemento.
+/// ###  count
+/// Cuenta estimada, que nunca es menor a la real.
+/// ###  error
+/// Cota de la sobreestimación: la cuenta real está entre 'count - error' y 'count'.
+/// ###  value
+/// Datos acumulados del elemento desde que comenzó a conservarse.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Counter<V> {
+    pub count: u64,
+    pub error: u64,
+    pub value: V,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Slot<V> {
+    key: String,
+    counter: Counter<V>,
+}
+
+///Estructura que estima los 'capacity' elementos más frecuentes.
+/// Los contadores se guardan en posiciones fijas y se indexan por clave y por cuenta, de forma que incrementar
+/// un contador o reemplazar el mínimo cuesta O(log m) sin reservar memoria nueva.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpaceSaving<V> {
+    capacity: usize,
+    total: u64,
+    slots: Vec<Slot<V>>,
+    index: HashMap<String, usize>,
+    by_count: BTreeSet<(u64, usize)>,
+}
+
+impl<V: Default> SpaceSaving<V> {
+    ///Crea una nueva instancia vacía que conservará como máximo 'capacity' contadores (al menos uno).
+    pub fn new(capacity: usize) -> SpaceSaving<V> {
+        SpaceSaving {
+            capacity: capacity.max(1),
+            total: 0,
+            slots: Vec::new(),
+            index: HashMap::new(),
+            by_count: BTreeSet::new(),
+        }
+    }
+
+    ///Cuenta una aparición del elemento y devuelve sus datos asociados para actualizarlos.
+    /// Si el elemento no estaba conservado y no hay lugar, reemplaza al de menor cuenta y sus datos comienzan
+    /// vacíos.
+    pub fn add(&mut self, key: &str) -> &mut V {
+        self.total += 1;
+
+        let position = match self.index.get(key) {
+            Some(&position) => {
+                let counter = &mut self.slots[position].counter;
+                self.by_count.remove(&(counter.count, position));
+                counter.count += 1;
+                self.by_count.insert((counter.count, position));
+                position
+            }
+            None if self.slots.len() < self.capacity => {
+                let position = self.slots.len();
+                self.slots.push(Slot {
+                    key: key.to_string(),
+                    counter: Counter {
+                        count: 1,
+                        error: 0,
+                        value: V::default(),
+                    },
+                });
+                self.index.insert(key.to_string(), position);
+                self.by_count.insert((1, position));
+                position
+            }
+            None => {
+                let (min_count, position) = self
+                    .by_count
+                    .pop_first()
+                    .unwrap_or((0, self.slots.len() - 1));
+                let slot = &mut self.slots[position];
+                self.index.remove(&slot.key);
+                slot.key = key.to_string();
+                slot.counter = Counter {
+                    count: min_count + 1,
+                    error: min_count,
+                    value: V::default(),
+                };
+                self.index.insert(key.to_string(), position);
+                self.by_count.insert((min_count + 1, position));
+                position
+            }
+        };
+        &mut self.slots[position].counter.value
+    }
+
+    ///Fusiona los contadores de otra instancia. Los elementos conservados en ambas suman sus cuentas y fusionan
+    /// sus datos con 'merge_value'; los conservados en una sola suman la menor cuenta de la otra, que es la
+    /// máxima cantidad de apariciones que pudo haber perdido. Luego se conservan los 'capacity' de mayor cuenta.
+    pub fn merge(&mut self, other: SpaceSaving<V>, merge_value: impl Fn(&mut V, V)) {
+        let self_min = self.min_count();
+        let other_min = other.min_count();
+        let capacity = self.capacity.max(other.capacity);
+        let total = self.total + other.total;
+
+        let mut other_slots: HashMap<String, Counter<V>> = other
+            .slots
+            .into_iter()
+            .map(|slot| (slot.key, slot.counter))
+            .collect();
+
+        let mut merged: Vec<Slot<V>> = Vec::with_capacity(self.slots.len() + other_slots.len());
+        for mut slot in std::mem::take(&mut self.slots) {
+            match other_slots.remove(&slot.key) {
+                Some(other_counter) => {
+                    slot.counter.count += other_counter.count;
+                    slot.counter.error += other_counter.error;
+                    merge_value(&mut slot.counter.value, other_counter.value);
+                }
+                None => {
+                    slot.counter.count += other_min;
+                    slot.counter.error += other_min;
+                }
+            }
+            merged.push(slot);
+        }
+        for (key, mut counter) in other_slots {
+            counter.count += self_min;
+            counter.error += self_min;
+            merged.push(Slot { key, counter });
+        }
+
+        merged.sort_by(|a, b| {
+            b.counter
+                .count
+                .cmp(&a.counter.count)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        merged.truncate(capacity);
+
+        *self = SpaceSaving::new(capacity);
+        self.total = total;
+        for (position, slot) in merged.into_iter().enumerate() {
+            self.index.insert(slot.key.clone(), position);
+            self.by_count.insert((slot.counter.count, position));
+            self.slots.push(slot);
+        }
+    }
+}
+
+impl<V> SpaceSaving<V> {
+    ///Cantidad máxima de contadores que se conservan.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///Cantidad total de elementos contados.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    ///Cota del error de cualquier cuenta estimada: N / m.
+    pub fn max_error(&self) -> u64 {
+        self.total / self.capacity as u64
+    }
+
+    ///Menor cuenta conservada si no hay lugar para nuevos contadores, o 0 si lo hay. Ningún elemento no
+    /// conservado puede haber aparecido más veces que este valor.
+    pub fn min_count(&self) -> u64 {
+        if self.slots.len() < self.capacity {
+            return 0;
+        }
+        self.by_count.first().map_or(0, |(count, _)| *count)
+    }
+
+    ///Devuelve el contador del elemento, si está conservado.
+    pub fn get(&self, key: &str) -> Option<&Counter<V>> {
+        self.index
+            .get(key)
+            .map(|&position| &self.slots[position].counter)
+    }
+
+    ///Devuelve los elementos conservados con sus contadores, sin consumir la estructura.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Counter<V>)> {
+        self.slots
+            .iter()
+            .map(|slot| (slot.key.as_str(), &slot.counter))
+    }
+
+    ///Devuelve los elementos conservados con sus contadores.
+    pub fn into_entries(self) -> Vec<(String, Counter<V>)> {
+        self.slots
+            .into_iter()
+            .map(|slot| (slot.key, slot.counter))
+            .collect()
+    }
+}
@@ -0,0 +1,330 @@
+//! Módulo para calcular estadísticas sobre el texto de las reseñas: largo promedio por juego e idioma, palabras
+//! más frecuentes por idioma sin contar las palabras vacías, y cuántas reseñas usan emojis o son arte ASCII.
+//! Las estadísticas de las reseñas son sumas de contadores, por lo que su fusión es asociativa y conmutativa. Las
+//! palabras de cada idioma se cuentan con un sketch Space-Saving para acotar la memoria: mientras un idioma no
+//! supere la cantidad de contadores las cuentas son exactas, y si la supera pueden sobreestimarse.
+use crate::config::Config;
+use crate::review_error::ReviewError;
+use crate::space_saving::SpaceSaving;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+///Cantidad mínima de líneas con dibujo para considerar que una reseña es arte ASCII.
+const ASCII_ART_MIN_LINES: usize = 3;
+///Cantidad mínima de caracteres visibles que debe tener una línea para considerarse parte de un dibujo.
+const ASCII_ART_MIN_LINE_CHARS: usize = 8;
+///Proporción mínima de símbolos entre los caracteres visibles de una línea de dibujo.
+const ASCII_ART_SYMBOL_RATIO: f64 = 0.6;
+///Cantidad de contadores de palabras que se conservan por idioma por cada palabra pedida en la salida.
+const WORD_COUNTERS_PER_TOP_WORD: usize = 100;
+///Cantidad mínima de contadores de palabras que se conservan por idioma.
+const MIN_WORD_COUNTERS: usize = 5000;
+
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "but", "by", "can", "could", "did", "do", "does", "don't", "for", "from",
+    "get", "had", "has", "have", "he", "her", "his", "how", "i", "i'm", "if", "in", "into", "is",
+    "it", "it's", "its", "just", "like", "me", "more", "my", "no", "not", "of", "on", "one",
+    "only", "or", "other", "out", "so", "some", "than", "that", "the", "their", "them", "then",
+    "there", "they", "this", "to", "up", "very", "was", "we", "were", "what", "when", "which",
+    "who", "will", "with", "would", "you", "your",
+];
+
+const SPANISH_STOP_WORDS: &[&str] = &[
+    "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "en", "entre", "era", "es", "esa",
+    "ese", "eso", "esta", "este", "esto", "fue", "ha", "hay", "la", "las", "le", "les", "lo",
+    "los", "me", "mi", "muy", "más", "ni", "no", "nos", "o", "para", "pero", "por", "porque",
+    "que", "qué", "se", "si", "sin", "sobre", "son", "su", "sus", "te", "tiene", "todo", "un",
+    "una", "uno", "y", "ya", "yo",
+];
+
+///Listas de palabras vacías por idioma, que no se cuentan entre las palabras más frecuentes.
+/// Por defecto se incluyen listas para 'english', 'spanish' y 'latam'; los idiomas sin lista cuentan todas las
+/// palabras.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StopWords {
+    lists: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Default for StopWords {
+    fn default() -> Self {
+        let list = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        let mut lists = BTreeMap::new();
+        lists.insert("english".to_string(), list(ENGLISH_STOP_WORDS));
+        lists.insert("spanish".to_string(), list(SPANISH_STOP_WORDS));
+        lists.insert("latam".to_string(), list(SPANISH_STOP_WORDS));
+        StopWords { lists }
+    }
+}
+
+impl StopWords {
+    ///Reemplaza la lista de un idioma por la del archivo indicado con el formato '<idioma>=<archivo>'.
+    /// El archivo tiene una palabra por línea; las líneas vacías se ignoran.
+    /// Devuelve error si el formato es inválido o el archivo no puede leerse.
+    pub fn load(&mut self, spec: &str) -> Result<(), ReviewError> {
+        let (language, path) = spec
+            .split_once('=')
+            .filter(|(language, path)| !language.is_empty() && !path.is_empty())
+            .ok_or_else(|| {
+                ReviewError::InvalidArgument(format!(
+                    "Lista de palabras vacías inválida '{}', se esperaba '<idioma>=<archivo>'",
+                    spec
+                ))
+            })?;
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ReviewError::FileOpenError(format!("{}: {}", path, e)))?;
+        let words = content
+            .lines()
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        self.lists.insert(language.to_string(), words);
+        Ok(())
+    }
+
+    ///Indica si la palabra, ya en minúsculas, es una palabra vacía del idioma.
+    pub fn contains(&self, language: &str, word: &str) -> bool {
+        self.lists
+            .get(language)
+            .is_some_and(|words| words.contains(word))
+    }
+}
+
+///Estadísticas del texto de un conjunto de reseñas.
+/// ###  characters
+/// Suma del largo de las reseñas, en caracteres, para calcular el promedio.
+/// ###  emoji_reviews y ascii_art_reviews
+/// Cantidad de reseñas que contienen al menos un emoji y que contienen un dibujo de arte ASCII.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TextStats {
+    reviews: u64,
+    characters: u64,
+    emoji_reviews: u64,
+    ascii_art_reviews: u64,
+}
+
+impl TextStats {
+    fn add(&mut self, features: &TextFeatures) {
+        self.reviews += 1;
+        self.characters += features.characters;
+        self.emoji_reviews += u64::from(features.has_emoji);
+        self.ascii_art_reviews += u64::from(features.is_ascii_art);
+    }
+
+    fn merge(&mut self, other: TextStats) {
+        self.reviews += other.reviews;
+        self.characters += other.characters;
+        self.emoji_reviews += other.emoji_reviews;
+        self.ascii_art_reviews += other.ascii_art_reviews;
+    }
+
+    ///Devuelve el largo promedio de las reseñas en caracteres, o None si no hay reseñas.
+    pub fn average_length(&self) -> Option<f64> {
+        (self.reviews > 0).then(|| self.characters as f64 / self.reviews as f64)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "review_count": self.reviews,
+            "average_length": self.average_length(),
+            "emoji_reviews": self.emoji_reviews,
+            "ascii_art_reviews": self.ascii_art_reviews,
+        })
+    }
+}
+
+///Estadísticas del texto de las reseñas de un idioma, junto con la cantidad estimada de apariciones de las
+/// palabras más frecuentes.
+#[derive(Clone, Serialize, Deserialize)]
+struct LanguageText {
+    stats: TextStats,
+    words: SpaceSaving<()>,
+}
+
+///Estructura que acumula las estadísticas de texto por juego y por idioma.
+/// En el modo de agregación aproximado no se acumulan estadísticas por juego, ya que conservar todos los juegos
+/// anularía el ahorro de memoria de ese modo.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TextAnalysis {
+    word_capacity: usize,
+    games: HashMap<String, TextStats>,
+    languages: HashMap<String, LanguageText>,
+}
+
+impl TextAnalysis {
+    ///Crea una nueva instancia sin reseñas, que conserva por idioma suficientes contadores de palabras para
+    /// estimar las 'top_words' más frecuentes.
+    pub fn new(top_words: usize) -> TextAnalysis {
+        TextAnalysis {
+            word_capacity: top_words
+                .saturating_mul(WORD_COUNTERS_PER_TOP_WORD)
+                .max(MIN_WORD_COUNTERS),
+            games: HashMap::new(),
+            languages: HashMap::new(),
+        }
+    }
+
+    ///Registra el texto de una reseña del juego indicado, si se acumulan juegos, escrita en el idioma indicado.
+    pub fn add_review(
+        &mut self,
+        game_name: Option<&str>,
+        language: &str,
+        text: &str,
+        stop_words: &StopWords,
+    ) {
+        let features = TextFeatures::of(text);
+        if let Some(game_name) = game_name {
+            match self.games.get_mut(game_name) {
+                Some(stats) => stats.add(&features),
+                None => {
+                    let mut stats = TextStats::default();
+                    stats.add(&features);
+                    self.games.insert(game_name.to_string(), stats);
+                }
+            }
+        }
+
+        let language_text = match self.languages.get_mut(language) {
+            Some(language_text) => language_text,
+            None => self
+                .languages
+                .entry(language.to_string())
+                .or_insert_with(|| LanguageText {
+                    stats: TextStats::default(),
+                    words: SpaceSaving::new(self.word_capacity),
+                }),
+        };
+        language_text.stats.add(&features);
+        for word in words(text) {
+            if !stop_words.contains(language, &word) {
+                language_text.words.add(&word);
+            }
+        }
+    }
+
+    ///Fusiona las estadísticas de otra instancia. Las palabras de un idioma presente en ambas se fusionan con las
+    /// garantías del sketch Space-Saving.
+    pub fn merge(&mut self, other: TextAnalysis) {
+        for (game_name, other_stats) in other.games {
+            self.games.entry(game_name).or_default().merge(other_stats);
+        }
+        for (language, other_text) in other.languages {
+            match self.languages.entry(language) {
+                Entry::Occupied(mut entry) => {
+                    let language_text = entry.get_mut();
+                    language_text.stats.merge(other_text.stats);
+                    language_text.words.merge(other_text.words, |_, _| {});
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other_text);
+                }
+            }
+        }
+    }
+
+    ///Devuelve las estadísticas de los juegos e idiomas incluidos en la salida, en el mismo orden, con el formato
+    /// en que se incluyen en la salida. Cada idioma incluye sus palabras más frecuentes, según la cantidad
+    /// solicitada en la configuración, ordenadas por cantidad de apariciones y luego alfabéticamente.
+    pub fn to_json(&self, top_games: &[Value], top_languages: &[Value], config: &Config) -> Value {
+        let games: Vec<Value> = top_games
+            .iter()
+            .filter_map(|game| {
+                let game_name = game["game"].as_str()?;
+                let mut entry = self.games.get(game_name)?.to_json();
+                entry["game"] = json!(game_name);
+                Some(entry)
+            })
+            .collect();
+
+        let top_words = config.top_words.unwrap_or(0);
+        let languages: Vec<Value> = top_languages
+            .iter()
+            .filter_map(|language| {
+                let language_name = language["language"].as_str()?;
+                let language_text = self.languages.get(language_name)?;
+                let mut words: Vec<_> = language_text
+                    .words
+                    .entries()
+                    .map(|(word, counter)| (word, counter.count))
+                    .collect();
+                words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+                words.truncate(top_words);
+
+                let mut entry = language_text.stats.to_json();
+                entry["language"] = json!(language_name);
+                entry["top_words"] = words
+                    .into_iter()
+                    .map(|(word, count)| json!({ "word": word, "count": count }))
+                    .collect();
+                Some(entry)
+            })
+            .collect();
+
+        json!({ "games": games, "languages": languages })
+    }
+}
+
+///Características de una reseña que se calculan en una sola pasada por su texto.
+struct TextFeatures {
+    characters: u64,
+    has_emoji: bool,
+    is_ascii_art: bool,
+}
+
+impl TextFeatures {
+    fn of(text: &str) -> TextFeatures {
+        TextFeatures {
+            characters: text.chars().count() as u64,
+            has_emoji: text.chars().any(is_emoji),
+            is_ascii_art: is_ascii_art(text),
+        }
+    }
+}
+
+///Divide el texto en palabras en minúsculas: secuencias de letras, números y apóstrofes internos. Se descartan
+/// las palabras sin letras, como los números o los puntajes del estilo '10/10'.
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| word.chars().any(char::is_alphabetic))
+        .map(str::to_lowercase)
+}
+
+///Indica si el caracter pertenece a alguno de los bloques Unicode de emojis y pictogramas.
+pub fn is_emoji(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B50 | 0x2B55 | 0x2B1B | 0x2B1C
+    )
+}
+
+///Indica si el texto contiene un dibujo: al menos ASCII_ART_MIN_LINES líneas formadas mayormente por símbolos,
+/// como los dibujos hechos con caracteres de puntuación, de cajas o de Braille que se ven en las reseñas.
+/// Las líneas que repiten un único símbolo, como los separadores '-----', no se consideran parte de un dibujo.
+pub fn is_ascii_art(text: &str) -> bool {
+    text.lines()
+        .filter(|line| is_drawing_line(line))
+        .nth(ASCII_ART_MIN_LINES - 1)
+        .is_some()
+}
+
+fn is_drawing_line(line: &str) -> bool {
+    let visible = line.chars().filter(|c| !c.is_whitespace()).count();
+    let mut symbols = line
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_alphanumeric());
+    let Some(first) = symbols.next() else {
+        return false;
+    };
+    let mut count = 1;
+    let mut varied = false;
+    for symbol in symbols {
+        count += 1;
+        varied |= symbol != first;
+    }
+    visible >= ASCII_ART_MIN_LINE_CHARS
+        && varied
+        && count as f64 >= ASCII_ART_SYMBOL_RATIO * visible as f64
+}
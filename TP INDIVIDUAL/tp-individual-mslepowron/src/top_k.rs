@@ -0,0 +1,76 @@
+//! Módulo con una estructura acotada para conservar únicamente los 'k' mejores elementos vistos.
+//! Permite agregar elementos de a uno y fusionar dos instancias sin que la memoria crezca con la cantidad de datos.
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+///Estructura que conserva los 'capacity' elementos más grandes según su orden.
+/// Internamente es un heap de mínimos, de forma que el peor elemento conservado se descarta en O(log k)
+/// cuando llega uno mejor.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopK<T: Ord> {
+    capacity: usize,
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopK<T> {
+    ///Crea una nueva instancia vacía que conservará como máximo 'capacity' elementos.
+    /// El heap crece a medida que se agregan elementos, para no reservar memoria según un 'k' elegido por el usuario.
+    pub fn new(capacity: usize) -> TopK<T> {
+        TopK {
+            capacity,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    ///Indica si el elemento sería conservado en caso de agregarse. Permite evitar construir o clonar
+    /// elementos que van a ser descartados.
+    pub fn accepts(&self, item: &T) -> bool {
+        if self.heap.len() < self.capacity {
+            return true;
+        }
+        match self.heap.peek() {
+            Some(Reverse(worst)) => item > worst,
+            None => false,
+        }
+    }
+
+    ///Agrega un elemento, descartando el peor conservado si se supera la capacidad.
+    pub fn push(&mut self, item: T) {
+        if !self.accepts(&item) {
+            return;
+        }
+        if self.heap.len() == self.capacity {
+            self.heap.pop();
+        }
+        self.heap.push(Reverse(item));
+    }
+
+    ///Fusiona los elementos de otra instancia, conservando los mejores de ambas.
+    pub fn merge(&mut self, other: TopK<T>) {
+        for Reverse(item) in other.heap {
+            self.push(item);
+        }
+    }
+
+    ///Devuelve el mejor elemento conservado, si existe.
+    pub fn best(&self) -> Option<&T> {
+        self.heap.iter().map(|Reverse(item)| item).max()
+    }
+
+    ///Devuelve referencias a los elementos conservados ordenadas de mejor a peor, sin consumir la estructura.
+    pub fn sorted(&self) -> Vec<&T> {
+        let mut items: Vec<&T> = self.heap.iter().map(|Reverse(item)| item).collect();
+        items.sort_by(|a, b| b.cmp(a));
+        items
+    }
+
+    ///Devuelve los elementos conservados ordenados de mejor a peor.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect()
+    }
+}
@@ -1,23 +1,69 @@
 //! Módulo para manejar los datos estadísticos las reseñas mejores puntuadas por los usuarios.
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 //Estructura para almacenar los datos de uan reseña escrita por un usuario acerca de un juego.
 /// ###  text
 /// Se almacena el texto escrito por el usuario.
 /// ###  votes_helpful
 /// Se almacenan la cantidad de votos (positivos) que recibió la reseña por parte de otros usuarios.
+/// ###  recommended
+/// Se almacena si el usuario recomienda el juego, en caso de estar informado.
+/// ###  score
+/// Se almacena el puntaje según el criterio elegido en la configuración, o None si se usan los votos útiles.
 pub struct TopReview {
     pub text: String,
     pub votes_helpful: u32,
+    pub recommended: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
 }
 
 impl TopReview {
     ///Función para crea runa nueva instancia de TopReview
-    pub fn new(user_review: String, votes_helpful: u32) -> TopReview {
+    pub fn new(
+        user_review: String,
+        votes_helpful: u32,
+        recommended: Option<bool>,
+        score: Option<f64>,
+    ) -> TopReview {
         TopReview {
             text: user_review,
             votes_helpful,
+            recommended,
+            score,
         }
     }
+
+    ///Devuelve el puntaje con el que se ordena la reseña: el del criterio elegido o, si no hay, los votos útiles.
+    pub fn ranking_score(&self) -> f64 {
+        self.score.unwrap_or(f64::from(self.votes_helpful))
+    }
+}
+
+///Las reseñas se ordenan por puntaje; ante igual puntaje se desempata por cantidad de votos útiles y luego por
+/// el texto para que el resultado no dependa del orden en que se procesaron ni de la cantidad de hilos.
+impl Ord for TopReview {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ranking_score()
+            .total_cmp(&other.ranking_score())
+            .then_with(|| self.votes_helpful.cmp(&other.votes_helpful))
+            .then_with(|| other.text.cmp(&self.text))
+            .then_with(|| self.recommended.cmp(&other.recommended))
+    }
+}
+
+impl PartialEq for TopReview {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TopReview {}
+
+impl PartialOrd for TopReview {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
@@ -0,0 +1,168 @@
+extern crate tp1_mslepowron;
+
+use csv::{ReaderBuilder, StringRecord};
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use tp1_mslepowron::chunking::{open_range, plan_chunks};
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::processor::process_with_threads;
+
+fn write_csv(name: &str, rows: usize) -> PathBuf {
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..rows {
+        content.push_str(&format!(
+            "Juego {},english,\"reseña {}\nen varias líneas, con \"\"comillas\"\"\n\",{}\n",
+            i % 7,
+            i,
+            i
+        ));
+    }
+    let path = std::env::temp_dir().join(format!("{}_{}.csv", name, std::process::id()));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn read_records(reader: impl Read, has_headers: bool) -> Vec<StringRecord> {
+    ReaderBuilder::new()
+        .has_headers(has_headers)
+        .from_reader(reader)
+        .records()
+        .map(|record| record.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_rangos_alineados_a_registros() {
+    let path = write_csv("chunking_alineados", 2000);
+    let plan = plan_chunks(&path, 4096).unwrap().unwrap();
+    assert!(plan.ranges.len() > 1);
+
+    let mut chunked = Vec::new();
+    for range in &plan.ranges {
+        chunked.extend(read_records(open_range(&path, range).unwrap(), false));
+    }
+    let sequential = read_records(fs::File::open(&path).unwrap(), true);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(plan.headers.len(), 4);
+    assert_eq!(chunked.len(), sequential.len());
+    assert!(chunked.iter().eq(sequential.iter()));
+}
+
+#[test]
+fn test_archivo_chico_no_se_divide() {
+    let path = write_csv("chunking_chico", 10);
+    let small = plan_chunks(&path, 1024 * 1024).unwrap();
+    let disabled = plan_chunks(&path, 0).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(small.is_none());
+    assert!(disabled.is_none());
+}
+
+#[test]
+fn test_lineas_que_parecen_registros_dentro_de_comillas() {
+    // Cada reseña tiene adentro más de SYNC_RECORDS líneas con la cantidad de campos del encabezado, que validando
+    // solo los registros siguientes se confundirían con el comienzo de un registro.
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..200 {
+        content.push_str(&format!("Juego {},english,\"", i));
+        for j in 0..20 {
+            content.push_str(&format!("\nJuego falso {},english,no es una fila,{}", j, j));
+        }
+        content.push_str(&format!("\n\",{}\n", i));
+    }
+    let path = std::env::temp_dir().join(format!("chunking_parecidos_{}.csv", std::process::id()));
+    fs::write(&path, content).unwrap();
+
+    let plan = plan_chunks(&path, 2048).unwrap().unwrap();
+    let mut chunked = Vec::new();
+    for range in &plan.ranges {
+        chunked.extend(read_records(open_range(&path, range).unwrap(), false));
+    }
+    let sequential = read_records(fs::File::open(&path).unwrap(), true);
+    fs::remove_file(&path).unwrap();
+
+    assert!(plan.ranges.len() > 1);
+    assert_eq!(sequential.len(), 200);
+    assert!(chunked.iter().eq(sequential.iter()));
+}
+
+#[test]
+fn test_registros_mas_largos_que_el_rango() {
+    // Varias posiciones nominales caen dentro de la misma reseña y se alinean al mismo registro siguiente.
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..20 {
+        let review = "una línea de la reseña\n".repeat(100);
+        content.push_str(&format!("Juego {},english,\"{}\",{}\n", i, review, i));
+    }
+    let path = std::env::temp_dir().join(format!("chunking_largos_{}.csv", std::process::id()));
+    fs::write(&path, content).unwrap();
+
+    let plan = plan_chunks(&path, 1024).unwrap().unwrap();
+    let mut chunked = Vec::new();
+    for range in &plan.ranges {
+        chunked.extend(read_records(open_range(&path, range).unwrap(), false));
+    }
+    let sequential = read_records(fs::File::open(&path).unwrap(), true);
+    fs::remove_file(&path).unwrap();
+
+    assert!(plan.ranges.iter().all(|range| range.start < range.end));
+    assert_eq!(sequential.len(), 20);
+    assert!(chunked.iter().eq(sequential.iter()));
+}
+
+#[test]
+fn test_numero_de_linea_de_filas_invalidas_en_rangos() {
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..2000 {
+        let votes = if i % 300 == 7 {
+            "muchos".to_string()
+        } else {
+            i.to_string()
+        };
+        content.push_str(&format!(
+            "Juego {},english,\"reseña {}\nen dos líneas\",{}\n",
+            i % 7,
+            i,
+            votes
+        ));
+    }
+    let path = std::env::temp_dir().join(format!("chunking_lineas_{}.csv", std::process::id()));
+    fs::write(&path, content).unwrap();
+    let input = path.to_str().unwrap();
+
+    let bad_rows = |chunk_size_bytes| {
+        let config = Config {
+            chunk_size_bytes,
+            ..Config::default()
+        };
+        let output = process_with_threads(4, input, &config).unwrap();
+        let report = output.errors().values().next().unwrap();
+        report
+            .rows
+            .iter()
+            .map(|row| (row.line, row.byte))
+            .collect::<Vec<_>>()
+    };
+    let sequential = bad_rows(0);
+    let chunked = bad_rows(4096);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(sequential.len(), 7);
+    assert_eq!(sequential[0].0, Some(16));
+    assert_eq!(chunked, sequential);
+}
+
+#[test]
+fn test_tamanio_de_rango_demasiado_grande() {
+    let args = ["--chunk-size".to_string(), u64::MAX.to_string()];
+    assert!(Config::from_args(&args).is_err());
+
+    let args = ["--chunk-size".to_string(), "2".to_string()];
+    assert_eq!(
+        Config::from_args(&args).unwrap().chunk_size_bytes,
+        2 * 1024 * 1024
+    );
+}
@@ -0,0 +1,84 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::date::{date_from_timestamp, parse_date};
+use tp1_mslepowron::filter::{Field, FieldValue, Filter, Filterable};
+
+struct MockReview {
+    language: String,
+    timestamp_created: i64,
+    recommended: Option<bool>,
+}
+
+impl Filterable for MockReview {
+    fn field_value(&self, field: Field) -> Option<FieldValue<'_>> {
+        match field {
+            Field::Language => Some(FieldValue::Text(&self.language)),
+            Field::TimestampCreated => Some(FieldValue::Number(self.timestamp_created as f64)),
+            Field::Year => Some(FieldValue::Number(
+                date_from_timestamp(self.timestamp_created).0 as f64,
+            )),
+            Field::Recommended => self.recommended.map(FieldValue::Bool),
+            _ => None,
+        }
+    }
+}
+
+fn negative_review_2020() -> MockReview {
+    MockReview {
+        language: "spanish".to_string(),
+        timestamp_created: 1584290964,
+        recommended: Some(false),
+    }
+}
+
+#[test]
+fn test_filtro_combina_condiciones() {
+    let review = negative_review_2020();
+
+    let filter = Filter::parse("recommended == false && year == 2020").unwrap();
+    assert!(filter.matches(&review));
+
+    let filter = Filter::parse("!recommended && (language == \"english\" || year < 2020)").unwrap();
+    assert!(!filter.matches(&review));
+}
+
+#[test]
+fn test_filtro_por_fecha() {
+    let review = negative_review_2020();
+
+    let filter = Filter::parse("timestamp_created >= \"2020-03-01\"").unwrap();
+    assert!(filter.matches(&review));
+
+    let filter = Filter::parse("timestamp_created < '2020-03-15'").unwrap();
+    assert!(!filter.matches(&review));
+}
+
+#[test]
+fn test_filtro_sobre_campo_no_informado_no_se_cumple() {
+    let review = MockReview {
+        recommended: None,
+        ..negative_review_2020()
+    };
+
+    assert!(!Filter::parse("recommended").unwrap().matches(&review));
+    assert!(!Filter::parse("recommended == false")
+        .unwrap()
+        .matches(&review));
+}
+
+#[test]
+fn test_filtro_invalido() {
+    assert!(Filter::parse("campo == 1").is_err());
+    assert!(Filter::parse("year == \"2020\"").is_err());
+    assert!(Filter::parse("(year == 2020").is_err());
+    assert!(Filter::parse("language").is_err());
+}
+
+#[test]
+fn test_conversion_de_fechas() {
+    assert_eq!(date_from_timestamp(0), (1970, 1, 1));
+    assert_eq!(date_from_timestamp(951782400), (2000, 2, 29));
+    assert_eq!(date_from_timestamp(-86400), (1969, 12, 31));
+    assert_eq!(parse_date("2020-03-15"), Some(1584230400));
+    assert_eq!(parse_date("2020-13-01"), None);
+}
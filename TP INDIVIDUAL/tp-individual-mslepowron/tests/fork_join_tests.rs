@@ -2,7 +2,10 @@ extern crate tp1_mslepowron;
 
 use std::time::Instant;
 
-use tp1_mslepowron::processor::fork_join;
+use rayon::ThreadPoolBuilder;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::monitor::Monitor;
+use tp1_mslepowron::processor::{fork_join, process, process_monitored, process_with_threads};
 
 #[test]
 fn test_aumento_de_threads() {
@@ -10,11 +13,21 @@ fn test_aumento_de_threads() {
     let output = "test_threads.json";
 
     let time_1_thread = Instant::now();
-    let _ = fork_join(1, dir_path.to_string(), output.to_string());
+    let _ = fork_join(
+        1,
+        dir_path.to_string(),
+        output.to_string(),
+        &Config::default(),
+    );
     let total_time_one_thread = time_1_thread.elapsed();
 
     let time_3_threads = Instant::now();
-    let _ = fork_join(3, dir_path.to_string(), output.to_string());
+    let _ = fork_join(
+        3,
+        dir_path.to_string(),
+        output.to_string(),
+        &Config::default(),
+    );
     let total_time_3_threads = time_3_threads.elapsed();
 
     assert!(
@@ -23,15 +36,104 @@ fn test_aumento_de_threads() {
     );
 }
 
+#[test]
+fn test_misma_salida_con_distinta_cantidad_de_threads() {
+    let dir_path = "tests/data";
+    let output = std::env::temp_dir().join(format!("test_threads_{}.json", std::process::id()));
+    let output = output.to_str().unwrap();
+
+    let result_one_thread = fork_join(
+        1,
+        dir_path.to_string(),
+        output.to_string(),
+        &Config::default(),
+    );
+    let output_one_thread = std::fs::read_to_string(output).unwrap();
+
+    let result_3_threads = fork_join(
+        3,
+        dir_path.to_string(),
+        output.to_string(),
+        &Config::default(),
+    );
+    let output_3_threads = std::fs::read_to_string(output).unwrap();
+    std::fs::remove_file(output).unwrap();
+
+    assert!(result_one_thread.is_ok() && result_3_threads.is_ok());
+    assert_eq!(output_one_thread, output_3_threads);
+}
+
+#[test]
+fn test_pool_reutilizable() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let config = Config::default();
+
+    let first = process(&pool, "tests/data", &config).unwrap();
+    let second = process(&pool, "tests/data", &config).unwrap();
+    let with_own_pool = process_with_threads(4, "tests/data", &config).unwrap();
+
+    assert!(!first.top_games().is_empty());
+    assert_eq!(first.top_games(), second.top_games());
+    assert_eq!(first.top_games(), with_own_pool.top_games());
+    assert_eq!(first.top_languages(), with_own_pool.top_languages());
+}
+
 #[test]
 fn test_fork_join_directory_not_found() {
     let result = fork_join(
         2,
         "non/existent/dir_path".to_string(),
         "test.json".to_string(),
+        &Config::default(),
     );
     assert!(
         result.is_err(),
         "Debería tirar error si el directorio no existe"
     );
 }
+
+#[test]
+fn test_progreso_y_tiempos() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let monitor = Monitor::new(2, true);
+
+    process_monitored(&pool, "tests/data", &Config::default(), &monitor).unwrap();
+
+    let progress = monitor.progress();
+    let file_size = std::fs::metadata("tests/data/reviews_recortado.csv")
+        .unwrap()
+        .len();
+    assert_eq!(progress.rows, 500);
+    assert_eq!(progress.bytes, file_size);
+    assert_eq!((progress.files_completed, progress.files_total), (1, 1));
+
+    let stats = monitor.stats_json().unwrap();
+    let mapped_rows: u64 = stats["threads"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|thread| thread["rows"].as_u64().unwrap())
+        .sum();
+    assert_eq!(mapped_rows, 500);
+}
+
+#[test]
+fn test_progreso_de_filas_en_lotes() {
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..10_000 {
+        content.push_str(&format!("Juego {},english,reseña,{}\n", i % 5, i));
+    }
+    let path = std::env::temp_dir().join(format!("progreso_lotes_{}.csv", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+    let config = Config {
+        chunk_size_bytes: 0,
+        ..Config::default()
+    };
+    let monitor = Monitor::new(3, false);
+
+    process_monitored(&pool, path.to_str().unwrap(), &config, &monitor).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(monitor.progress().rows, 10_000);
+}
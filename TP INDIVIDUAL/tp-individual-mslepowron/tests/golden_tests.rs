@@ -0,0 +1,259 @@
+//! Pruebas de salida de referencia: se procesan csv de prueba con distintas cantidades de hilos y se compara el
+//! json producido con el esperado.
+//!
+//! Para regenerar los archivos esperados luego de un cambio intencional en la salida:
+//! `UPDATE_GOLDEN=1 cargo test --test golden_tests`.
+extern crate tp1_mslepowron;
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::processor::process_with_threads;
+
+const MAX_THREADS: usize = 4;
+const FLOAT_TOLERANCE: f64 = 1e-9;
+
+///Forma de comparar la salida con la esperada.
+/// ###  Exact
+/// Los objetos deben tener exactamente las mismas claves.
+/// ###  Subset
+/// La salida puede tener claves que la esperada no tiene. Se usa para la salida de la versión original del
+/// programa, anterior a los campos agregados después.
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Exact,
+    Subset,
+}
+
+struct GoldenCase {
+    name: &'static str,
+    input: &'static str,
+    args: &'static [&'static str],
+    chunk_size_bytes: Option<u64>,
+    expected: &'static str,
+    mode: Mode,
+}
+
+const CASES: [GoldenCase; 9] = [
+    GoldenCase {
+        name: "salida original",
+        input: "tests/data",
+        args: &["--padron", "109454"],
+        chunk_size_bytes: None,
+        expected: "test_salida.json",
+        mode: Mode::Subset,
+    },
+    GoldenCase {
+        name: "por defecto",
+        input: "tests/data",
+        args: &[],
+        chunk_size_bytes: None,
+        expected: "tests/golden/default.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "dividido en rangos",
+        input: "tests/data/reviews_recortado.csv",
+        args: &[],
+        chunk_size_bytes: Some(16 * 1024),
+        expected: "tests/golden/default.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "filtro e histograma",
+        input: "tests/data",
+        args: &[
+            "--filter",
+            "recommended && year >= 2018",
+            "--histogram",
+            "year",
+            "--top-games",
+            "5",
+            "--top-languages",
+            "2",
+            "--reviews-per-language",
+            "3",
+        ],
+        chunk_size_bytes: None,
+        expected: "tests/golden/filter_histogram.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "varios archivos con filas inválidas",
+        input: "tests/golden/input/mixed",
+        args: &["--histogram", "month", "--tie-break", "name-desc"],
+        chunk_size_bytes: None,
+        expected: "tests/golden/mixed.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "modo aproximado sin desalojos",
+        input: "tests/golden/input/mixed",
+        args: &["--aggregation", "approximate"],
+        chunk_size_bytes: None,
+        expected: "tests/golden/mixed_approximate.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "estadísticas de texto",
+        input: "tests/data",
+        args: &["--text-stats", "5", "--top-languages", "4"],
+        chunk_size_bytes: None,
+        expected: "tests/golden/text_stats.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "puntaje combinado",
+        input: "tests/data",
+        args: &[
+            "--score",
+            "combined",
+            "--top-languages",
+            "2",
+            "--reviews-per-language",
+            "5",
+        ],
+        chunk_size_bytes: None,
+        expected: "tests/golden/score_combined.json",
+        mode: Mode::Exact,
+    },
+    GoldenCase {
+        name: "agrupamiento",
+        input: "tests/data",
+        args: &[
+            "--group-by",
+            "language,recommended",
+            "--aggregate",
+            "count",
+            "--aggregate",
+            "sum:votes_helpful",
+            "--aggregate",
+            "top:2:weighted_vote_score",
+            "--top-games",
+            "1",
+            "--top-languages",
+            "1",
+        ],
+        chunk_size_bytes: None,
+        expected: "tests/golden/group_by.json",
+        mode: Mode::Exact,
+    },
+];
+
+#[test]
+fn test_salidas_de_referencia() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    for case in &CASES {
+        let args: Vec<String> = case.args.iter().map(|arg| arg.to_string()).collect();
+        let mut config = Config::from_args(&args).unwrap();
+        if let Some(chunk_size_bytes) = case.chunk_size_bytes {
+            config.chunk_size_bytes = chunk_size_bytes;
+        }
+
+        if update && case.mode == Mode::Exact {
+            let output = run(case, &config, 1);
+            let content = serde_json::to_string_pretty(&output).unwrap();
+            std::fs::write(case.expected, content + "\n").unwrap();
+        }
+
+        let expected: Value =
+            serde_json::from_str(&std::fs::read_to_string(case.expected).unwrap()).unwrap();
+        for threads in 1..=MAX_THREADS {
+            let output = run(case, &config, threads);
+            let context = format!("{} ({} hilos)", case.name, threads);
+            assert_matches(&output, &expected, case.mode, &config, &context);
+        }
+    }
+}
+
+fn run(case: &GoldenCase, config: &Config, threads: usize) -> Value {
+    let output = process_with_threads(threads, case.input, config).unwrap();
+    serde_json::to_value(&output).unwrap()
+}
+
+fn assert_matches(actual: &Value, expected: &Value, mode: Mode, config: &Config, path: &str) {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            if mode == Mode::Exact {
+                let actual_keys: Vec<_> = actual.keys().collect();
+                let expected_keys: Vec<_> = expected.keys().collect();
+                assert_eq!(actual_keys, expected_keys, "claves distintas en {}", path);
+            }
+            for (key, expected_value) in expected {
+                let actual_value = actual
+                    .get(key)
+                    .unwrap_or_else(|| panic!("falta la clave '{}' en {}", key, path));
+                let path = format!("{}.{}", path, key);
+                if key == "top_reviews" {
+                    assert_ranking_matches(actual_value, expected_value, config, &path);
+                } else {
+                    assert_matches(actual_value, expected_value, mode, config, &path);
+                }
+            }
+        }
+        (Value::Array(actual), Value::Array(expected)) => {
+            assert_eq!(actual.len(), expected.len(), "largo distinto en {}", path);
+            for (index, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                let path = format!("{}[{}]", path, index);
+                assert_matches(actual, expected, mode, config, &path);
+            }
+        }
+        (Value::Number(actual), Value::Number(expected))
+            if actual.is_f64() || expected.is_f64() =>
+        {
+            let (actual, expected) = (actual.as_f64().unwrap(), expected.as_f64().unwrap());
+            assert!(
+                (actual - expected).abs() <= FLOAT_TOLERANCE,
+                "{} != {} en {}",
+                actual,
+                expected,
+                path
+            );
+        }
+        _ => assert_eq!(actual, expected, "valor distinto en {}", path),
+    }
+}
+
+///Compara un ranking de reseñas sin importar el orden entre reseñas con la misma cantidad de votos.
+/// Los votos deben coincidir posición a posición y las reseñas de cada cantidad de votos deben ser las mismas,
+/// salvo las del último grupo si el ranking está completo, ya que cuáles de las empatadas entran al ranking puede
+/// variar entre versiones del programa.
+fn assert_ranking_matches(actual: &Value, expected: &Value, config: &Config, path: &str) {
+    let votes = |reviews: &Value| -> Vec<u64> {
+        reviews
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|review| review["votes"].as_u64().unwrap())
+            .collect()
+    };
+    let groups = |reviews: &Value| -> BTreeMap<u64, Vec<String>> {
+        let mut groups: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+        for review in reviews.as_array().unwrap() {
+            groups
+                .entry(review["votes"].as_u64().unwrap())
+                .or_default()
+                .push(review["review"].as_str().unwrap().to_string());
+        }
+        groups.values_mut().for_each(|texts| texts.sort());
+        groups
+    };
+
+    let expected_votes = votes(expected);
+    assert_eq!(votes(actual), expected_votes, "votos distintos en {}", path);
+
+    let mut actual_groups = groups(actual);
+    let mut expected_groups = groups(expected);
+    if expected_votes.len() == config.reviews_per_language {
+        if let Some(last) = expected_votes.last() {
+            actual_groups.remove(last);
+            expected_groups.remove(last);
+        }
+    }
+    assert_eq!(
+        actual_groups, expected_groups,
+        "reseñas distintas en {}",
+        path
+    );
+}
@@ -0,0 +1,111 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::filter::{Field, FieldValue, Filterable};
+use tp1_mslepowron::group_by::{Aggregate, GroupBy, GroupedReviews};
+
+struct Row {
+    language: &'static str,
+    year: Option<f64>,
+    votes_helpful: f64,
+}
+
+impl Filterable for Row {
+    fn field_value(&self, field: Field) -> Option<FieldValue<'_>> {
+        match field {
+            Field::Language => Some(FieldValue::Text(self.language)),
+            Field::Year => self.year.map(FieldValue::Number),
+            Field::VotesHelpful => Some(FieldValue::Number(self.votes_helpful)),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_interpretacion_de_agregaciones() {
+    assert_eq!(Aggregate::parse("count").unwrap(), Aggregate::Count);
+    assert_eq!(
+        Aggregate::parse("sum:playtime_forever").unwrap(),
+        Aggregate::Sum(Field::PlaytimeForever)
+    );
+    assert_eq!(
+        Aggregate::parse("top:3:votes_funny").unwrap(),
+        Aggregate::TopK(3, Field::VotesFunny)
+    );
+    assert!(Aggregate::parse("sum:app_name").is_err());
+    assert!(Aggregate::parse("top:tres:votes_helpful").is_err());
+    assert!(Aggregate::parse("avg:votes_helpful").is_err());
+
+    let args: Vec<String> = ["--group-by", "app_name, year"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let group_by = Config::from_args(&args).unwrap().group_by.unwrap();
+    assert_eq!(group_by.keys, vec![Field::AppName, Field::Year]);
+    assert_eq!(group_by.aggregates, vec![Aggregate::Count]);
+    let args = vec!["--aggregate".to_string(), "count".to_string()];
+    assert!(Config::from_args(&args).is_err());
+}
+
+#[test]
+fn test_nombres_de_agregaciones_distintos() {
+    let top_2 = Aggregate::TopK(2, Field::VotesHelpful);
+    let top_5 = Aggregate::TopK(5, Field::VotesHelpful);
+
+    assert_eq!(top_2.name(), "top_2_votes_helpful");
+    assert_ne!(top_2.name(), top_5.name());
+    assert_eq!(
+        Aggregate::Sum(Field::VotesHelpful).name(),
+        "sum_votes_helpful"
+    );
+}
+
+#[test]
+fn test_fusion_de_grupos_parciales() {
+    let group_by = GroupBy::new(
+        "language,year",
+        vec![
+            Aggregate::Count,
+            Aggregate::Sum(Field::VotesHelpful),
+            Aggregate::TopK(1, Field::VotesHelpful),
+        ],
+    )
+    .unwrap();
+    let rows = [
+        ("a", "spanish", Some(2020.0), 3.0),
+        ("b", "english", None, 1.0),
+        ("c", "spanish", Some(2020.0), 7.0),
+        ("d", "spanish", Some(2021.0), 2.0),
+        ("e", "english", None, 4.0),
+    ];
+
+    let mut whole = GroupedReviews::new();
+    let mut first = GroupedReviews::new();
+    let mut second = GroupedReviews::new();
+    for (position, (text, language, year, votes_helpful)) in rows.into_iter().enumerate() {
+        let row = Row {
+            language,
+            year,
+            votes_helpful,
+        };
+        whole.add(&group_by, text, &row);
+        let part = if position % 2 == 0 {
+            &mut first
+        } else {
+            &mut second
+        };
+        part.add(&group_by, text, &row);
+    }
+    second.merge(first);
+
+    let groups = whole.to_json(&group_by);
+    assert_eq!(groups, second.to_json(&group_by));
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[0]["key"]["language"], "english");
+    assert!(groups[0]["key"]["year"].is_null());
+    assert_eq!(groups[0]["count"], 2);
+    assert_eq!(groups[0]["sum_votes_helpful"], 5);
+    assert_eq!(groups[1]["key"]["year"], 2020);
+    assert_eq!(groups[1]["top_1_votes_helpful"][0]["review"], "c");
+    assert_eq!(groups[1]["top_1_votes_helpful"][0]["value"], 7);
+}
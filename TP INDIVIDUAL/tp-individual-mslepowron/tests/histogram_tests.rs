@@ -0,0 +1,59 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::date::timestamp_from_date;
+use tp1_mslepowron::histogram::{Granularity, Histogram};
+use tp1_mslepowron::processor::process_with_threads;
+
+#[test]
+fn test_periodos_por_mes_y_anio() {
+    let timestamp = timestamp_from_date(2020, 12, 31) + 86_399;
+
+    assert_eq!(
+        Granularity::Month.label(Granularity::Month.period(timestamp)),
+        "2020-12"
+    );
+    assert_eq!(
+        Granularity::Year.label(Granularity::Year.period(timestamp)),
+        "2020"
+    );
+    assert_eq!(
+        Granularity::Month.period(timestamp) + 1,
+        Granularity::Month.period(timestamp_from_date(2021, 1, 1))
+    );
+}
+
+#[test]
+fn test_fusion_de_histogramas() {
+    let period = |year, month| Some(Granularity::Month.period(timestamp_from_date(year, month, 1)));
+
+    let mut first = Histogram::new();
+    first.add(period(2019, 5));
+    first.add(period(2020, 1));
+    let mut second = Histogram::new();
+    second.add(period(2020, 1));
+    second.add(None);
+
+    first.merge(second);
+    let json = first.to_json(Granularity::Month);
+
+    assert_eq!(json["2019-05"], 1);
+    assert_eq!(json["2020-01"], 2);
+    assert_eq!(json.as_object().unwrap().len(), 2);
+}
+
+#[test]
+fn test_histograma_suma_las_resenas_del_juego() {
+    let config = Config::from_args(&["--histogram".to_string(), "month".to_string()]).unwrap();
+    let output = process_with_threads(2, "tests/data", &config).unwrap();
+
+    for game in output.top_games() {
+        let total: u64 = game["histogram"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|count| count.as_u64().unwrap())
+            .sum();
+        assert_eq!(total, game["review_count"].as_u64().unwrap());
+    }
+}
@@ -0,0 +1,137 @@
+extern crate tp1_mslepowron;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::input::{collect_inputs, InputSource};
+
+const CONTENT: &str = "app_name,language,review\nJuego,english,reseña\n";
+
+fn input_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("input_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    dir
+}
+
+fn file_names(inputs: &[InputSource], root: &Path) -> Vec<String> {
+    inputs
+        .iter()
+        .map(|input| {
+            let path = input.path().unwrap();
+            path.strip_prefix(root).unwrap().display().to_string()
+        })
+        .collect()
+}
+
+fn read_all(input: &InputSource) -> String {
+    let mut content = String::new();
+    input.open().unwrap().read_to_string(&mut content).unwrap();
+    content
+}
+
+#[test]
+fn test_recorrido_con_patrones() {
+    let dir = input_dir("patrones");
+    for file in [
+        "a.csv",
+        "b.txt",
+        "sub/c.csv",
+        "sub/d.csv.gz",
+        "sub/viejo.csv",
+    ] {
+        fs::write(dir.join(file), CONTENT).unwrap();
+    }
+
+    let default = collect_inputs(dir.to_str().unwrap(), &Config::default()).unwrap();
+    let config = Config {
+        exclude_patterns: vec!["**/viejo*".to_string()],
+        ..Config::default()
+    };
+    let excluded = collect_inputs(dir.to_str().unwrap(), &config).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        file_names(&default, &dir),
+        ["a.csv", "sub/c.csv", "sub/d.csv.gz", "sub/viejo.csv"]
+    );
+    assert_eq!(
+        file_names(&excluded, &dir),
+        ["a.csv", "sub/c.csv", "sub/d.csv.gz"]
+    );
+}
+
+#[test]
+fn test_archivo_indicado_dentro_del_directorio_se_procesa_una_vez() {
+    let dir = input_dir("repetido");
+    fs::write(dir.join("a.csv"), CONTENT).unwrap();
+    fs::write(dir.join("sub/b.csv"), CONTENT).unwrap();
+    fs::write(dir.join("sub/fuera.txt"), CONTENT).unwrap();
+
+    let config = Config {
+        input_files: vec![
+            dir.join("sub/../a.csv").display().to_string(),
+            dir.join("sub/fuera.txt").display().to_string(),
+            dir.join("sub/fuera.txt").display().to_string(),
+        ],
+        ..Config::default()
+    };
+    let inputs = collect_inputs(dir.to_str().unwrap(), &config).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        file_names(&inputs, &dir),
+        ["a.csv", "sub/b.csv", "sub/fuera.txt"]
+    );
+}
+
+#[test]
+fn test_entrada_estandar_y_ruta_inexistente() {
+    let inputs = collect_inputs("-", &Config::default()).unwrap();
+
+    assert_eq!(inputs, [InputSource::Stdin]);
+    assert_eq!(InputSource::Stdin.snapshot_key(), None);
+    assert!(collect_inputs("no/existe", &Config::default()).is_err());
+}
+
+#[test]
+fn test_clave_de_snapshot_canonica() {
+    let dir = input_dir("clave");
+    fs::write(dir.join("a.csv"), CONTENT).unwrap();
+
+    let direct = InputSource::File(dir.join("a.csv")).snapshot_key();
+    let indirect = InputSource::File(dir.join("sub/.././a.csv")).snapshot_key();
+    let missing = InputSource::File(dir.join("b.csv")).snapshot_key();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(direct.is_some());
+    assert_eq!(direct, indirect);
+    assert_eq!(missing, None);
+}
+
+#[test]
+fn test_descompresion_por_contenido() {
+    let dir = input_dir("comprimidos");
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(CONTENT.as_bytes()).unwrap();
+    // La extensión no coincide con el formato: se detecta por los primeros bytes.
+    fs::write(dir.join("gzip.csv"), gzip.finish().unwrap()).unwrap();
+    fs::write(
+        dir.join("zstd.csv"),
+        zstd::encode_all(CONTENT.as_bytes(), 0).unwrap(),
+    )
+    .unwrap();
+    fs::write(dir.join("plano.csv.gz"), CONTENT).unwrap();
+    fs::write(dir.join("corto.csv"), "a").unwrap();
+
+    let contents: Vec<String> = ["gzip.csv", "zstd.csv", "plano.csv.gz", "corto.csv"]
+        .iter()
+        .map(|file| read_all(&InputSource::File(dir.join(file))))
+        .collect();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(contents, [CONTENT, CONTENT, CONTENT, "a"]);
+}
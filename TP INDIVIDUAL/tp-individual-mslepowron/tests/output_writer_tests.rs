@@ -0,0 +1,135 @@
+extern crate tp1_mslepowron;
+
+use csv::Reader;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::output_data::OutputData;
+use tp1_mslepowron::output_writer::OutputFormat;
+use tp1_mslepowron::processor::process_with_threads;
+
+fn output_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+}
+
+fn default_output() -> OutputData {
+    process_with_threads(2, "tests/data", &Config::default()).unwrap()
+}
+
+#[test]
+fn test_json_ida_y_vuelta() {
+    let output = default_output();
+    let path = output_path("salida.json");
+    let path = path.to_str().unwrap();
+
+    let written = output.save_output(path, None).unwrap();
+    let read: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(written, vec![path.to_string()]);
+    // Se compara contra el json parseado para que los flotantes pasen por la misma conversión.
+    let expected: Value = serde_json::from_str(&serde_json::to_string(&output).unwrap()).unwrap();
+    assert_eq!(read, expected);
+    assert_eq!(read["padron"], 109454);
+}
+
+#[test]
+fn test_padron_indicado() {
+    let config = Config::from_args(&["--padron".to_string(), "100000".to_string()]).unwrap();
+    let output = process_with_threads(1, "tests/data", &config).unwrap();
+
+    assert_eq!(output.padron(), Some(100000));
+}
+
+#[test]
+fn test_sin_padron() {
+    let config = Config::from_args(&["--padron".to_string(), "none".to_string()]).unwrap();
+    let output = process_with_threads(1, "tests/data", &config).unwrap();
+    assert_eq!(output.padron(), None);
+
+    let json_path = output_path("sin_padron.json");
+    let ndjson_path = output_path("sin_padron.ndjson");
+    let markdown_path = output_path("sin_padron.md");
+    for path in [&json_path, &ndjson_path, &markdown_path] {
+        output.save_output(path.to_str().unwrap(), None).unwrap();
+    }
+    let json: Value = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    let ndjson = fs::read_to_string(&ndjson_path).unwrap();
+    let markdown = fs::read_to_string(&markdown_path).unwrap();
+    for path in [&json_path, &ndjson_path, &markdown_path] {
+        fs::remove_file(path).unwrap();
+    }
+
+    assert!(json.get("padron").is_none());
+    assert!(ndjson
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .all(|line| line.get("padron").is_none()));
+    assert!(!markdown.contains("Padrón"));
+}
+
+#[test]
+fn test_csv_ida_y_vuelta() {
+    let output = default_output();
+    let path = output_path("salida.csv");
+    let path = path.to_str().unwrap();
+
+    let written = output.save_output(path, None).unwrap();
+    let games_path = written
+        .iter()
+        .find(|written| written.ends_with("_games.csv"))
+        .unwrap();
+    let mut reader = Reader::from_path(games_path).unwrap();
+    let header: Vec<String> = reader.headers().unwrap().iter().map(String::from).collect();
+    let rows: Vec<(String, u64)> = reader
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            (record[0].to_string(), record[1].parse().unwrap())
+        })
+        .collect();
+    let all_exist = written
+        .iter()
+        .all(|written| PathBuf::from(written).exists());
+    for written in &written {
+        fs::remove_file(written).unwrap();
+    }
+
+    assert!(all_exist);
+    assert!(!PathBuf::from(path).exists());
+    assert!(written.iter().all(|written| written != path));
+    assert_eq!(header[..2], ["game", "review_count"]);
+    let expected: Vec<(String, u64)> = output
+        .top_games()
+        .iter()
+        .map(|game| {
+            (
+                game["game"].as_str().unwrap().to_string(),
+                game["review_count"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn test_formato_indicado_escribe_un_archivo() {
+    let output = default_output();
+    let path = output_path("salida.txt");
+    let path = path.to_str().unwrap();
+
+    let written = output
+        .save_output(path, Some(OutputFormat::Ndjson))
+        .unwrap();
+    let lines: Vec<Value> = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(written, vec![path.to_string()]);
+    assert_eq!(lines[0]["type"], "meta");
+    assert_eq!(lines[0]["padron"], 109454);
+}
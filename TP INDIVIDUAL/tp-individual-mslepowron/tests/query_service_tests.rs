@@ -0,0 +1,132 @@
+extern crate tp1_mslepowron;
+
+use rayon::ThreadPoolBuilder;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::monitor::Monitor;
+use tp1_mslepowron::processor::aggregate;
+use tp1_mslepowron::query_service::QueryService;
+
+fn service(input: &str) -> QueryService {
+    let config = Config::default();
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let monitor = Monitor::new(2, false);
+    let result = aggregate(&pool, input, &config, &monitor).unwrap();
+    QueryService::new(result, config)
+}
+
+#[test]
+fn test_consultas() {
+    let service = service("tests/golden/input/mixed");
+
+    let games = service.query("/games?top=2&language=spanish").body;
+    let names: Vec<&str> = games
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|game| game["game"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Celeste", "Hollow Knight"]);
+    assert_eq!(games[0]["language_review_count"], 1);
+
+    let languages = service.query("/languages?game=Hollow+Knight&top=1").body;
+    assert_eq!(languages.as_array().unwrap().len(), 1);
+    assert_eq!(languages[0]["language"], "english");
+    assert_eq!(languages[0]["review_count"], 3);
+
+    let game = service.query("/game?name=Stardew%20Valley").body;
+    assert_eq!(game["review_count"], 3);
+
+    let report = service.query("/").body;
+    assert_eq!(report["top_games"][0]["game"], "Hollow Knight");
+
+    assert_eq!(service.query("/game?name=Portal").status, 404);
+    assert_eq!(service.query("/games?top=muchos").status, 400);
+    assert_eq!(service.query("/games?idioma=spanish").status, 400);
+    assert_eq!(service.query("/juegos").status, 404);
+}
+
+#[test]
+fn test_servicio_http() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let service = service("tests/golden/input/mixed");
+    thread::spawn(move || service.serve_on(listener));
+
+    let request = |request: &str| -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let response = request("GET /languages?top=1&reviews=2 HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    let languages: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(languages[0]["language"], "english");
+    assert_eq!(languages[0]["top_reviews"].as_array().unwrap().len(), 2);
+
+    let response = request("POST /games HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 405"));
+}
+
+#[test]
+fn test_servicio_desde_snapshot() {
+    let snapshot = std::env::temp_dir().join(format!("consultas_{}.json", std::process::id()));
+    let snapshot = snapshot.to_str().unwrap().to_string();
+    let config = Config {
+        snapshot_path: Some(snapshot.clone()),
+        ..Config::default()
+    };
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let monitor = Monitor::new(2, false);
+    aggregate(&pool, "tests/golden/input/mixed", &config, &monitor).unwrap();
+
+    let from_snapshot = QueryService::from_snapshot(&snapshot, Config::default()).unwrap();
+    let other_options = Config {
+        reviews_per_language: 1,
+        ..Config::default()
+    };
+    let mismatch = QueryService::from_snapshot(&snapshot, other_options);
+    std::fs::remove_file(&snapshot).unwrap();
+
+    let processed = service("tests/golden/input/mixed");
+    assert_eq!(from_snapshot.query("/").body, processed.query("/").body);
+    assert!(mismatch.is_err());
+    assert!(QueryService::from_snapshot(&snapshot, Config::default()).is_err());
+}
+
+#[test]
+fn test_conexiones_inactivas_acotadas() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let service = service("tests/golden/input/mixed");
+    thread::spawn(move || service.serve_on(listener));
+
+    // Conexiones que no envían ninguna consulta y ocupan todos los hilos del servicio.
+    let idle: Vec<TcpStream> = (0..16)
+        .map(|_| TcpStream::connect(address).unwrap())
+        .collect();
+    thread::sleep(Duration::from_millis(200));
+
+    let start = Instant::now();
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(b"GET /games HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let waited = start.elapsed();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(waited >= Duration::from_secs(4));
+    for mut stream in idle {
+        let mut closed = String::new();
+        stream.read_to_string(&mut closed).unwrap();
+        assert!(closed.is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Pruebas de propiedades de ReviewResult::reduce: la salida no debe depender de cómo se agrupan ni en qué orden
+//! se fusionan los resultados parciales de los hilos.
+extern crate tp1_mslepowron;
+
+use proptest::prelude::*;
+use serde_json::Value;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::histogram::Granularity;
+use tp1_mslepowron::review_result::ReviewResult;
+use tp1_mslepowron::top_review::TopReview;
+
+const GAMES: [&str; 4] = ["Portal 2", "Celeste", "Terraria", "Hades"];
+const LANGUAGES: [&str; 3] = ["english", "spanish", "french"];
+const TEXTS: [&str; 5] = ["bueno", "malo", "excelente", "aburrido", "10/10"];
+
+///Fila generada: una reseña o, si 'bad_row' es verdadero, una fila inválida del archivo 'game'.
+#[derive(Debug, Clone)]
+struct Row {
+    game: usize,
+    language: usize,
+    text: usize,
+    votes: u32,
+    recommended: Option<bool>,
+    period: Option<i64>,
+    bad_row: bool,
+}
+
+fn row() -> impl Strategy<Value = Row> {
+    (
+        0..GAMES.len(),
+        0..LANGUAGES.len(),
+        0..TEXTS.len(),
+        0..4u32,
+        prop::option::of(any::<bool>()),
+        prop::option::of(24_000..24_004i64),
+        prop::bool::weighted(0.1),
+    )
+        .prop_map(
+            |(game, language, text, votes, recommended, period, bad_row)| Row {
+                game,
+                language,
+                text,
+                votes,
+                recommended,
+                period,
+                bad_row,
+            },
+        )
+}
+
+fn config() -> Config {
+    let mut config = Config {
+        top_games: GAMES.len(),
+        top_languages: LANGUAGES.len(),
+        languages_per_game: LANGUAGES.len(),
+        reviews_per_language: 2,
+        ..Config::default()
+    };
+    config.histogram = Some(Granularity::Month);
+    config.top_words = Some(3);
+    config
+}
+
+///Acumula las filas en un resultado parcial, como lo haría un hilo que procesa las filas a partir de 'first_line'.
+fn build(rows: &[Row], first_line: usize, config: &Config) -> ReviewResult {
+    let mut result = ReviewResult::new();
+    for (line, row) in (first_line..).zip(rows) {
+        if row.bad_row {
+            result.add_bad_row(
+                GAMES[row.game],
+                Some(line as u64),
+                None,
+                "fila inválida".to_string(),
+            );
+            continue;
+        }
+        let review = TopReview::new(
+            TEXTS[row.text].to_string(),
+            row.votes,
+            row.recommended,
+            None,
+        );
+        result.add_review(
+            GAMES[row.game].to_string(),
+            LANGUAGES[row.language].to_string(),
+            review,
+            row.period,
+            None,
+            config,
+        );
+    }
+    result
+}
+
+fn output(result: ReviewResult, config: &Config) -> Value {
+    serde_json::to_value(result.get_top_results(config)).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn reduce_es_conmutativo(a in prop::collection::vec(row(), 0..40), b in prop::collection::vec(row(), 0..40)) {
+        let config = config();
+        let ab = build(&a, 0, &config).reduce(build(&b, 0, &config));
+        let ba = build(&b, 0, &config).reduce(build(&a, 0, &config));
+        prop_assert_eq!(output(ab, &config), output(ba, &config));
+    }
+
+    #[test]
+    fn reduce_es_asociativo(
+        a in prop::collection::vec(row(), 0..30),
+        b in prop::collection::vec(row(), 0..30),
+        c in prop::collection::vec(row(), 0..30),
+    ) {
+        let config = config();
+        let left = build(&a, 0, &config).reduce(build(&b, 0, &config)).reduce(build(&c, 0, &config));
+        let right = build(&a, 0, &config).reduce(build(&b, 0, &config).reduce(build(&c, 0, &config)));
+        prop_assert_eq!(output(left, &config), output(right, &config));
+    }
+
+    #[test]
+    fn reduce_equivale_a_procesar_todo_junto(rows in prop::collection::vec(row(), 0..60), split in 0..60usize) {
+        let config = config();
+        let split = split.min(rows.len());
+        let reduced = build(&rows[..split], 0, &config).reduce(build(&rows[split..], split, &config));
+        let whole = build(&rows, 0, &config);
+        prop_assert_eq!(output(reduced, &config), output(whole, &config));
+    }
+}
@@ -0,0 +1,53 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::processor::process_with_threads;
+use tp1_mslepowron::score::{ScoreInputs, ScoreStrategy};
+use tp1_mslepowron::top_k::TopK;
+use tp1_mslepowron::top_review::TopReview;
+
+#[test]
+fn test_criterios_de_puntaje() {
+    let inputs = ScoreInputs {
+        votes_helpful: 10,
+        votes_funny: Some(4),
+        weighted_vote_score: Some(0.5),
+        timestamp_created: None,
+    };
+    assert_eq!(ScoreStrategy::VotesHelpful.score(&inputs), None);
+    assert_eq!(ScoreStrategy::VotesFunny.score(&inputs), Some(4.0));
+    assert_eq!(ScoreStrategy::WeightedVoteScore.score(&inputs), Some(0.5));
+    assert_eq!(ScoreStrategy::Combined.score(&inputs), Some(12.0));
+    assert_eq!(ScoreStrategy::Recency.score(&inputs), Some(0.0));
+    assert!(ScoreStrategy::parse("votes").is_err());
+}
+
+#[test]
+fn test_desempate_por_votos_y_texto() {
+    let mut top = TopK::new(3);
+    top.push(TopReview::new("b".to_string(), 1, None, Some(2.0)));
+    top.push(TopReview::new("c".to_string(), 9, None, Some(1.0)));
+    top.push(TopReview::new("a".to_string(), 1, None, Some(2.0)));
+    top.push(TopReview::new("d".to_string(), 5, None, Some(2.0)));
+
+    let texts: Vec<String> = top
+        .into_sorted_vec()
+        .into_iter()
+        .map(|review| review.text)
+        .collect();
+    assert_eq!(texts, vec!["d", "a", "b"]);
+}
+
+#[test]
+fn test_puntaje_estable_con_distintos_hilos() {
+    for score in ["votes-funny", "weighted-vote-score", "recency"] {
+        let args = vec!["--score".to_string(), score.to_string()];
+        let config = Config::from_args(&args).unwrap();
+        let expected =
+            serde_json::to_value(process_with_threads(1, "tests/data", &config).unwrap()).unwrap();
+        for threads in 2..=4 {
+            let output = process_with_threads(threads, "tests/data", &config).unwrap();
+            assert_eq!(serde_json::to_value(output).unwrap(), expected, "{}", score);
+        }
+    }
+}
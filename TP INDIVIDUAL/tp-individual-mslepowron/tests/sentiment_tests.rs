@@ -0,0 +1,35 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::sentiment::Sentiment;
+use tp1_mslepowron::top_review::TopReview;
+
+fn review(text: &str, votes: u32, recommended: Option<bool>) -> TopReview {
+    TopReview::new(text.to_string(), votes, recommended, None)
+}
+
+#[test]
+fn test_proporcion_de_recomendaciones() {
+    let mut sentiment = Sentiment::new();
+    assert_eq!(sentiment.recommended_ratio(), None);
+
+    sentiment.add_review(&review("bueno", 3, Some(true)));
+    sentiment.add_review(&review("excelente", 1, Some(true)));
+    sentiment.add_review(&review("divertido", 2, Some(true)));
+    sentiment.add_review(&review("malo", 1, Some(false)));
+    assert_eq!(sentiment.recommended_ratio(), Some(0.75));
+    assert_eq!(
+        sentiment.top_positive.map(|top| top.text),
+        Some("bueno".to_string())
+    );
+}
+
+#[test]
+fn test_no_contabiliza_reseñas_sin_recomendacion() {
+    let mut sentiment = Sentiment::new();
+    sentiment.add_review(&review("aburrido", 9, None));
+    assert_eq!(sentiment.recommended, 0);
+    assert_eq!(sentiment.not_recommended, 0);
+    assert!(sentiment.top_positive.is_none());
+    assert!(sentiment.top_negative.is_none());
+    assert_eq!(sentiment.recommended_ratio(), None);
+}
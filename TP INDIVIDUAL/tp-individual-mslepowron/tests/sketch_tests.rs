@@ -0,0 +1,73 @@
+extern crate tp1_mslepowron;
+
+use std::collections::HashMap;
+use tp1_mslepowron::hyperloglog::{HyperLogLog, RELATIVE_STANDARD_ERROR};
+use tp1_mslepowron::space_saving::SpaceSaving;
+
+///Flujo con pocos elementos frecuentes y muchos que aparecen una sola vez.
+fn stream(seed: usize) -> Vec<String> {
+    let mut items = Vec::new();
+    for i in 0..2000 {
+        items.push(format!("frecuente {}", i % 4));
+        items.push(format!("raro {} {}", seed, i));
+    }
+    items
+}
+
+#[test]
+fn test_space_saving_respeta_las_cotas_al_fusionar() {
+    let mut exact: HashMap<String, u64> = HashMap::new();
+    let mut partials = Vec::new();
+    for seed in 0..4 {
+        let mut sketch: SpaceSaving<()> = SpaceSaving::new(20);
+        for item in stream(seed) {
+            *exact.entry(item.clone()).or_insert(0) += 1;
+            sketch.add(&item);
+        }
+        partials.push(sketch);
+    }
+
+    let mut merged = partials.pop().unwrap();
+    for partial in partials {
+        merged.merge(partial, |_, _| {});
+    }
+
+    assert_eq!(merged.total(), 16000);
+    let entries = merged.into_entries();
+    for i in 0..4 {
+        let key = format!("frecuente {}", i);
+        assert!(entries.iter().any(|(name, _)| *name == key));
+    }
+    for (key, counter) in entries {
+        let real = exact[&key];
+        assert!(counter.count >= real);
+        assert!(counter.count - counter.error <= real);
+        assert!(counter.error <= 16000 / 20);
+    }
+}
+
+#[test]
+fn test_hyperloglog_estima_dentro_del_error() {
+    let mut first = HyperLogLog::new();
+    let mut second = HyperLogLog::new();
+    for author in 0..60_000u64 {
+        first.add(76_561_197_960_265_728 + author);
+    }
+    for author in 40_000..100_000u64 {
+        second.add(76_561_197_960_265_728 + author);
+    }
+    first.merge(second);
+
+    let estimate = first.estimate() as f64;
+    let relative_error = (estimate - 100_000.0).abs() / 100_000.0;
+    assert!(relative_error < 3.0 * RELATIVE_STANDARD_ERROR);
+}
+
+#[test]
+fn test_hyperloglog_cantidades_chicas() {
+    let mut authors = HyperLogLog::new();
+    for author in [7, 7, 8, 9, 9, 9] {
+        authors.add(author);
+    }
+    assert_eq!(authors.estimate(), 3);
+}
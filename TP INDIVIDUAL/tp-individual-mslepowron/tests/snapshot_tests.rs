@@ -0,0 +1,154 @@
+extern crate tp1_mslepowron;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::monitor::Monitor;
+use tp1_mslepowron::processor::process_monitored;
+
+struct Run {
+    review_counts: BTreeMap<String, u64>,
+    rows_read: u64,
+}
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("snapshot_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data")).unwrap();
+    dir
+}
+
+fn write_reviews(path: &Path, game: &str, reviews: usize) {
+    let mut content = String::from("app_name,language,review,votes_helpful\n");
+    for i in 0..reviews {
+        content.push_str(&format!("{},english,reseña {},{}\n", game, i, i));
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn run(pool: &ThreadPool, input: &Path, snapshot: &Path) -> Run {
+    let config = Config {
+        snapshot_path: Some(snapshot.display().to_string()),
+        ..Config::default()
+    };
+    let monitor = Monitor::new(pool.current_num_threads(), false);
+    let output = process_monitored(pool, input.to_str().unwrap(), &config, &monitor).unwrap();
+    let review_counts = output
+        .top_games()
+        .iter()
+        .map(|game| {
+            (
+                game["game"].as_str().unwrap().to_string(),
+                game["review_count"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    Run {
+        review_counts,
+        rows_read: monitor.progress().rows,
+    }
+}
+
+fn counts(entries: &[(&str, u64)]) -> BTreeMap<String, u64> {
+    entries
+        .iter()
+        .map(|(game, count)| (game.to_string(), *count))
+        .collect()
+}
+
+#[test]
+fn test_archivo_sin_cambios_no_se_reprocesa() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let dir = data_dir("sin_cambios");
+    let snapshot = dir.join("snapshot.json");
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 4);
+
+    let first = run(&pool, &dir.join("data"), &snapshot);
+    let second = run(&pool, &dir.join("data"), &snapshot);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(first.rows_read, 4);
+    assert_eq!(second.rows_read, 0);
+    assert_eq!(first.review_counts, counts(&[("Juego A", 4)]));
+    assert_eq!(second.review_counts, first.review_counts);
+}
+
+#[test]
+fn test_archivo_modificado_reemplaza_su_resultado() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let dir = data_dir("modificado");
+    let snapshot = dir.join("snapshot.json");
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 2);
+    write_reviews(&dir.join("data/b.csv"), "Juego B", 3);
+    run(&pool, &dir.join("data"), &snapshot);
+
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 5);
+    let second = run(&pool, &dir.join("data"), &snapshot);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(second.rows_read, 5);
+    assert_eq!(
+        second.review_counts,
+        counts(&[("Juego A", 5), ("Juego B", 3)])
+    );
+}
+
+#[test]
+fn test_archivo_nuevo_se_fusiona_con_el_snapshot() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let dir = data_dir("nuevo");
+    let snapshot = dir.join("snapshot.json");
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 2);
+    run(&pool, &dir.join("data"), &snapshot);
+
+    write_reviews(&dir.join("data/b.csv"), "Juego B", 3);
+    let second = run(&pool, &dir.join("data"), &snapshot);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(second.rows_read, 3);
+    assert_eq!(
+        second.review_counts,
+        counts(&[("Juego A", 2), ("Juego B", 3)])
+    );
+}
+
+#[test]
+fn test_archivo_borrado_se_descarta_del_snapshot() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let dir = data_dir("borrado");
+    let snapshot = dir.join("snapshot.json");
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 2);
+    write_reviews(&dir.join("data/b.csv"), "Juego B", 3);
+    run(&pool, &dir.join("data"), &snapshot);
+
+    fs::remove_file(dir.join("data/b.csv")).unwrap();
+    let second = run(&pool, &dir.join("data"), &snapshot);
+    write_reviews(&dir.join("data/b.csv"), "Juego B", 1);
+    let third = run(&pool, &dir.join("data"), &snapshot);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(second.rows_read, 0);
+    assert_eq!(second.review_counts, counts(&[("Juego A", 2)]));
+    assert_eq!(third.rows_read, 1);
+    assert_eq!(
+        third.review_counts,
+        counts(&[("Juego A", 2), ("Juego B", 1)])
+    );
+}
+
+#[test]
+fn test_misma_ruta_escrita_distinto_no_se_cuenta_dos_veces() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let dir = data_dir("misma_ruta");
+    let snapshot = dir.join("snapshot.json");
+    write_reviews(&dir.join("data/a.csv"), "Juego A", 2);
+    run(&pool, &dir.join("data"), &snapshot);
+
+    let second = run(&pool, &dir.join("data/../data/."), &snapshot);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(second.rows_read, 0);
+    assert_eq!(second.review_counts, counts(&[("Juego A", 2)]));
+}
@@ -0,0 +1,90 @@
+extern crate tp1_mslepowron;
+
+use serde_json::json;
+use tp1_mslepowron::config::Config;
+use tp1_mslepowron::processor::process_with_threads;
+use tp1_mslepowron::text_stats::{is_ascii_art, is_emoji, words, StopWords, TextAnalysis};
+
+#[test]
+fn test_palabras_y_palabras_vacias() {
+    let found: Vec<String> = words("Don't STOP... 10/10 ¡Qué juego!").collect();
+    assert_eq!(found, vec!["don't", "stop", "qué", "juego"]);
+
+    let mut stop_words = StopWords::default();
+    assert!(stop_words.contains("spanish", "qué"));
+    assert!(!stop_words.contains("spanish", "juego"));
+    assert!(!stop_words.contains("german", "und"));
+
+    let path = std::env::temp_dir().join("test_palabras_vacias_german.txt");
+    std::fs::write(&path, "Und\n\nder\n").unwrap();
+    stop_words
+        .load(&format!("german={}", path.display()))
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(stop_words.contains("german", "und"));
+    assert!(stop_words.load("german").is_err());
+}
+
+#[test]
+fn test_emojis_y_arte_ascii() {
+    assert!(is_emoji('😀'));
+    assert!(is_emoji('❤'));
+    assert!(!is_emoji('a'));
+    assert!(!is_emoji('ñ'));
+
+    let drawing = "⡴⠑⡄⠀⠀⠀⠀⠀⠀⠀⣀⣀⣤\n⠸⡇⠀⠿⡀⠀⠀⠀⣀⡴⢿⣿⣿\n⠀⠀⠀⠀⠑⢄⣠⠾⠁⣀⣄⡈⠙ ta bueno";
+    assert!(is_ascii_art(drawing));
+    let separators = "uno\n----------\ndos\n----------\ntres\n----------";
+    assert!(!is_ascii_art(separators));
+    assert!(!is_ascii_art("Muy bueno!!! 10/10\nlo recomiendo"));
+}
+
+#[test]
+fn test_estadisticas_de_texto_en_la_salida() {
+    let args = vec!["--text-stats".to_string(), "2".to_string()];
+    let config = Config::from_args(&args).unwrap();
+    let output = process_with_threads(2, "tests/golden/input/mixed", &config).unwrap();
+
+    let text_stats = output.text_stats().unwrap();
+    let english = &text_stats["languages"][0];
+    assert_eq!(english["language"], "english");
+    assert_eq!(english["review_count"], 7);
+    assert!((english["average_length"].as_f64().unwrap() - 96.0 / 7.0).abs() < 1e-9);
+    assert_eq!(english["top_words"][0]["word"], "hard");
+    assert_eq!(english["top_words"][0]["count"], 2);
+    assert_eq!(english["top_words"][1]["word"], "atmosphere");
+    assert_eq!(text_stats["games"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_palabras_acotadas_por_idioma() {
+    let config = Config::from_args(&["--text-stats".to_string(), "1".to_string()]).unwrap();
+    let stop_words = StopWords::default();
+    let mut first = TextAnalysis::new(1);
+    let mut second = TextAnalysis::new(1);
+    for i in 0..20_000 {
+        let analysis = if i % 2 == 0 { &mut first } else { &mut second };
+        let text = format!("palabra{} frecuente", i);
+        analysis.add_review(None, "german", &text, &stop_words);
+    }
+    first.merge(second);
+
+    let counters = serde_json::to_value(&first).unwrap()["languages"]["german"]["words"]["slots"]
+        .as_array()
+        .unwrap()
+        .len();
+    let languages = [json!({ "language": "german" })];
+    let text_stats = first.to_json(&[], &languages, &config);
+
+    assert_eq!(counters, 5000);
+    assert_eq!(
+        text_stats["languages"][0]["top_words"][0]["word"],
+        "frecuente"
+    );
+    assert!(
+        text_stats["languages"][0]["top_words"][0]["count"]
+            .as_u64()
+            .unwrap()
+            >= 20_000
+    );
+}
@@ -0,0 +1,81 @@
+extern crate tp1_mslepowron;
+
+use tp1_mslepowron::top_k::TopK;
+use tp1_mslepowron::top_review::TopReview;
+
+fn review(text: &str, votes_helpful: u32) -> TopReview {
+    TopReview::new(text.to_string(), votes_helpful, None, None)
+}
+
+fn texts(top: TopK<TopReview>) -> Vec<String> {
+    top.into_sorted_vec()
+        .into_iter()
+        .map(|review| review.text)
+        .collect()
+}
+
+#[test]
+fn test_conserva_como_maximo_la_capacidad() {
+    let mut top = TopK::new(3);
+    for i in 0..100 {
+        top.push(i);
+    }
+    assert_eq!(top.sorted(), vec![&99, &98, &97]);
+    assert_eq!(top.best(), Some(&99));
+    assert!(!top.accepts(&50));
+    assert!(top.accepts(&100));
+}
+
+#[test]
+fn test_capacidad_cero_no_conserva_elementos() {
+    let mut top = TopK::new(0);
+    assert!(!top.accepts(&1));
+    top.push(1);
+    assert_eq!(top.best(), None);
+    assert!(top.into_sorted_vec().is_empty());
+}
+
+#[test]
+fn test_un_empate_con_el_peor_conservado_no_lo_reemplaza() {
+    let mut top = TopK::new(2);
+    top.push((5, 'a'));
+    top.push((3, 'b'));
+    assert!(!top.accepts(&(3, 'b')));
+    top.push((3, 'b'));
+    assert_eq!(top.into_sorted_vec(), vec![(5, 'a'), (3, 'b')]);
+}
+
+#[test]
+fn test_el_desempate_no_depende_del_orden_de_insercion() {
+    let reviews = [
+        review("b", 4),
+        review("a", 4),
+        review("c", 4),
+        review("d", 9),
+    ];
+
+    let mut forward = TopK::new(3);
+    for r in reviews.iter().cloned() {
+        forward.push(r);
+    }
+    let mut backward = TopK::new(3);
+    for r in reviews.iter().rev().cloned() {
+        backward.push(r);
+    }
+
+    // ante igual cantidad de votos gana el texto menor
+    assert_eq!(texts(forward), vec!["d", "a", "b"]);
+    assert_eq!(texts(backward), vec!["d", "a", "b"]);
+}
+
+#[test]
+fn test_fusionar_respeta_la_capacidad() {
+    let mut left = TopK::new(3);
+    let mut right = TopK::new(3);
+    for i in 0..5 {
+        left.push(i * 2);
+        right.push(i * 2 + 1);
+    }
+    left.merge(right);
+    assert_eq!(left.into_sorted_vec(), vec![9, 8, 7]);
+}